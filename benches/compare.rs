@@ -0,0 +1,424 @@
+use std::{
+    env, fs,
+    path::Path,
+    process::Command,
+    time::{Duration, Instant},
+};
+
+use advent_of_code_2023::runner::PartReport;
+use anyhow::{bail, Context, Result};
+use tempfile::TempDir;
+
+// Compares the alternative implementations of some days on generated inputs, e.g.
+// `cargo bench --bench compare -- day01`. Arguments that don't start with `-` filter the
+// comparisons by name. Comparisons of the parallel implementations need `--features parallel`.
+// Every variant runs in a fresh day binary with `--machine-readable`, so the reported times are
+// the ones the runner measured around the solver and neither process startup nor input generation
+// is included. We report the median of a few runs and check that all variants agree on the
+// answer.
+
+const RUNS: usize = 5;
+
+/// One way of running a day, `args` are passed to the binary in addition to the input and part
+/// and `env` is added to its environment
+struct Variant {
+    label: &'static str,
+    args: &'static [&'static str],
+    env: &'static [(&'static str, &'static str)],
+}
+
+struct Comparison {
+    name: &'static str,
+    binary: &'static str,
+    part: u8,
+    generate: fn(&mut Rng) -> String,
+    variants: &'static [Variant],
+}
+
+/// Small xorshift generator, the inputs only need to be reproducible, not random
+struct Rng(u64);
+
+impl Rng {
+    fn new() -> Self {
+        Rng(0x2023_1201_dead_beef)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// Number in `0..bound`
+    fn below(&mut self, bound: u64) -> u64 {
+        self.next() % bound
+    }
+}
+
+const DAY01_LINES: usize = 2_000_000;
+const SPELLED_DIGITS: [&str; 9] = [
+    "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+];
+
+/// Calibration document lines mixing letters, digits and spelled digits, each with at least one
+/// digit so that both parts have an answer
+fn generate_day01(rng: &mut Rng) -> String {
+    let mut input = String::new();
+    for _ in 0..DAY01_LINES {
+        let digit_at = rng.below(4);
+        for idx in 0..4 {
+            if idx == digit_at {
+                input.push(char::from(b'1' + rng.below(9) as u8));
+            } else if rng.below(2) == 0 {
+                input.push_str(SPELLED_DIGITS[rng.below(9) as usize]);
+            } else {
+                input.push(char::from(b'a' + rng.below(26) as u8));
+            }
+        }
+        input.push('\n');
+    }
+    input
+}
+
+const DAY05_SEED_RANGES: usize = 20_000;
+const DAY05_RULES: usize = 2_000;
+/// Values stay below this, like in the real inputs
+const DAY05_MAX_VALUE: u64 = 1 << 32;
+const DAY05_CATEGORIES: [&str; 8] = [
+    "seed",
+    "soil",
+    "fertilizer",
+    "water",
+    "light",
+    "temperature",
+    "humidity",
+    "location",
+];
+
+/// Almanac with many small seed ranges and many rules per map. The rules of a map cover
+/// consecutive source intervals and shift them to random destinations, so the seed ranges keep
+/// getting split up instead of merging.
+#[cfg_attr(not(feature = "parallel"), allow(dead_code))]
+fn generate_day05(rng: &mut Rng) -> String {
+    let range_len = DAY05_MAX_VALUE / DAY05_SEED_RANGES as u64 / 2;
+    let mut input = String::from("seeds:");
+    for _ in 0..DAY05_SEED_RANGES {
+        let start = rng.below(DAY05_MAX_VALUE - range_len);
+        input.push_str(&format!(" {} {}", start, 1 + rng.below(range_len)));
+    }
+    input.push('\n');
+    for categories in DAY05_CATEGORIES.windows(2) {
+        input.push_str(&format!("\n{}-to-{} map:\n", categories[0], categories[1]));
+        let mut bounds = (0..=DAY05_RULES)
+            .map(|_| rng.below(DAY05_MAX_VALUE))
+            .collect::<Vec<_>>();
+        bounds.sort();
+        bounds.dedup();
+        for bound in bounds.windows(2) {
+            let len = bound[1] - bound[0];
+            let destination = rng.below(DAY05_MAX_VALUE - len);
+            input.push_str(&format!("{} {} {}\n", destination, bound[0], len));
+        }
+    }
+    input
+}
+
+const DAY14_SIZE: usize = 100;
+
+/// Square field like the real inputs, with a round rock in about a fifth of the cells and a cube
+/// rock in about a tenth
+fn generate_day14(rng: &mut Rng) -> String {
+    let mut input = String::new();
+    for _ in 0..DAY14_SIZE {
+        for _ in 0..DAY14_SIZE {
+            input.push(match rng.below(10) {
+                0 | 1 => 'O',
+                2 => '#',
+                _ => '.',
+            });
+        }
+        input.push('\n');
+    }
+    input
+}
+
+const DAY16_SIZE: usize = 110;
+
+/// Contraption as large as the real inputs, with a mirror or splitter on about a tenth of the
+/// tiles
+fn generate_day16(rng: &mut Rng) -> String {
+    let mut input = String::new();
+    for _ in 0..DAY16_SIZE {
+        for _ in 0..DAY16_SIZE {
+            input.push(match rng.below(40) {
+                0 => '|',
+                1 => '-',
+                2 => '/',
+                3 => '\\',
+                _ => '.',
+            });
+        }
+        input.push('\n');
+    }
+    input
+}
+
+const DAY17_SIZE: usize = 141;
+
+/// Heat loss map of random digits, as large as the real inputs
+fn generate_day17(rng: &mut Rng) -> String {
+    let mut input = String::new();
+    for _ in 0..DAY17_SIZE {
+        for _ in 0..DAY17_SIZE {
+            input.push(char::from(b'1' + rng.below(9) as u8));
+        }
+        input.push('\n');
+    }
+    input
+}
+
+fn comparisons() -> Vec<Comparison> {
+    #[allow(unused_mut)]
+    let mut comparisons = vec![
+        Comparison {
+            name: "day01 part 1",
+            binary: env!("CARGO_BIN_EXE_day01"),
+            part: 1,
+            generate: generate_day01,
+            variants: &[
+                Variant {
+                    label: "mmap",
+                    args: &[],
+                    env: &[],
+                },
+                Variant {
+                    label: "lines",
+                    args: &["--algo", "lines"],
+                    env: &[],
+                },
+            ],
+        },
+        Comparison {
+            name: "day01 part 2",
+            binary: env!("CARGO_BIN_EXE_day01"),
+            part: 2,
+            generate: generate_day01,
+            variants: &[
+                Variant {
+                    label: "mmap",
+                    args: &[],
+                    env: &[],
+                },
+                Variant {
+                    label: "lines",
+                    args: &["--algo", "lines"],
+                    env: &[],
+                },
+            ],
+        },
+        Comparison {
+            name: "day14 part 2",
+            binary: env!("CARGO_BIN_EXE_day14"),
+            part: 2,
+            generate: generate_day14,
+            variants: &[
+                Variant {
+                    label: "in place",
+                    args: &[],
+                    env: &[],
+                },
+                Variant {
+                    label: "rotate",
+                    args: &["--algo", "rotate"],
+                    env: &[],
+                },
+            ],
+        },
+        Comparison {
+            name: "day16 part 1",
+            binary: env!("CARGO_BIN_EXE_day16"),
+            part: 1,
+            generate: generate_day16,
+            variants: &[
+                Variant {
+                    label: "dense",
+                    args: &[],
+                    env: &[],
+                },
+                Variant {
+                    label: "hashed",
+                    args: &["--algo", "hashed"],
+                    env: &[],
+                },
+            ],
+        },
+        Comparison {
+            name: "day16 part 2",
+            binary: env!("CARGO_BIN_EXE_day16"),
+            part: 2,
+            generate: generate_day16,
+            variants: &[
+                Variant {
+                    label: "dense",
+                    args: &[],
+                    env: &[],
+                },
+                Variant {
+                    label: "hashed",
+                    args: &["--algo", "hashed"],
+                    env: &[],
+                },
+            ],
+        },
+        Comparison {
+            name: "day17 part 1",
+            binary: env!("CARGO_BIN_EXE_day17"),
+            part: 1,
+            generate: generate_day17,
+            variants: &[
+                Variant {
+                    label: "buckets",
+                    args: &[],
+                    env: &[],
+                },
+                Variant {
+                    label: "heap",
+                    args: &["--algo", "heap"],
+                    env: &[],
+                },
+            ],
+        },
+        Comparison {
+            name: "day17 part 2",
+            binary: env!("CARGO_BIN_EXE_day17"),
+            part: 2,
+            generate: generate_day17,
+            variants: &[
+                Variant {
+                    label: "buckets",
+                    args: &[],
+                    env: &[],
+                },
+                Variant {
+                    label: "heap",
+                    args: &["--algo", "heap"],
+                    env: &[],
+                },
+            ],
+        },
+    ];
+    // The stages convert the seed ranges of each stage in parallel, `RAYON_NUM_THREADS=1` gives
+    // the sequential baseline for the speedup
+    #[cfg(feature = "parallel")]
+    comparisons.push(Comparison {
+        name: "day05 part 2 parallel stages",
+        binary: env!("CARGO_BIN_EXE_day05"),
+        part: 2,
+        generate: generate_day05,
+        variants: &[
+            Variant {
+                label: "1 thread",
+                args: &["--algo", "stages"],
+                env: &[("RAYON_NUM_THREADS", "1")],
+            },
+            Variant {
+                label: "all threads",
+                args: &["--algo", "stages"],
+                env: &[],
+            },
+        ],
+    });
+    comparisons
+}
+
+/// Runs the variant once and returns its answer and the time the runner measured
+fn run_variant(comparison: &Comparison, variant: &Variant, input: &Path) -> Result<PartReport> {
+    let output = Command::new(comparison.binary)
+        .arg("--input")
+        .arg(input)
+        .args(["--part", &comparison.part.to_string(), "--machine-readable"])
+        .args(variant.args)
+        .envs(variant.env.iter().copied())
+        .output()
+        .with_context(|| format!("Could not start {}", comparison.binary))?;
+    if !output.status.success() {
+        bail!(
+            "{} ({}) failed: {}",
+            comparison.name,
+            variant.label,
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find_map(PartReport::parse_machine_line)
+        .with_context(|| format!("No report from {} ({})", comparison.name, variant.label))
+}
+
+fn median(mut times: Vec<Duration>) -> Duration {
+    times.sort();
+    times[times.len() / 2]
+}
+
+fn compare(comparison: &Comparison, dir: &TempDir) -> Result<()> {
+    let start = Instant::now();
+    let input = dir
+        .path()
+        .join(format!("{}.txt", comparison.name.replace(' ', "_")));
+    fs::write(&input, (comparison.generate)(&mut Rng::new()))?;
+    println!(
+        "{} ({:.0} kB input, generated in {:.2?})",
+        comparison.name,
+        fs::metadata(&input)?.len() as f64 / 1e3,
+        start.elapsed()
+    );
+
+    let mut baseline = None;
+    for variant in comparison.variants {
+        let reports = (0..RUNS)
+            .map(|_| run_variant(comparison, variant, &input))
+            .collect::<Result<Vec<_>>>()?;
+        let answer = &reports[0].answer;
+        if reports.iter().any(|report| report.answer != *answer) {
+            bail!(
+                "{} ({}) is not deterministic",
+                comparison.name,
+                variant.label
+            );
+        }
+        let time = median(reports.iter().map(|report| report.total).collect());
+        let (expected, baseline_time) = baseline.get_or_insert((answer.clone(), time));
+        if answer != expected {
+            bail!(
+                "{} ({}) answered {}, but {} answered {}",
+                comparison.name,
+                variant.label,
+                answer,
+                comparison.variants[0].label,
+                expected
+            );
+        }
+        println!(
+            "  {:<12} {:>12.2?}  {:>6.2}x",
+            variant.label,
+            time,
+            time.as_secs_f64() / baseline_time.as_secs_f64()
+        );
+    }
+    Ok(())
+}
+
+fn main() -> Result<()> {
+    let filters = env::args()
+        .skip(1)
+        .filter(|arg| !arg.starts_with('-'))
+        .collect::<Vec<_>>();
+    let dir = TempDir::new()?;
+    for comparison in comparisons() {
+        if filters.is_empty() || filters.iter().any(|f| comparison.name.contains(f.as_str())) {
+            compare(&comparison, &dir)?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,69 @@
+use std::fmt::Display;
+
+/// The result of a single puzzle part.
+///
+/// Puzzles answer with integers of all sizes and signs, sometimes even with text, so the parts
+/// convert their native result into this type before handing it to the runner.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Answer {
+    Int(i128),
+    Uint(u128),
+    Text(String),
+}
+
+impl Display for Answer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Answer::Int(v) => write!(f, "{}", v),
+            Answer::Uint(v) => write!(f, "{}", v),
+            Answer::Text(v) => write!(f, "{}", v),
+        }
+    }
+}
+
+macro_rules! impl_from_int {
+    ($variant:ident, $target:ty, $($t:ty),*) => {
+        $(
+            impl From<$t> for Answer {
+                fn from(value: $t) -> Self {
+                    Answer::$variant(value as $target)
+                }
+            }
+        )*
+    };
+}
+
+impl_from_int!(Uint, u128, u8, u16, u32, u64, u128, usize);
+impl_from_int!(Int, i128, i8, i16, i32, i64, i128, isize);
+
+impl From<String> for Answer {
+    fn from(value: String) -> Self {
+        Answer::Text(value)
+    }
+}
+
+impl From<&str> for Answer {
+    fn from(value: &str) -> Self {
+        Answer::Text(value.to_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_conversions() {
+        assert_eq!(Answer::from(42usize), Answer::Uint(42));
+        assert_eq!(Answer::from(-42isize), Answer::Int(-42));
+        assert_eq!(Answer::from(u64::MAX), Answer::Uint(u64::MAX as u128));
+        assert_eq!(Answer::from("abc"), Answer::Text("abc".to_owned()));
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(Answer::Int(-7).to_string(), "-7");
+        assert_eq!(Answer::Uint(7).to_string(), "7");
+        assert_eq!(Answer::Text("LGTM".to_owned()).to_string(), "LGTM");
+    }
+}
@@ -0,0 +1,124 @@
+use std::{
+    env::consts::EXE_SUFFIX,
+    path::PathBuf,
+    process::{Command, ExitStatus},
+};
+
+use advent_of_code_2023::cli::Options;
+use anyhow::{bail, Context, Result};
+
+mod serve;
+mod stats;
+mod tui;
+
+// Front-end for running several days in one go, e.g. `aoc run 16 17 --resume`.
+// The days are separate binaries, so we just look for them next to our own executable and spawn
+// them, passing through all options.
+
+const USAGE: &str =
+    "Usage: aoc (run|tui) [DAY...] [OPTIONS...] | aoc serve [--bind ADDRESS] | aoc stats [DAY...]";
+
+/// Day binaries by day number
+type DayBinaries = Vec<(u8, PathBuf)>;
+
+/// Finds all day binaries that were built alongside this one, sorted by day.
+fn day_binaries() -> Result<DayBinaries> {
+    let exe = std::env::current_exe()?;
+    let dir = exe
+        .parent()
+        .context("Could not determine the binary directory")?;
+    let mut days = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().into_string().ok()?;
+            let day = name
+                .strip_suffix(EXE_SUFFIX)?
+                .strip_prefix("day")?
+                .parse::<u8>()
+                .ok()?;
+            Some((day, entry.path()))
+        })
+        .collect::<Vec<_>>();
+    days.sort();
+    Ok(days)
+}
+
+fn run_day(day: u8, binary: &PathBuf, options: &[String]) -> Result<ExitStatus> {
+    println!("Day {:02}", day);
+    Command::new(binary)
+        .args(options)
+        .status()
+        .with_context(|| format!("Could not start {}", binary.display()))
+}
+
+/// Splits the arguments into the selected day binaries and the options for them.
+///
+/// Leading numbers select the days (all days if there are none), everything after that is passed
+/// to the day binaries.
+fn select_days(args: &[String]) -> Result<(DayBinaries, &[String])> {
+    let selected = args
+        .iter()
+        .map_while(|arg| arg.parse::<u8>().ok())
+        .collect::<Vec<_>>();
+    let options = &args[selected.len()..];
+
+    let available = day_binaries()?;
+    if available.is_empty() {
+        bail!("No day binaries found, build them with `cargo build --bins`");
+    }
+    if let Some(missing) = selected
+        .iter()
+        .find(|day| !available.iter().any(|(d, _)| d == *day))
+    {
+        bail!("No binary for day {} found", missing);
+    }
+
+    let days = available
+        .into_iter()
+        .filter(|(day, _)| selected.is_empty() || selected.contains(day))
+        .collect();
+    Ok((days, options))
+}
+
+fn run(args: &[String]) -> Result<()> {
+    let (days, options) = select_days(args)?;
+
+    let mut failed = Vec::new();
+    for (day, binary) in days {
+        if !run_day(day, &binary, options)?.success() {
+            failed.push(day);
+        }
+    }
+
+    if !failed.is_empty() {
+        bail!("Failed days: {:?}", failed);
+    }
+    Ok(())
+}
+
+fn main() -> Result<()> {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    match args.first().map(|s| s.as_str()) {
+        Some("run") => run(&args[1..]),
+        Some("tui") => {
+            let (days, options) = select_days(&args[1..])?;
+            tui::run(days, options)
+        }
+        Some("serve") => {
+            let options = Options::parse(args[1..].iter().cloned())?;
+            let address = options
+                .get::<String>("bind")?
+                .unwrap_or_else(|| "127.0.0.1:8080".to_owned());
+            serve::serve(day_binaries()?, &address)
+        }
+        Some("stats") => {
+            let days = args[1..]
+                .iter()
+                .map(|arg| arg.parse::<u8>())
+                .collect::<Result<Vec<_>, _>>()
+                .context(USAGE)?;
+            stats::print(&days)
+        }
+        _ => bail!(USAGE),
+    }
+}
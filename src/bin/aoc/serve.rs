@@ -0,0 +1,210 @@
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::{TcpListener, TcpStream},
+    path::PathBuf,
+    process::Command,
+    sync::Arc,
+    thread,
+};
+
+use advent_of_code_2023::runner::PartReport;
+use anyhow::{anyhow, bail, Context, Result};
+use serde_json::json;
+
+use crate::DayBinaries;
+
+// Tiny HTTP/1.1 server exposing the solvers:
+//
+//     POST /solve/{day}/{part}   (body: raw puzzle input)
+//
+// answers with {"answer":"46","day":16,"part":1,"time_ns":246329}.
+// Every request spawns the day binary on a temporary copy of the input, so the solvers don't need
+// to know anything about HTTP.
+
+const MAX_BODY_SIZE: usize = 16 * 1024 * 1024;
+
+#[derive(Debug, PartialEq, Eq)]
+struct Request {
+    method: String,
+    path: String,
+    body: Vec<u8>,
+}
+
+fn read_request<R: BufRead>(reader: &mut R) -> Result<Request> {
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut fields = request_line.split_whitespace();
+    let method = fields.next().ok_or_else(|| anyhow!("Empty request"))?;
+    let path = fields
+        .next()
+        .ok_or_else(|| anyhow!("Missing request path"))?;
+
+    let mut content_length = 0;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 || header.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().context("Invalid Content-Length")?;
+            }
+        }
+    }
+    if content_length > MAX_BODY_SIZE {
+        bail!("Request body too large");
+    }
+
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+    Ok(Request {
+        method: method.to_owned(),
+        path: path.to_owned(),
+        body,
+    })
+}
+
+fn parse_solve_path(path: &str) -> Option<(u8, u8)> {
+    let mut segments = path.strip_prefix("/solve/")?.split('/');
+    let day = segments.next()?.parse().ok()?;
+    let part = segments.next()?.parse().ok()?;
+    if segments.next().is_some() || !(1..=2).contains(&part) {
+        return None;
+    }
+    Some((day, part))
+}
+
+fn json_error(message: &str) -> String {
+    json!({ "error": message }).to_string()
+}
+
+fn solve(binary: &PathBuf, part: u8, input: &[u8]) -> Result<PartReport> {
+    let mut input_file = tempfile::NamedTempFile::new()?;
+    input_file.write_all(input)?;
+    input_file.flush()?;
+
+    let output = Command::new(binary)
+        .arg("--input")
+        .arg(input_file.path())
+        .args(["--part", &part.to_string(), "--machine-readable"])
+        .output()
+        .with_context(|| format!("Could not start {}", binary.display()))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!(
+            "Solver failed: {}",
+            stderr
+                .lines()
+                .find(|line| !line.trim().is_empty())
+                .unwrap_or("unknown error")
+        );
+    }
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find_map(PartReport::parse_machine_line)
+        .ok_or_else(|| anyhow!("Solver did not report an answer"))
+}
+
+/// Returns the status line and JSON body for the request.
+fn handle(days: &DayBinaries, request: &Request) -> (&'static str, String) {
+    let Some((day, part)) = parse_solve_path(&request.path) else {
+        return ("404 Not Found", json_error("Expected /solve/{day}/{part}"));
+    };
+    if request.method != "POST" {
+        return (
+            "405 Method Not Allowed",
+            json_error("Only POST is supported"),
+        );
+    }
+    let Some((_, binary)) = days.iter().find(|(d, _)| *d == day) else {
+        return ("404 Not Found", json_error(&format!("Unknown day {}", day)));
+    };
+
+    match solve(binary, part, &request.body) {
+        Ok(report) => (
+            "200 OK",
+            json!({
+                "day": day,
+                "part": part,
+                "answer": report.answer,
+                "time_ns": u64::try_from(report.total.as_nanos()).unwrap_or(u64::MAX),
+            })
+            .to_string(),
+        ),
+        Err(e) => ("422 Unprocessable Entity", json_error(&e.to_string())),
+    }
+}
+
+fn handle_connection(days: &DayBinaries, mut stream: TcpStream) -> Result<()> {
+    let request = read_request(&mut BufReader::new(&mut stream));
+    let (status, body) = match request {
+        Ok(request) => handle(days, &request),
+        Err(e) => ("400 Bad Request", json_error(&e.to_string())),
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    )?;
+    Ok(())
+}
+
+pub fn serve(days: DayBinaries, address: &str) -> Result<()> {
+    let listener =
+        TcpListener::bind(address).with_context(|| format!("Could not listen on {}", address))?;
+    println!("Listening on http://{}", listener.local_addr()?);
+
+    let days = Arc::new(days);
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(e) => {
+                eprintln!("Connection failed: {}", e);
+                continue;
+            }
+        };
+        let days = Arc::clone(&days);
+        thread::spawn(move || {
+            if let Err(e) = handle_connection(&days, stream) {
+                eprintln!("Error while handling request: {}", e);
+            }
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_request() {
+        let raw = "POST /solve/1/2 HTTP/1.1\r\nHost: x\r\ncontent-length: 5\r\n\r\nabc\nd";
+        let request = read_request(&mut raw.as_bytes()).unwrap();
+        assert_eq!(
+            request,
+            Request {
+                method: "POST".to_owned(),
+                path: "/solve/1/2".to_owned(),
+                body: b"abc\nd".to_vec(),
+            }
+        );
+        assert!(read_request(&mut "".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_parse_solve_path() {
+        assert_eq!(parse_solve_path("/solve/16/1"), Some((16, 1)));
+        assert_eq!(parse_solve_path("/solve/16/3"), None);
+        assert_eq!(parse_solve_path("/solve/16"), None);
+        assert_eq!(parse_solve_path("/solve/16/1/x"), None);
+        assert_eq!(parse_solve_path("/other/16/1"), None);
+    }
+
+    #[test]
+    fn test_json_error() {
+        assert_eq!(json_error("a\"b\\c\n"), r#"{"error":"a\"b\\c\n"}"#);
+    }
+}
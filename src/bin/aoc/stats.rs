@@ -0,0 +1,91 @@
+use std::time::Duration;
+
+use advent_of_code_2023::history::{self, Trend};
+use anyhow::Result;
+
+// Prints how the recorded timings evolved over the commits, e.g. after a refactoring:
+//
+//     2023 day 16 part 2 (default, release)
+//       3f2a9c1      4 runs    412.33ms
+//       8b0e7d4      2 runs    127.90ms   -69.0%
+
+fn relative_change(previous: Duration, current: Duration) -> f64 {
+    (current.as_secs_f64() / previous.as_secs_f64() - 1.0) * 100.0
+}
+
+fn format_trend(trend: &Trend) -> String {
+    let mut lines = vec![format!(
+        "{} day {:02} part {} ({}, {})",
+        trend.year, trend.day, trend.part, trend.algorithm, trend.profile
+    )];
+    let mut previous = None;
+    for point in &trend.points {
+        let mut line = format!(
+            "  {:<14}{:>3} run{}{:>12.2?}",
+            point.commit,
+            point.runs,
+            if point.runs == 1 { " " } else { "s" },
+            point.best
+        );
+        if let Some(previous) = previous.filter(|d: &Duration| !d.is_zero()) {
+            line.push_str(&format!("{:>+9.1}%", relative_change(previous, point.best)));
+        }
+        lines.push(line);
+        previous = Some(point.best);
+    }
+    lines.join("\n")
+}
+
+/// Prints the trends of the given days, or of all recorded days if none are given.
+pub fn print(days: &[u8]) -> Result<()> {
+    let path = history::history_path();
+    let records = history::load(&path)?;
+    let trends = history::trends(&records)
+        .into_iter()
+        .filter(|trend| days.is_empty() || days.contains(&trend.day))
+        .collect::<Vec<_>>();
+    if trends.is_empty() {
+        println!(
+            "No timings recorded in {} yet, run some days first",
+            path.display()
+        );
+        return Ok(());
+    }
+    let reports = trends.iter().map(format_trend).collect::<Vec<_>>();
+    println!("{}", reports.join("\n\n"));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use advent_of_code_2023::history::TrendPoint;
+
+    use super::*;
+
+    #[test]
+    fn test_format_trend() {
+        let point = |commit: &str, runs, millis| TrendPoint {
+            commit: commit.to_owned(),
+            first_run: 0,
+            runs,
+            best: Duration::from_millis(millis),
+        };
+        let trend = Trend {
+            year: 2023,
+            day: 16,
+            part: 2,
+            algorithm: "default".to_owned(),
+            profile: "release".to_owned(),
+            points: vec![point("3f2a9c1", 4, 400), point("8b0e7d4", 1, 100)],
+        };
+        assert_eq!(
+            format_trend(&trend),
+            [
+                "2023 day 16 part 2 (default, release)",
+                "  3f2a9c1         4 runs    400.00ms",
+                "  8b0e7d4         1 run     100.00ms    -75.0%",
+            ]
+            .join("\n")
+        );
+    }
+}
@@ -0,0 +1,289 @@
+use std::{
+    io::{BufRead, BufReader},
+    path::PathBuf,
+    process::{Command, Stdio},
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
+    time::{Duration, Instant},
+};
+
+use advent_of_code_2023::runner::PartReport;
+
+use crate::DayBinaries;
+use anyhow::Result;
+use ratatui::{
+    crossterm::event::{self, Event, KeyCode, KeyEventKind},
+    layout::{Constraint, Layout},
+    style::{Color, Modifier, Style},
+    text::Line,
+    widgets::{Block, Cell, Paragraph, Row, Table, TableState},
+    DefaultTerminal, Frame,
+};
+
+// Dashboard that runs all days one after another and shows the answers and timings as they come
+// in. The days are run on a worker thread, which reports back through a channel.
+
+#[derive(Debug, Clone)]
+enum Status {
+    Queued,
+    Running(Instant),
+    Done,
+    Failed(String),
+}
+
+struct DayRow {
+    day: u8,
+    status: Status,
+    parts: [Option<PartReport>; 2],
+}
+
+enum WorkerEvent {
+    Started(usize),
+    Part(usize, PartReport),
+    Finished(usize, Result<(), String>),
+}
+
+fn run_day_reporting(
+    idx: usize,
+    binary: &PathBuf,
+    options: &[String],
+    events: &Sender<WorkerEvent>,
+) {
+    let _ = events.send(WorkerEvent::Started(idx));
+    let child = Command::new(binary)
+        .args(options)
+        .arg("--machine-readable")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn();
+    let mut child = match child {
+        Ok(child) => child,
+        Err(e) => {
+            let _ = events.send(WorkerEvent::Finished(idx, Err(e.to_string())));
+            return;
+        }
+    };
+
+    // Diagnostics can fill the stderr pipe before stdout is closed, so stderr is drained
+    // concurrently. The error of a failing day is reported last, everything before it is noise.
+    let stderr = child.stderr.take().expect("stderr is piped");
+    let last_error_line = thread::spawn(move || {
+        BufReader::new(stderr)
+            .lines()
+            .map_while(Result::ok)
+            .filter(|line| !line.trim().is_empty())
+            .last()
+    });
+
+    let stdout = child.stdout.take().expect("stdout is piped");
+    for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+        if let Some(report) = PartReport::parse_machine_line(&line) {
+            let _ = events.send(WorkerEvent::Part(idx, report));
+        }
+    }
+
+    let last_error_line = last_error_line.join().ok().flatten();
+    let result = match child.wait() {
+        Ok(status) if status.success() => Ok(()),
+        Ok(_) => Err(last_error_line.unwrap_or_else(|| "failed".to_owned())),
+        Err(e) => Err(e.to_string()),
+    };
+    let _ = events.send(WorkerEvent::Finished(idx, result));
+}
+
+fn spawn_worker(
+    binaries: Vec<PathBuf>,
+    options: Vec<String>,
+    requests: Receiver<usize>,
+    events: Sender<WorkerEvent>,
+) {
+    thread::spawn(move || {
+        for idx in requests {
+            run_day_reporting(idx, &binaries[idx], &options, &events);
+        }
+    });
+}
+
+struct App {
+    rows: Vec<DayRow>,
+    table_state: TableState,
+    requests: Sender<usize>,
+}
+
+impl App {
+    fn schedule(&mut self, idx: usize) {
+        let row = &mut self.rows[idx];
+        if matches!(row.status, Status::Queued | Status::Running(_)) {
+            return;
+        }
+        row.status = Status::Queued;
+        row.parts = [None, None];
+        let _ = self.requests.send(idx);
+    }
+
+    fn handle(&mut self, event: WorkerEvent) {
+        match event {
+            WorkerEvent::Started(idx) => self.rows[idx].status = Status::Running(Instant::now()),
+            WorkerEvent::Part(idx, report) => {
+                let slot = usize::from(report.part.clamp(1, 2)) - 1;
+                self.rows[idx].parts[slot] = Some(report);
+            }
+            WorkerEvent::Finished(idx, result) => {
+                self.rows[idx].status = match result {
+                    Ok(()) => Status::Done,
+                    Err(e) => Status::Failed(e),
+                }
+            }
+        }
+    }
+
+    fn select(&mut self, delta: isize) {
+        let current = self.table_state.selected().unwrap_or(0);
+        let next = current
+            .saturating_add_signed(delta)
+            .min(self.rows.len().saturating_sub(1));
+        self.table_state.select(Some(next));
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [table_area, help_area] =
+            Layout::vertical([Constraint::Min(3), Constraint::Length(1)]).areas(frame.area());
+
+        let rows = self.rows.iter().map(|row| {
+            let (status, style) = match &row.status {
+                Status::Queued => ("queued".to_owned(), Style::default().fg(Color::DarkGray)),
+                Status::Running(start) => (
+                    format!("running {:.1?}", start.elapsed()),
+                    Style::default().fg(Color::Yellow),
+                ),
+                Status::Done => ("done".to_owned(), Style::default().fg(Color::Green)),
+                Status::Failed(e) => (format!("failed: {}", e), Style::default().fg(Color::Red)),
+            };
+            let part_cells = row.parts.iter().flat_map(|part| match part {
+                Some(report) => [
+                    Cell::from(report.answer.clone()),
+                    Cell::from(format!("{:.2?}", report.total)),
+                ],
+                None => [Cell::from("-"), Cell::from("-")],
+            });
+            Row::new(
+                std::iter::once(Cell::from(format!("{:02}", row.day)))
+                    .chain(part_cells)
+                    .chain(std::iter::once(Cell::from(status).style(style))),
+            )
+        });
+
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(4),
+                Constraint::Min(16),
+                Constraint::Length(12),
+                Constraint::Min(16),
+                Constraint::Length(12),
+                Constraint::Min(20),
+            ],
+        )
+        .header(
+            Row::new(["Day", "Part 1", "Time", "Part 2", "Time", "Status"])
+                .style(Style::default().add_modifier(Modifier::BOLD)),
+        )
+        .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+        .block(Block::bordered().title("Advent of Code"));
+
+        frame.render_stateful_widget(table, table_area, &mut self.table_state);
+        frame.render_widget(
+            Paragraph::new(Line::from(
+                "q: quit  ↑/↓: select  r: re-run selected day  a: re-run all",
+            )),
+            help_area,
+        );
+    }
+}
+
+fn event_loop(
+    terminal: &mut DefaultTerminal,
+    app: &mut App,
+    events: Receiver<WorkerEvent>,
+) -> Result<()> {
+    loop {
+        while let Ok(worker_event) = events.try_recv() {
+            app.handle(worker_event);
+        }
+        terminal.draw(|frame| app.draw(frame))?;
+
+        // Poll with a timeout so the running timers keep ticking
+        if event::poll(Duration::from_millis(100))? {
+            if let Event::Key(key) = event::read()? {
+                if key.kind != KeyEventKind::Press {
+                    continue;
+                }
+                match key.code {
+                    KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                    KeyCode::Up | KeyCode::Char('k') => app.select(-1),
+                    KeyCode::Down | KeyCode::Char('j') => app.select(1),
+                    KeyCode::Char('r') => {
+                        if let Some(idx) = app.table_state.selected() {
+                            app.schedule(idx);
+                        }
+                    }
+                    KeyCode::Char('a') => (0..app.rows.len()).for_each(|idx| app.schedule(idx)),
+                    _ => {}
+                }
+            }
+        }
+    }
+}
+
+pub fn run(days: DayBinaries, options: &[String]) -> Result<()> {
+    let (request_tx, request_rx) = mpsc::channel();
+    let (event_tx, event_rx) = mpsc::channel();
+    spawn_worker(
+        days.iter().map(|(_, binary)| binary.clone()).collect(),
+        options.to_vec(),
+        request_rx,
+        event_tx,
+    );
+
+    let mut app = App {
+        rows: days
+            .into_iter()
+            .map(|(day, _)| DayRow {
+                day,
+                status: Status::Queued,
+                parts: [None, None],
+            })
+            .collect(),
+        table_state: TableState::default().with_selected(Some(0)),
+        requests: request_tx,
+    };
+    for idx in 0..app.rows.len() {
+        let _ = app.requests.send(idx);
+    }
+
+    let mut terminal = ratatui::init();
+    let result = event_loop(&mut terminal, &mut app, event_rx);
+    ratatui::restore();
+    result
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_failing_day_with_lots_of_diagnostics() {
+        // More than a pipe buffer of diagnostics on stderr before the error, like `--debug` does
+        let script =
+            "head -c 200000 /dev/zero | tr '\\0' x >&2; echo >&2; echo 'Error: boom' >&2; exit 1";
+        let options = ["-c".to_owned(), script.to_owned()];
+        let (events, received) = mpsc::channel();
+        run_day_reporting(0, &PathBuf::from("/bin/sh"), &options, &events);
+        drop(events);
+        let finished = received.into_iter().find_map(|event| match event {
+            WorkerEvent::Finished(_, result) => Some(result),
+            _ => None,
+        });
+        assert_eq!(finished, Some(Err("Error: boom".to_owned())));
+    }
+}
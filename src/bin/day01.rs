@@ -1,42 +1,17 @@
 use std::path::Path;
 
-use advent_of_code_2023::stream_items_from_file;
-use anyhow::Result;
-
-const INPUT: &str = "input/day01.txt";
-
-fn get_digits_allowing_for_spelled_digits(line: &str) -> (u32, u32) {
-    // Replace each spelled digit by the digit surrounded by the first and last letter of the
-    // spelled digit. This allows for overlapping spelled digits, e.g. threeight
-    let preprocessed = line
-        .replace("one", "o1e")
-        .replace("two", "t2o")
-        .replace("three", "t3e")
-        .replace("four", "f4r")
-        .replace("five", "f5e")
-        .replace("six", "s6x")
-        .replace("seven", "s7n")
-        .replace("eight", "e8t")
-        .replace("nine", "n9e");
-
-    get_digits_plain(&preprocessed)
-}
-
-fn get_digits_plain(line: &str) -> (u32, u32) {
-    let mut digits = line.chars().filter_map(|c| c.to_digit(10));
-    let first = digits.next().unwrap();
-    let last = digits.last().unwrap_or(first);
-
-    (first, last)
-}
+use advent_of_code_2023::{
+    answer::Answer,
+    byte_lines,
+    digits::first_and_last_digit,
+    map_file,
+    runner::{run, AocDay, Solver},
+    stream_items_from_file,
+};
+use anyhow::{anyhow, Result};
 
 fn get_calibration_value_for_line(line: String, allow_spelled: bool) -> usize {
-    let (first, last) = if allow_spelled {
-        get_digits_allowing_for_spelled_digits(&line)
-    } else {
-        get_digits_plain(&line)
-    };
-    //println!("{} -> {}, {}", &line, first, last);
+    let (first, last) = first_and_last_digit(line.as_bytes(), allow_spelled).unwrap();
     (first * 10 + last) as usize
 }
 
@@ -51,19 +26,60 @@ fn get_calibration_value_stream<P: AsRef<Path>>(
     }))
 }
 
+/// Sums the calibration values of a memory mapped input, without allocating anything per line.
+/// This keeps huge inputs (e.g. the 1GB community input) fast, compare with `--algo lines`.
+fn sum_calibration_values_mapped<P: AsRef<Path>>(input: P, allow_spelled: bool) -> Result<usize> {
+    let bytes = map_file(input)?;
+    byte_lines(&bytes)
+        .map(|line| {
+            let (first, last) = first_and_last_digit(line, allow_spelled)
+                .ok_or_else(|| anyhow!("No digit in line {:?}", String::from_utf8_lossy(line)))?;
+            Ok((first * 10 + last) as usize)
+        })
+        .sum()
+}
+
 fn part1<P: AsRef<Path>>(input: P) -> Result<usize> {
-    Ok(get_calibration_value_stream(input, false)?.sum())
+    sum_calibration_values_mapped(input, false)
 }
 
 fn part2<P: AsRef<Path>>(input: P) -> Result<usize> {
+    sum_calibration_values_mapped(input, true)
+}
+
+fn part1_lines<P: AsRef<Path>>(input: P) -> Result<usize> {
+    Ok(get_calibration_value_stream(input, false)?.sum())
+}
+
+fn part2_lines<P: AsRef<Path>>(input: P) -> Result<usize> {
     Ok(get_calibration_value_stream(input, true)?.sum())
 }
 
-fn main() -> Result<()> {
-    println!("Answer for part 1: {}", part1(INPUT)?);
-    println!("Answer for part 2: {}", part2(INPUT)?);
+struct Day01;
+
+impl AocDay for Day01 {
+    const YEAR: u16 = 2023;
+    const DAY: u8 = 1;
 
-    Ok(())
+    fn part1(input: &Path) -> Result<Answer> {
+        Ok(part1(input)?.into())
+    }
+
+    fn part2(input: &Path) -> Result<Answer> {
+        Ok(part2(input)?.into())
+    }
+
+    fn part1_alternatives() -> Vec<(&'static str, Solver)> {
+        vec![("lines", |input| Ok(part1_lines(input)?.into()))]
+    }
+
+    fn part2_alternatives() -> Vec<(&'static str, Solver)> {
+        vec![("lines", |input| Ok(part2_lines(input)?.into()))]
+    }
+}
+
+fn main() -> Result<()> {
+    run::<Day01>()
 }
 
 #[cfg(test)]
@@ -84,6 +100,7 @@ mod tests_day01 {
             None,
         );
         assert_eq!(part1(&file).unwrap(), 142);
+        assert_eq!(part1_lines(&file).unwrap(), 142);
         drop(dir);
     }
 
@@ -102,6 +119,15 @@ mod tests_day01 {
             None,
         );
         assert_eq!(part2(&file).unwrap(), 281);
+        assert_eq!(part2_lines(&file).unwrap(), 281);
+        drop(dir);
+    }
+
+    #[test]
+    fn test_mapped_crlf_input() {
+        let (dir, file) = create_example_file("1abc2\r\nx3yone\r\n", None);
+        assert_eq!(part1(&file).unwrap(), 45);
+        assert_eq!(part2(&file).unwrap(), 43);
         drop(dir);
     }
 }
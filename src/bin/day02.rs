@@ -1,155 +1,62 @@
-use std::collections::HashMap;
 use std::path::Path;
-use std::str::FromStr;
 
-use advent_of_code_2023::stream_items_from_file;
-use anyhow::Result;
-
-const INPUT: &str = "input/day02.txt";
-
-// From the example:
-// Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
-// A game has rounds which are separated by ;.
-// Each round contains several Draws, which are a color and an amount.
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-enum Color {
-    Red,
-    Green,
-    Blue,
-}
-
-#[derive(Debug, Clone, PartialEq)]
-struct Draw {
-    color: Color,
-    amount: usize,
-}
-
-#[derive(Debug, Clone)]
-struct Round {
-    draws: Vec<Draw>,
+use advent_of_code_2023::{
+    answer::Answer,
+    cube_game::{self, Bag, Game},
+    runner::{options, run, AocDay},
+    stream_items_from_file,
+};
+use anyhow::{Context, Result};
+
+const DEFAULT_BAG: &str = "12 red, 13 green, 14 blue";
+
+fn read_games<P: AsRef<Path>>(input: P) -> Result<Vec<Game>> {
+    stream_items_from_file::<_, Game>(input)?
+        .enumerate()
+        .map(|(idx, game)| game.with_context(|| format!("Invalid game in line {}", idx + 1)))
+        .collect()
 }
 
-impl Round {
-    fn can_be_drawn_from_bag(&self, bag: &Bag) -> bool {
-        self.draws
-            .iter()
-            .all(|draw| bag.0[&draw.color] >= draw.amount)
-    }
-
-    fn grow_bag_to_make_round_possible(&self, bag: &mut Bag) {
-        for draw in &self.draws {
-            if draw.amount > bag.0[&draw.color] {
-                bag.0.insert(draw.color, draw.amount);
-            }
-        }
-    }
+fn part1<P: AsRef<Path>>(input: P, bag: &Bag) -> Result<usize> {
+    let games = read_games(input)?;
+    Ok(cube_game::possible_games(&games, bag)
+        .map(|game| game.id)
+        .sum())
 }
 
-#[derive(Debug, Clone)]
-struct Game {
-    rounds: Vec<Round>,
-    id: usize,
-}
-
-impl FromStr for Game {
-    type Err = anyhow::Error;
-
-    fn from_str(s: &str) -> Result<Self> {
-        let (descriptor, game_content) = s.split_once(':').unwrap();
-        let rounds = game_content
-            .split(';')
-            .map(|round| {
-                let draws = round
-                    .split(',')
-                    .map(|draw| {
-                        let draw = draw.trim();
-                        let (amounts, colors) = draw.split_once(' ').unwrap();
-                        let amount = amounts.parse::<usize>()?;
-                        let color = match colors {
-                            "red" => Color::Red,
-                            "green" => Color::Green,
-                            "blue" => Color::Blue,
-                            _ => return Err(anyhow::anyhow!("Invalid color")),
-                        };
-                        Ok(Draw { color, amount })
-                    })
-                    .collect::<Result<Vec<Draw>>>()?;
-                Ok(Round { draws })
-            })
-            .collect::<Result<Vec<Round>>>()?;
-        let id = descriptor.split_once(" ").unwrap().1.parse().unwrap();
-
-        Ok(Game { rounds, id })
-    }
-}
-
-impl Game {
-    fn can_be_drawn_from_bag(&self, bag: &Bag) -> bool {
-        self.rounds
-            .iter()
-            .all(|round| round.can_be_drawn_from_bag(&bag))
-    }
-
-    fn get_min_bag(&self) -> Bag {
-        let mut bag = Bag::default();
-        for round in &self.rounds {
-            round.grow_bag_to_make_round_possible(&mut bag);
-        }
-        return bag;
-    }
+fn part2<P: AsRef<Path>>(input: P) -> Result<usize> {
+    let games = read_games(input)?;
+    // A color that is missing from a game needs zero cubes, so all colors of the input count
+    let colors = cube_game::all_colors(&games);
+    let sum_of_powers = cube_game::min_bags(&games)
+        .map(|(_, bag)| bag.power(&colors))
+        .sum();
+    Ok(sum_of_powers)
 }
 
-struct Bag(HashMap<Color, usize>);
+struct Day02;
 
-impl Bag {
-    fn new(red: usize, green: usize, blue: usize) -> Self {
-        let mut bag = HashMap::new();
-        bag.insert(Color::Red, red);
-        bag.insert(Color::Green, green);
-        bag.insert(Color::Blue, blue);
-        Bag(bag)
-    }
+impl AocDay for Day02 {
+    const YEAR: u16 = 2023;
+    const DAY: u8 = 2;
 
-    fn power(&self) -> usize {
-        self.0.values().product()
+    /// The bag contents can be set with `--bag "12 red, 13 green, 14 blue"`, which is the
+    /// default.
+    fn part1(input: &Path) -> Result<Answer> {
+        let bag = match options().get::<Bag>("bag")? {
+            Some(bag) => bag,
+            None => DEFAULT_BAG.parse()?,
+        };
+        Ok(part1(input, &bag)?.into())
     }
-}
 
-impl Default for Bag {
-    fn default() -> Self {
-        Bag::new(0, 0, 0)
+    fn part2(input: &Path) -> Result<Answer> {
+        Ok(part2(input)?.into())
     }
 }
 
-fn part1<P: AsRef<Path>>(input: P) -> Result<usize> {
-    let bag = Bag::new(12, 13, 14);
-    let sum_of_possible_games = stream_items_from_file::<_, Game>(input)?
-        .map(|g| g.unwrap())
-        .filter_map(|game| {
-            if game.can_be_drawn_from_bag(&bag) {
-                Some(game.id)
-            } else {
-                None
-            }
-        })
-        .sum();
-    Ok(sum_of_possible_games)
-}
-
-fn part2<P: AsRef<Path>>(input: P) -> Result<usize> {
-    let sum_of_powers = stream_items_from_file::<_, Game>(input)?
-        .map(|g| g.unwrap())
-        .map(|g| g.get_min_bag().power())
-        .sum();
-    Ok(sum_of_powers)
-}
-
 fn main() -> Result<()> {
-    println!("Answer for part 1: {}", part1(INPUT)?);
-    println!("Answer for part 2: {}", part2(INPUT)?);
-
-    Ok(())
+    run::<Day02>()
 }
 
 #[cfg(test)]
@@ -170,8 +77,43 @@ mod tests_day02 {
         "},
             None,
         );
-        assert_eq!(part1(&file).unwrap(), 8);
+        assert_eq!(part1(&file, &DEFAULT_BAG.parse().unwrap()).unwrap(), 8);
         assert_eq!(part2(&file).unwrap(), 2286);
         drop(dir);
     }
+
+    #[test]
+    fn test_arbitrary_colors() {
+        let (dir, file) = create_example_file(
+            indoc! {"
+            Game 1: 3 blue, 4 purple; 1 red
+            Game 2: 2 purple, 1 blue, 1 red; 2 blue
+            Game 3: 1 red, 1 blue
+        "},
+            None,
+        );
+        let bag = "2 purple, 3 blue, 1 red".parse().unwrap();
+        assert_eq!(part1(&file, &bag).unwrap(), 5);
+        // Game 3 has no purple cubes, which makes its power zero
+        assert_eq!(part2(&file).unwrap(), 3 * 4 + 2 * 2);
+        assert!("12 red, thirteen green".parse::<Bag>().is_err());
+        drop(dir);
+    }
+
+    #[test]
+    fn test_malformed_line() {
+        let (dir, file) = create_example_file(
+            indoc! {"
+            Game 1: 3 blue, 4 red
+            Game 2 1 blue
+        "},
+            None,
+        );
+        let error = part2(&file).unwrap_err();
+        assert_eq!(
+            format!("{:#}", error),
+            "Invalid game in line 2: Conversion error: Missing ':' between the game id and the rounds"
+        );
+        drop(dir);
+    }
 }
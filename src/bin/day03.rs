@@ -1,122 +1,55 @@
-use regex::Regex;
 use std::collections::HashMap;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-use advent_of_code_2023::read_lines;
+use advent_of_code_2023::{
+    answer::Answer,
+    read_lines,
+    render_grid::GridRenderer,
+    runner::{options, run, AocDay},
+    schematic::{Schematic, SymbolClass},
+    timed,
+};
 use anyhow::Result;
 
-const INPUT: &str = "input/day03.txt";
-
+/// What counts as a symbol for part 1 and as a gear for part 2.
 #[derive(Debug, Clone)]
-struct SymbolMap(HashMap<(usize, usize), char>);
-
-impl SymbolMap {
-    fn from_lines(lines: &Vec<String>) -> Self {
-        // Search all lines for symbols (chars that are not whitespace, dots or numbers) and insert
-        // the coordinates and symbol char into a map
-        let mut map = HashMap::new();
-        for (y, line) in lines.iter().enumerate() {
-            for (x, c) in line.chars().enumerate() {
-                if c.is_whitespace() || c == '.' || c.is_numeric() {
-                    continue;
-                }
-                map.insert((x, y), c);
-            }
-        }
-        Self(map)
-    }
-
-    fn contains_adjacent_symbol(&self, y: usize, x_start: usize, x_end: usize) -> bool {
-        // First candidates: left and right of the number
-        if x_start
-            .checked_sub(1)
-            .map(|x| self.0.contains_key(&(x, y)))
-            .unwrap_or(false)
-        {
-            true
-        } else if self.0.contains_key(&(x_end, y)) {
-            true
-        } else {
-            // Expand the x range by one to both sides to accomodate for diagonal hits
-            let safe_start = x_start.checked_sub(1).unwrap_or(x_start);
-            (safe_start..x_end + 1).any(|x| {
-                y.checked_sub(1)
-                    .and_then(|ny| {
-                        if self.0.contains_key(&(x, ny)) {
-                            Some(true)
-                        } else {
-                            None
-                        }
-                    })
-                    .unwrap_or_else(|| self.0.contains_key(&(x, y + 1)))
-            })
+struct Rules {
+    symbols: SymbolClass,
+    gears: SymbolClass,
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Rules {
+            symbols: SymbolClass::Any,
+            gears: SymbolClass::OneOf(['*'].into()),
         }
     }
+}
 
-    fn is_gear_at(&self, x: usize, y: usize) -> bool {
-        self.0.get(&(x, y)) == Some(&'*')
+impl Rules {
+    /// The puzzle rules, unless overridden with `--symbols CHARS` or `--gears CHARS`
+    fn from_options() -> Result<Self> {
+        let default = Rules::default();
+        Ok(Rules {
+            symbols: options().get("symbols")?.unwrap_or(default.symbols),
+            gears: options().get("gears")?.unwrap_or(default.gears),
+        })
     }
+}
 
-    fn get_adjacent_gear(&self, y: usize, x_start: usize, x_end: usize) -> Option<(usize, usize)> {
-        // First candidates: left and right of the number
-        if x_start
-            .checked_sub(1)
-            .map(|x| self.is_gear_at(x, y))
-            .unwrap_or(false)
-        {
-            Some((x_start - 1, y))
-        } else if self.is_gear_at(x_end, y) {
-            Some((x_end, y))
-        } else {
-            // Expand the x range by one to both sides to accomodate for diagonal hits
-            let safe_start = x_start.checked_sub(1).unwrap_or(x_start);
-            (safe_start..x_end + 1).find_map(|x| {
-                y.checked_sub(1)
-                    .and_then(|ny| {
-                        if self.is_gear_at(x, ny) {
-                            Some(Some((x, ny)))
-                        } else {
-                            None
-                        }
-                    })
-                    .unwrap_or_else(|| {
-                        if self.is_gear_at(x, y + 1) {
-                            Some((x, y + 1))
-                        } else {
-                            None
-                        }
-                    })
-            })
-        }
-    }
+fn read_schematic<P: AsRef<Path>>(input: P, symbols: &SymbolClass) -> Result<Schematic> {
+    let lines = timed!("parse", read_lines(input)?.collect::<Result<Vec<_>, _>>()?);
+    Ok(Schematic::with_symbols(lines, symbols))
 }
 
-fn part1<P: AsRef<Path>>(input: P) -> Result<usize> {
-    let lines: Vec<_> = read_lines(input)?
-        .map(|item| item.unwrap())
-        .collect();
-
-    let symbols = SymbolMap::from_lines(&lines);
-
-    let number_regex = Regex::new(r"\d+")?;
-
-    let sum_of_part_numbers = lines
-        .iter()
-        .enumerate()
-        .flat_map(|(y, line)| {
-            number_regex
-                .find_iter(line)
-                .map(|m| {
-                    if symbols.contains_adjacent_symbol(y, m.start(), m.end()) {
-                        m.as_str().parse().unwrap()
-                    } else {
-                        0
-                    }
-                })
-                .collect::<Vec<_>>()
-        })
+fn part1<P: AsRef<Path>>(input: P, rules: &Rules) -> Result<usize> {
+    let schematic = read_schematic(input, &rules.symbols)?;
+    let sum_of_part_numbers = schematic
+        .numbers()
+        .filter(|number| schematic.adjacent_symbols(number).next().is_some())
+        .map(|number| number.value)
         .sum();
-
     Ok(sum_of_part_numbers)
 }
 
@@ -132,7 +65,7 @@ impl PotentialGearInfo {
     }
 
     fn get_ratio(&self) -> Option<usize> {
-        if self.neighbors >= 2 {
+        if self.neighbors == 2 {
             Some(self.product_of_neighbors)
         } else {
             None
@@ -149,28 +82,24 @@ impl Default for PotentialGearInfo {
     }
 }
 
-fn part2<P: AsRef<Path>>(input: P) -> Result<usize> {
+fn find_gears(schematic: &Schematic) -> HashMap<(usize, usize), PotentialGearInfo> {
     let mut potential_gear_map: HashMap<(usize, usize), PotentialGearInfo> = HashMap::new();
-
-    let lines: Vec<_> = read_lines(input)?
-        .map(|item| item.unwrap())
-        .collect();
-
-    let symbols = SymbolMap::from_lines(&lines);
-
-    let number_regex = Regex::new(r"\d+")?;
-    for (y, line) in lines.iter().enumerate() {
-        for m in number_regex.find_iter(line) {
-            if let Some(gear_coords) = symbols.get_adjacent_gear(y, m.start(), m.end()) {
-                let gear_info = potential_gear_map
-                    .entry(gear_coords)
-                    .or_insert(Default::default());
-                gear_info.push(m.as_str().parse()?);
-            }
+    for number in schematic.numbers() {
+        // A number between several gears belongs to each of them
+        for (y, x, _) in schematic.adjacent_symbols(&number) {
+            potential_gear_map
+                .entry((y, x))
+                .or_default()
+                .push(number.value);
         }
     }
+    potential_gear_map
+}
 
-    let sum_of_gear_ratios = potential_gear_map
+fn part2<P: AsRef<Path>>(input: P, rules: &Rules) -> Result<usize> {
+    // Only gears matter here, so they are the only symbols
+    let schematic = read_schematic(input, &rules.gears)?;
+    let sum_of_gear_ratios = find_gears(&schematic)
         .values()
         .filter_map(|i| i.get_ratio())
         .sum();
@@ -178,11 +107,78 @@ fn part2<P: AsRef<Path>>(input: P) -> Result<usize> {
     Ok(sum_of_gear_ratios)
 }
 
-fn main() -> Result<()> {
-    println!("Answer for part 1: {}", part1(INPUT)?);
-    println!("Answer for part 2: {}", part2(INPUT)?);
+const PART_NUMBER_COLOR: &str = "#59a14f";
+const OTHER_NUMBER_COLOR: &str = "#e15759";
+const SYMBOL_COLOR: &str = "#bab0ac";
+const GEAR_COLOR: &str = "#edc948";
 
-    Ok(())
+/// Draws the schematic with part numbers, other numbers, symbols and gears in different colors,
+/// which shows which numbers were (not) counted. Hovering a tile shows its number or gear ratio.
+fn render_schematic<P: AsRef<Path>>(input: P, rules: &Rules) -> Result<GridRenderer<usize>> {
+    let parts = read_schematic(&input, &rules.symbols)?;
+    let gears = read_schematic(&input, &rules.gears)?;
+    let mut renderer = GridRenderer::new();
+    for number in parts.numbers() {
+        let is_part = parts.adjacent_symbols(&number).next().is_some();
+        let color = if is_part {
+            PART_NUMBER_COLOR
+        } else {
+            OTHER_NUMBER_COLOR
+        };
+        for (x, digit) in number.cols.clone().zip(number.value.to_string().chars()) {
+            renderer.add_colored_grid_tile(
+                number.row,
+                x,
+                color.to_owned(),
+                Some(number.value.to_string()),
+            );
+            renderer.add_labeled_tile(number.row, x, digit.to_string(), "black".to_owned());
+        }
+    }
+    for (y, x, symbol) in parts.symbols() {
+        renderer.add_colored_grid_tile(y, x, SYMBOL_COLOR.to_owned(), None);
+        renderer.add_labeled_tile(y, x, symbol.to_string(), "black".to_owned());
+    }
+    let gear_info = find_gears(&gears);
+    for (y, x, symbol) in gears.symbols() {
+        let ratio = gear_info
+            .get(&(y, x))
+            .and_then(PotentialGearInfo::get_ratio);
+        let tooltip = ratio.map(|ratio| format!("ratio {}", ratio));
+        renderer.add_colored_grid_tile(y, x, GEAR_COLOR.to_owned(), tooltip);
+        renderer.add_labeled_tile(y, x, symbol.to_string(), "black".to_owned());
+    }
+    renderer.add_legend(&[
+        (PART_NUMBER_COLOR, "part number"),
+        (OTHER_NUMBER_COLOR, "other number"),
+        (SYMBOL_COLOR, "symbol"),
+        (GEAR_COLOR, "gear"),
+    ]);
+    Ok(renderer)
+}
+
+struct Day03;
+
+impl AocDay for Day03 {
+    const YEAR: u16 = 2023;
+    const DAY: u8 = 3;
+
+    /// `--visualize PATH` additionally stores an SVG of the schematic, see [`render_schematic`].
+    fn part1(input: &Path) -> Result<Answer> {
+        let rules = Rules::from_options()?;
+        if let Some(path) = options().get::<PathBuf>("visualize")? {
+            render_schematic(input, &rules)?.store_svg(path)?;
+        }
+        Ok(part1(input, &rules)?.into())
+    }
+
+    fn part2(input: &Path) -> Result<Answer> {
+        Ok(part2(input, &Rules::from_options()?)?.into())
+    }
+}
+
+fn main() -> Result<()> {
+    run::<Day03>()
 }
 
 #[cfg(test)]
@@ -208,8 +204,86 @@ mod tests_day03 {
         "},
             None,
         );
-        assert_eq!(part1(&file).unwrap(), 4361);
-        assert_eq!(part2(&file).unwrap(), 467835);
+        assert_eq!(part1(&file, &Rules::default()).unwrap(), 4361);
+        assert_eq!(part2(&file, &Rules::default()).unwrap(), 467835);
+        drop(dir);
+    }
+
+    #[test]
+    fn test_number_between_two_gears() {
+        let (dir, file) = create_example_file(
+            indoc! {"
+            2*3*4
+            .....
+        "},
+            None,
+        );
+        // 3 belongs to both gears
+        assert_eq!(part2(&file, &Rules::default()).unwrap(), 2 * 3 + 3 * 4);
+        drop(dir);
+    }
+
+    #[test]
+    fn test_gear_with_three_numbers() {
+        let (dir, file) = create_example_file(
+            indoc! {"
+            2*3*4
+            ....5
+            ...*.
+            ..6..
+        "},
+            None,
+        );
+        // The second `*` in the first line touches 3, 4 and 5, so it is no gear
+        assert_eq!(part2(&file, &Rules::default()).unwrap(), 2 * 3 + 5 * 6);
+        drop(dir);
+    }
+
+    #[test]
+    fn test_custom_rules() {
+        let (dir, file) = create_example_file(
+            indoc! {"
+            467..114..
+            ...*......
+            ..35..633.
+            ......#...
+            617*......
+        "},
+            None,
+        );
+        let rules = Rules {
+            symbols: "#".parse().unwrap(),
+            gears: "#*".parse().unwrap(),
+        };
+        assert_eq!(part1(&file, &rules).unwrap(), 633);
+        assert_eq!(part2(&file, &rules).unwrap(), 467 * 35);
+        drop(dir);
+    }
+
+    #[test]
+    fn test_render_schematic() {
+        let (dir, file) = create_example_file(
+            indoc! {"
+            467..114..
+            ...*......
+            ..35..633.
+        "},
+            None,
+        );
+        let mut svg = Vec::new();
+        render_schematic(&file, &Rules::default())
+            .unwrap()
+            .render_svg(&mut svg)
+            .unwrap();
+        let svg = String::from_utf8(svg).unwrap();
+        let count = |color: &str| svg.matches(&format!(r#"fill="{}""#, color)).count();
+        // One extra swatch per color in the legend
+        assert_eq!(count(PART_NUMBER_COLOR), 5 + 1);
+        assert_eq!(count(OTHER_NUMBER_COLOR), 6 + 1);
+        // The star is both a symbol and a gear
+        assert_eq!(count(SYMBOL_COLOR), 1 + 1);
+        assert_eq!(count(GEAR_COLOR), 1 + 1);
+        assert!(svg.contains("<title>ratio 16345</title>"));
         drop(dir);
     }
 }
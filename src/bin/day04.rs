@@ -1,29 +1,51 @@
-use std::{collections::HashSet, path::Path, str::FromStr};
+use std::{
+    collections::{HashSet, VecDeque},
+    ops::Range,
+    path::Path,
+    str::FromStr,
+};
 
-use advent_of_code_2023::stream_items_from_file;
-use anyhow::Result;
+use advent_of_code_2023::{
+    answer::Answer,
+    runner::{run, AocDay},
+    stream_items_from_file,
+};
+use anyhow::{bail, Context, Result};
+use thiserror::Error;
 
-const INPUT: &str = "input/day04.txt";
+#[derive(Error, Debug, PartialEq, Eq)]
+enum CardError {
+    #[error("Missing {0:?}, expected \"Card <id>: <winning numbers> | <numbers>\"")]
+    MissingSeparator(&'static str),
+    #[error("Invalid number {0:?}")]
+    InvalidNumber(String),
+}
 
 struct Card {
     winning_numbers: HashSet<usize>,
     numbers: Vec<usize>,
 }
 
+fn parse_number(s: &str) -> Result<usize, CardError> {
+    s.parse()
+        .map_err(|_| CardError::InvalidNumber(s.to_owned()))
+}
+
 impl FromStr for Card {
-    type Err = anyhow::Error;
+    type Err = CardError;
 
-    fn from_str(s: &str) -> Result<Self> {
-        // FIXME: Don't unwrap, built a result
-        let (winning_numbers_string, numbers_string) =
-            s.split_once(": ").unwrap().1.split_once(" | ").unwrap();
+    fn from_str(s: &str) -> Result<Self, CardError> {
+        let (_, all_numbers) = s.split_once(':').ok_or(CardError::MissingSeparator(":"))?;
+        let (winning_numbers_string, numbers_string) = all_numbers
+            .split_once('|')
+            .ok_or(CardError::MissingSeparator("|"))?;
         let winning_numbers = winning_numbers_string
             .split_whitespace()
-            .map(|s| s.parse::<usize>())
+            .map(parse_number)
             .collect::<Result<HashSet<usize>, _>>()?;
         let numbers = numbers_string
             .split_whitespace()
-            .map(|s| s.parse::<usize>())
+            .map(parse_number)
             .collect::<Result<Vec<usize>, _>>()?;
         Ok(Card {
             winning_numbers,
@@ -41,44 +63,129 @@ impl Card {
     }
 }
 
-fn part1<P: AsRef<Path>>(input: P) -> Result<usize> {
-    let cards = stream_items_from_file::<_, Card>(input)?;
-    Ok(cards
-        .map(|card| {
-            let winning_numbers = card.unwrap().count_winning_numbers();
-            if winning_numbers == 0 {
-                0
-            } else {
-                2usize.pow(winning_numbers - 1)
-            }
-        })
-        .sum())
+/// Scratchcards reduced to their match counts, which is all the scoring and copy rules look at.
+struct ScratchcardGame {
+    match_counts: Vec<usize>,
 }
 
-fn part2<P: AsRef<Path>>(input: P) -> Result<usize> {
-    let cards = stream_items_from_file::<_, Card>(input)?
-        .map(|r| r.unwrap())
-        .collect::<Vec<_>>();
-    let mut copies = vec![1usize; cards.len()];
-
-    for (idx, card) in cards.iter().enumerate() {
-        let winning_numbers = card.count_winning_numbers();
-        for copy_idx in 0..winning_numbers as usize {
-            let new_idx = idx + 1 + copy_idx;
-            if new_idx < cards.len() {
-                copies[new_idx] += copies[idx];
+impl ScratchcardGame {
+    fn from_cards<I: IntoIterator<Item = Card>>(cards: I) -> Self {
+        ScratchcardGame {
+            match_counts: cards
+                .into_iter()
+                .map(|card| card.count_winning_numbers() as usize)
+                .collect(),
+        }
+    }
+
+    /// Sum of the points of all cards, `score` maps the match count of a card to its points.
+    fn total_score<F>(&self, score: F) -> usize
+    where
+        F: Fn(usize) -> usize,
+    {
+        self.match_counts
+            .iter()
+            .map(|&matches| score(matches))
+            .sum()
+    }
+
+    /// Total number of cards after all copies were won.
+    ///
+    /// `copies` maps the index and match count of a card to the indices of the cards it wins a
+    /// copy of, indices past the last card are ignored. Cards may also win copies of earlier
+    /// cards, as long as no card ends up winning copies of itself.
+    fn total_cards<F>(&self, copies: F) -> Result<usize>
+    where
+        F: Fn(usize, usize) -> Range<usize>,
+    {
+        let count = self.match_counts.len();
+        let won = self
+            .match_counts
+            .iter()
+            .enumerate()
+            .map(|(idx, &matches)| {
+                let range = copies(idx, matches);
+                range.start.min(count)..range.end.min(count)
+            })
+            .collect::<Vec<_>>();
+
+        // Every card needs to know how many instances it has before handing out its copies, so
+        // the cards are processed in topological order
+        let mut unresolved_sources = vec![0usize; count];
+        for range in &won {
+            for target in range.clone() {
+                unresolved_sources[target] += 1;
+            }
+        }
+        let mut instances = vec![1usize; count];
+        let mut ready = (0..count)
+            .filter(|&idx| unresolved_sources[idx] == 0)
+            .collect::<VecDeque<_>>();
+        let mut resolved = 0;
+        while let Some(idx) = ready.pop_front() {
+            resolved += 1;
+            for target in won[idx].clone() {
+                instances[target] += instances[idx];
+                unresolved_sources[target] -= 1;
+                if unresolved_sources[target] == 0 {
+                    ready.push_back(target);
+                }
             }
         }
+        if resolved < count {
+            bail!("The copy rule makes cards win copies of themselves");
+        }
+        Ok(instances.iter().sum())
     }
+}
 
-    Ok(copies.iter().sum())
+/// One point for the first match, doubled for every further match
+fn doubling_score(matches: usize) -> usize {
+    if matches == 0 {
+        0
+    } else {
+        1 << (matches - 1)
+    }
 }
 
-fn main() -> Result<()> {
-    println!("Answer for part 1: {}", part1(INPUT)?);
-    println!("Answer for part 2: {}", part2(INPUT)?);
+/// A card wins copies of as many following cards as it has matches
+fn copies_of_following(idx: usize, matches: usize) -> Range<usize> {
+    idx + 1..idx + 1 + matches
+}
+
+fn read_game<P: AsRef<Path>>(input: P) -> Result<ScratchcardGame> {
+    let cards = stream_items_from_file::<_, Card>(input)?
+        .enumerate()
+        .map(|(idx, card)| card.with_context(|| format!("Invalid card in line {}", idx + 1)))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(ScratchcardGame::from_cards(cards))
+}
+
+fn part1<P: AsRef<Path>>(input: P) -> Result<usize> {
+    Ok(read_game(input)?.total_score(doubling_score))
+}
+
+fn part2<P: AsRef<Path>>(input: P) -> Result<usize> {
+    read_game(input)?.total_cards(copies_of_following)
+}
+
+struct Day04;
+
+impl AocDay for Day04 {
+    const YEAR: u16 = 2023;
+    const DAY: u8 = 4;
 
-    Ok(())
+    fn part1(input: &Path) -> Result<Answer> {
+        Ok(part1(input)?.into())
+    }
+
+    fn part2(input: &Path) -> Result<Answer> {
+        Ok(part2(input)?.into())
+    }
+}
+
+fn main() -> Result<()> {
+    run::<Day04>()
 }
 
 #[cfg(test)]
@@ -87,21 +194,66 @@ mod tests_day04 {
     use advent_of_code_2023::test_helpers::create_example_file;
     use indoc::indoc;
 
-    #[test]
-    fn test_example() {
-        let (dir, file) = create_example_file(
-            indoc! {"
+    const EXAMPLE: &str = indoc! {"
             Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
             Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
             Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1
             Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83
             Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
             Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11
+        "};
+
+    #[test]
+    fn test_example() {
+        let (dir, file) = create_example_file(EXAMPLE, None);
+        assert_eq!(part1(&file).unwrap(), 13);
+        assert_eq!(part2(&file).unwrap(), 30);
+        drop(dir);
+    }
+
+    #[test]
+    fn test_rule_variants() {
+        let (dir, file) = create_example_file(EXAMPLE, None);
+        // Match counts are 4, 2, 2, 1, 0, 0
+        let game = read_game(&file).unwrap();
+        assert_eq!(game.total_score(|matches| matches * matches), 25);
+        // Copies of the previous cards instead of the following ones
+        assert_eq!(
+            game.total_cards(|idx, matches| idx.saturating_sub(matches)..idx)
+                .unwrap(),
+            6 + 3 + 2 + 1 + 1 + 1
+        );
+        assert!(game.total_cards(|idx, _| idx..idx + 1).is_err());
+        drop(dir);
+    }
+
+    #[test]
+    fn test_malformed_cards() {
+        assert_eq!(
+            "Card 1: 41 48 83 86 6 31".parse::<Card>().err(),
+            Some(CardError::MissingSeparator("|"))
+        );
+        assert_eq!(
+            "41 48 | 83 86".parse::<Card>().err(),
+            Some(CardError::MissingSeparator(":"))
+        );
+        assert_eq!(
+            "Card 1: 41 x8 | 83 86".parse::<Card>().err(),
+            Some(CardError::InvalidNumber("x8".to_owned()))
+        );
+
+        let (dir, file) = create_example_file(
+            indoc! {"
+            Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
+            Card 2: 13 32 20 16 61   61 30 68 82 17 32 24 19
         "},
             None,
         );
-        assert_eq!(part1(&file).unwrap(), 13);
-        assert_eq!(part2(&file).unwrap(), 30);
+        let error = part2(&file).unwrap_err();
+        assert_eq!(
+            format!("{:#}", error),
+            "Invalid card in line 2: Conversion error: Missing \"|\", expected \"Card <id>: <winning numbers> | <numbers>\""
+        );
         drop(dir);
     }
 }
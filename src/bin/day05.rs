@@ -1,11 +1,27 @@
-use std::collections::HashMap;
-use std::path::Path;
-use std::str::FromStr;
-
-use advent_of_code_2023::stream_file_blocks;
-use anyhow::Result;
-
-const INPUT: &str = "input/day05.txt";
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use advent_of_code_2023::{
+    answer::Answer,
+    ranges,
+    render_grid::GridRenderer,
+    runner::{options, run, AocDay, Solver},
+    stream_file_blocks, timed,
+};
+use anyhow::{anyhow, bail, Result};
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq, Eq)]
+enum AlmanacError {
+    #[error("Expected \"seeds: <numbers>\" before the maps, found {0:?}")]
+    InvalidSeeds(String),
+    #[error("Expected pairs of seed range start and length, found {0} numbers")]
+    IncompleteSeedRange(usize),
+    #[error("Invalid map header {0:?}, expected \"<from>-to-<to> map:\"")]
+    InvalidHeader(String),
+    #[error("Invalid rule {line:?} in {header:?}, expected three numbers")]
+    InvalidRule { header: String, line: String },
+}
 
 struct ConversionRange {
     dest_range_start: usize,
@@ -13,23 +29,21 @@ struct ConversionRange {
     range_length: usize,
 }
 
-impl FromStr for ConversionRange {
-    type Err = anyhow::Error;
-
-    fn from_str(s: &str) -> Result<Self> {
+impl ConversionRange {
+    fn parse(s: &str) -> Option<Self> {
         // Input is three numbers like this: 60 56 37
-
-        let mut nums = s.split_whitespace().map(|n| n.parse::<usize>());
-        // TODO: If next returns None, generate error
-        let dest_range_start = nums.next().unwrap()?;
-        let source_range_start = nums.next().unwrap()?;
-        let range_length = nums.next().unwrap()?;
-
-        Ok(ConversionRange {
-            dest_range_start,
-            source_range_start,
-            range_length,
-        })
+        let nums = s
+            .split_whitespace()
+            .map(|n| n.parse::<usize>().ok())
+            .collect::<Option<Vec<_>>>()?;
+        match nums[..] {
+            [dest_range_start, source_range_start, range_length] => Some(ConversionRange {
+                dest_range_start,
+                source_range_start,
+                range_length,
+            }),
+            _ => None,
+        }
     }
 }
 
@@ -61,6 +75,12 @@ impl ConversionRange {
         }
     }
 
+    /// Source value that is converted to the given destination value by this range, if any
+    fn try_invert(&self, dest_value: usize) -> Option<usize> {
+        let delta = dest_value.checked_sub(self.dest_range_start)?;
+        (delta < self.range_length).then(|| self.source_range_start + delta)
+    }
+
     fn split_and_convert_range(
         &self,
         source_start: usize,
@@ -107,24 +127,35 @@ impl ConversionRange {
 type Category = String;
 
 struct ConversionRuleSet {
+    /// Sorted by `source_range_start`. Rules of the puzzle input never overlap, so the order of
+    /// the input doesn't matter.
     rules: Vec<ConversionRange>,
     from: Category,
     to: Category,
 }
 
 impl TryFrom<&Vec<String>> for ConversionRuleSet {
-    type Error = anyhow::Error;
+    type Error = AlmanacError;
 
-    fn try_from(lines: &Vec<String>) -> Result<Self> {
-        let mut categories = lines[0].split_once(" ").unwrap().0.split("-");
-        let from = categories.next().unwrap().to_owned();
-        let to = categories.skip(1).next().unwrap().to_owned();
+    fn try_from(lines: &Vec<String>) -> Result<Self, AlmanacError> {
+        let header = &lines[0];
+        let (from, to) = header
+            .strip_suffix(" map:")
+            .and_then(|categories| categories.split_once("-to-"))
+            .ok_or_else(|| AlmanacError::InvalidHeader(header.to_owned()))?;
+        let (from, to) = (from.to_owned(), to.to_owned());
 
-        let conversions = lines
+        let mut conversions = lines
             .iter()
             .skip(1)
-            .map(|s| s.parse())
-            .collect::<Result<Vec<_>>>()?;
+            .map(|line| {
+                ConversionRange::parse(line).ok_or_else(|| AlmanacError::InvalidRule {
+                    header: header.to_owned(),
+                    line: line.to_owned(),
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        conversions.sort_by_key(|rule| rule.source_range_start);
 
         Ok(ConversionRuleSet {
             rules: conversions,
@@ -135,13 +166,36 @@ impl TryFrom<&Vec<String>> for ConversionRuleSet {
 }
 
 impl ConversionRuleSet {
-    fn convert_value(&self, source_value: usize) -> (Category, usize) {
-        let new_value = self
+    /// The only rule that can apply to a value is the last one starting at or before it
+    fn rule_for(&self, source_value: usize) -> Option<&ConversionRange> {
+        let idx = self
+            .rules
+            .partition_point(|rule| rule.source_range_start <= source_value);
+        idx.checked_sub(1).map(|idx| &self.rules[idx])
+    }
+
+    fn convert_value(&self, source_value: usize) -> usize {
+        self.rule_for(source_value)
+            .and_then(|range| range.try_convert(source_value))
+            .unwrap_or(source_value)
+    }
+
+    /// All values that are converted to the given one. Besides the rules that produce it, the
+    /// value can map to itself if no rule applies to it.
+    fn preimages(&self, value: usize) -> Vec<usize> {
+        let mut preimages = self
             .rules
             .iter()
-            .find_map(|range| range.try_convert(source_value))
-            .unwrap_or(source_value);
-        (self.to.to_owned(), new_value)
+            .filter_map(|range| range.try_invert(value))
+            .collect::<Vec<_>>();
+        if self
+            .rule_for(value)
+            .and_then(|range| range.try_convert(value))
+            .is_none()
+        {
+            preimages.push(value);
+        }
+        preimages
     }
 
     fn convert_range_rec(
@@ -182,12 +236,159 @@ impl ConversionRuleSet {
         // println!("({},{}) -> {:?}", source_start, source_end, result);
         result
     }
+
+    /// Converts every range, each of them independently of the others.
+    #[cfg(not(feature = "parallel"))]
+    fn convert_ranges(&self, ranges: &[ValueRange]) -> Vec<ValueRange> {
+        ranges
+            .iter()
+            .flat_map(|&(start, end)| self.convert_range(start, end))
+            .collect()
+    }
+
+    /// Converts every range, spread over all cores since they are independent of each other.
+    #[cfg(feature = "parallel")]
+    fn convert_ranges(&self, ranges: &[ValueRange]) -> Vec<ValueRange> {
+        use rayon::prelude::*;
+        ranges
+            .par_iter()
+            .flat_map_iter(|&(start, end)| self.convert_range(start, end))
+            .collect()
+    }
+}
+
+/// Mapping that shifts each of its pieces by an offset.
+///
+/// The pieces are half-open `(start, end, offset)` intervals that partition all values, sorted
+/// by start. This allows composing the conversions of several categories into one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct PiecewiseMap {
+    pieces: Vec<(usize, usize, isize)>,
+}
+
+impl PiecewiseMap {
+    fn identity() -> Self {
+        PiecewiseMap {
+            pieces: vec![(0, usize::MAX, 0)],
+        }
+    }
+
+    /// Index of the piece that contains the value
+    fn piece_index(&self, value: usize) -> usize {
+        self.pieces.partition_point(|&(_, end, _)| end <= value)
+    }
+
+    fn apply(&self, value: usize) -> usize {
+        let (_, _, offset) = self.pieces[self.piece_index(value)];
+        value.wrapping_add_signed(offset)
+    }
+
+    /// Smallest value that any value of the half-open range maps to, `None` for an empty range
+    fn min_image(&self, start: usize, end: usize) -> Option<usize> {
+        if start >= end {
+            return None;
+        }
+        self.pieces[self.piece_index(start)..]
+            .iter()
+            .take_while(|&&(piece_start, _, _)| piece_start < end)
+            .map(|&(piece_start, _, offset)| piece_start.max(start).wrapping_add_signed(offset))
+            .min()
+    }
+
+    /// The mapping that applies this one first and `next` on its result.
+    fn then(&self, next: &PiecewiseMap) -> PiecewiseMap {
+        let mut pieces: Vec<(usize, usize, isize)> = Vec::new();
+        for &(start, end, offset) in &self.pieces {
+            // Split the image of the piece wherever a piece of the next mapping ends
+            let (image_start, image_end) = (
+                start.wrapping_add_signed(offset),
+                end.wrapping_add_signed(offset),
+            );
+            let mut current = image_start;
+            for &(_, next_end, next_offset) in &next.pieces[next.piece_index(image_start)..] {
+                if current >= image_end {
+                    break;
+                }
+                let segment_end = next_end.min(image_end);
+                let piece = (
+                    current.wrapping_add_signed(-offset),
+                    segment_end.wrapping_add_signed(-offset),
+                    offset + next_offset,
+                );
+                match pieces.last_mut() {
+                    // Neighbors with the same offset are one piece
+                    Some(last) if last.1 == piece.0 && last.2 == piece.2 => last.1 = piece.1,
+                    _ => pieces.push(piece),
+                }
+                current = segment_end;
+            }
+        }
+        PiecewiseMap { pieces }
+    }
+}
+
+impl From<&ConversionRuleSet> for PiecewiseMap {
+    fn from(rule_set: &ConversionRuleSet) -> Self {
+        let mut pieces = Vec::new();
+        let mut current = 0;
+        for rule in &rule_set.rules {
+            let start = rule.source_range_start.max(current);
+            let end = rule.source_range_start + rule.range_length;
+            if start >= end {
+                continue;
+            }
+            if current < start {
+                pieces.push((current, start, 0));
+            }
+            let offset = rule.dest_range_start as isize - rule.source_range_start as isize;
+            pieces.push((start, end, offset));
+            current = end;
+        }
+        pieces.push((current, usize::MAX, 0));
+        PiecewiseMap { pieces }
+    }
 }
 
 struct AlmanacContent {
     rule_sets: HashMap<Category, ConversionRuleSet>,
 }
 
+impl AlmanacContent {
+    /// The rule sets that convert from one category to another, in order
+    fn chain(&self, from: &str, to: &str) -> Result<Vec<&ConversionRuleSet>> {
+        let mut chain = Vec::new();
+        let mut visited = HashSet::new();
+        let mut category = from;
+        while category != to {
+            if !visited.insert(category) {
+                bail!(
+                    "The conversions from {} run in circles through {} without reaching {}",
+                    from,
+                    category,
+                    to
+                );
+            }
+            let rule_set = self
+                .rule_sets
+                .get(category)
+                .ok_or_else(|| anyhow!("No conversion from {} towards {}", category, to))?;
+            chain.push(rule_set);
+            category = &rule_set.to;
+        }
+        Ok(chain)
+    }
+
+    /// Composes the conversions from one category to another into a single mapping.
+    fn compose(&self, from: &str, to: &str) -> Result<PiecewiseMap> {
+        Ok(self
+            .chain(from, to)?
+            .into_iter()
+            .fold(PiecewiseMap::identity(), |map, rule_set| {
+                map.then(&PiecewiseMap::from(rule_set))
+            }))
+    }
+}
+
 struct WrappedValue<T>(T);
 
 impl<T> TryFrom<WrappedValue<T>> for AlmanacContent
@@ -199,6 +400,8 @@ where
     fn try_from(blocks: WrappedValue<T>) -> Result<Self> {
         let rule_sets = blocks
             .0
+            // Additional blank lines result in empty blocks
+            .filter(|block| !block.is_empty())
             .map(|block| {
                 let set = ConversionRuleSet::try_from(&block)?;
                 Ok((set.from.to_owned(), set))
@@ -214,6 +417,20 @@ struct PuzzleInput {
     almanac: AlmanacContent,
 }
 
+impl PuzzleInput {
+    /// The seeds as half-open ranges, as part 2 reads them
+    fn seed_ranges(&self) -> Result<Vec<ValueRange>, AlmanacError> {
+        if !self.seeds_to_place.len().is_multiple_of(2) {
+            return Err(AlmanacError::IncompleteSeedRange(self.seeds_to_place.len()));
+        }
+        Ok(self
+            .seeds_to_place
+            .chunks(2)
+            .map(|s| (s[0], s[0] + s[1]))
+            .collect())
+    }
+}
+
 impl<T> TryFrom<WrappedValue<T>> for PuzzleInput
 where
     T: AsRef<Path>,
@@ -222,14 +439,17 @@ where
 
     fn try_from(path: WrappedValue<T>) -> Result<Self> {
         let mut blocks = stream_file_blocks(path.0)?;
-        let seed_info = blocks.next().unwrap();
-        let seeds_to_place = seed_info[0]
-            .split_once(": ")
-            .unwrap()
-            .1
-            .split_whitespace()
-            .map(|s| s.parse::<usize>().map_err(|e| e.into()))
-            .collect::<Result<Vec<_>>>()?;
+        let seed_info = blocks.next().unwrap_or_default();
+        let seed_line = seed_info.first().map_or("", String::as_str);
+        let seeds_to_place = seed_line
+            .strip_prefix("seeds:")
+            .and_then(|seeds| {
+                seeds
+                    .split_whitespace()
+                    .map(|s| s.parse::<usize>().ok())
+                    .collect::<Option<Vec<_>>>()
+            })
+            .ok_or_else(|| AlmanacError::InvalidSeeds(seed_line.to_owned()))?;
 
         let almanac = AlmanacContent::try_from(WrappedValue(blocks))?;
 
@@ -241,76 +461,272 @@ where
 }
 
 fn part1<P: AsRef<Path>>(input: P) -> Result<usize> {
-    let puzzle_input = PuzzleInput::try_from(WrappedValue(input))?;
-
-    let mut seed_states = puzzle_input
+    let puzzle_input = timed!("parse", PuzzleInput::try_from(WrappedValue(input)))?;
+    let seed_to_location = puzzle_input.almanac.compose("seed", "location")?;
+    puzzle_input
         .seeds_to_place
         .iter()
-        .map(|seed| ("seed".to_owned(), *seed))
-        .collect::<Vec<_>>();
+        .map(|&seed| seed_to_location.apply(seed))
+        .min()
+        .ok_or_else(|| anyhow!("No seeds to place"))
+}
 
-    while seed_states
+fn part2<P: AsRef<Path>>(input: P) -> Result<usize> {
+    let puzzle_input = timed!("parse", PuzzleInput::try_from(WrappedValue(input)))?;
+    let seed_to_location = puzzle_input.almanac.compose("seed", "location")?;
+    puzzle_input
+        .seed_ranges()?
+        .into_iter()
+        .filter_map(|(start, end)| seed_to_location.min_image(start, end))
+        .min()
+        .ok_or_else(|| anyhow!("No seeds to place"))
+}
+
+/// Tries all locations in ascending order until one of them can be traced back to a seed, by
+/// converting backwards through the categories. The location of the first seed of any range
+/// bounds the search.
+fn part2_reverse<P: AsRef<Path>>(input: P) -> Result<usize> {
+    let puzzle_input = timed!("parse", PuzzleInput::try_from(WrappedValue(input)))?;
+    let chain = puzzle_input.almanac.chain("seed", "location")?;
+    let seed_ranges = puzzle_input.seed_ranges()?;
+    let Some(bound) = seed_ranges
         .iter()
-        .any(|(category, _)| category != "location")
-    {
-        seed_states.iter_mut().for_each(|(category, number)| {
-            let (new_cat, new_val) =
-                puzzle_input.almanac.rule_sets[category].convert_value(*number);
-            *category = new_cat;
-            *number = new_val;
-        });
-    }
+        .filter(|(start, end)| start < end)
+        .map(|&(start, _)| {
+            chain
+                .iter()
+                .fold(start, |value, rule_set| rule_set.convert_value(value))
+        })
+        .min()
+    else {
+        bail!("No seeds to place");
+    };
+    (0..=bound)
+        .find(|&location| {
+            let mut candidates = vec![location];
+            for rule_set in chain.iter().rev() {
+                candidates = candidates
+                    .into_iter()
+                    .flat_map(|value| rule_set.preimages(value))
+                    .collect();
+            }
+            candidates.iter().any(|seed| {
+                seed_ranges
+                    .iter()
+                    .any(|(start, end)| (start..end).contains(&seed))
+            })
+        })
+        .ok_or_else(|| anyhow!("No location can be traced back to a seed"))
+}
 
-    let lowest_location_number = seed_states
+/// Converts the seeds category by category instead of composing the conversions first.
+fn part1_stages<P: AsRef<Path>>(input: P) -> Result<usize> {
+    let puzzle_input = timed!("parse", PuzzleInput::try_from(WrappedValue(input)))?;
+    let chain = puzzle_input.almanac.chain("seed", "location")?;
+
+    let mut numbers = puzzle_input.seeds_to_place;
+    for rule_set in chain {
+        numbers
+            .iter_mut()
+            .for_each(|number| *number = rule_set.convert_value(*number));
+    }
+    numbers
         .into_iter()
-        .map(|(_, number)| number)
         .min()
-        .unwrap();
-    Ok(lowest_location_number)
+        .ok_or_else(|| anyhow!("No seeds to place"))
 }
 
-fn part2<P: AsRef<Path>>(input: P) -> Result<usize> {
-    let puzzle_input = PuzzleInput::try_from(WrappedValue(input))?;
+/// Converts the seed ranges category by category instead of composing the conversions first.
+/// Prints the number of ranges per stage to stderr if `debug` is set.
+fn part2_stages<P: AsRef<Path>>(input: P, debug: bool) -> Result<usize> {
+    let puzzle_input = timed!("parse", PuzzleInput::try_from(WrappedValue(input)))?;
 
     // For this one, we need to be a bit smarter.
     // Instead of handling every single number, we handle ranges of numbers.
     // Every range of numbers may be split into multiple output ranges when we apply a conversion
     // rule.
-    // We just keep collecting those ranges.
-    // There still are some ways to make this more efficient, e.g by adding merging logic that
-    // can merge adjacent ranges with no gaps.
-    let mut value_ranges = puzzle_input
-        .seeds_to_place
-        .chunks(2)
-        .map(|s| (s[0], s[0] + s[1]))
-        .collect::<Vec<_>>();
-    let mut current_category = "seed".to_owned();
-
-    while current_category != "location" {
-        // println!( "Input: {} ranges, category {}", value_ranges.len(), current_category);
-        value_ranges = value_ranges
-            .into_iter()
-            .flat_map(|(start, end)| {
-                puzzle_input.almanac.rule_sets[&current_category].convert_range(start, end)
-            })
-            .collect::<Vec<_>>();
-        current_category = puzzle_input.almanac.rule_sets[&current_category]
-            .to
-            .to_owned();
+    // We just keep collecting those ranges, merging overlapping and adjacent ones after each
+    // stage so their number doesn't explode.
+    let chain = puzzle_input.almanac.chain("seed", "location")?;
+    let mut value_ranges = puzzle_input.seed_ranges()?;
+    ranges::normalize(&mut value_ranges);
+
+    for rule_set in chain {
+        value_ranges = rule_set.convert_ranges(&value_ranges);
+        let converted = value_ranges.len();
+        ranges::normalize(&mut value_ranges);
+        if debug {
+            eprintln!(
+                "{} -> {}: {} ranges, {} after merging",
+                rule_set.from,
+                rule_set.to,
+                converted,
+                value_ranges.len()
+            );
+        }
     }
-    let lowest_location_number = value_ranges
+    value_ranges
         .into_iter()
         .map(|(start, _)| start)
         .min()
-        .unwrap();
-    Ok(lowest_location_number)
+        .ok_or_else(|| anyhow!("No seeds to place"))
 }
 
-fn main() -> Result<()> {
-    println!("Answer for part 1: {}", part1(INPUT)?);
-    println!("Answer for part 2: {}", part2(INPUT)?);
+/// Width of the bands in grid units, values are scaled to fit
+const PIPELINE_WIDTH: f64 = 100.0;
+/// Vertical distance between the tops of consecutive bands, the mappings are drawn in between
+const BAND_SPACING: f64 = 6.0;
+/// Ranges narrower than this (in grid units) are widened so they stay visible
+const MIN_RANGE_WIDTH: f64 = 0.2;
+const BAND_COLOR: &str = "#e0e0e0";
+const RANGE_COLOR: &str = "#4e79a7";
+const RULE_COLOR: &str = "#f28e2b66";
+const MINIMUM_COLOR: &str = "#e15759";
+
+/// Draws each category from seed to location as a horizontal band with the value ranges that
+/// reach it, and the rules between consecutive categories as bands connecting source and
+/// destination intervals. The seed that ends up at the lowest location is traced through all
+/// categories. Hovering a range or rule shows its values.
+fn render_pipeline<P: AsRef<Path>>(input: P) -> Result<GridRenderer<f64>> {
+    let puzzle_input = PuzzleInput::try_from(WrappedValue(input))?;
+    let chain = puzzle_input.almanac.chain("seed", "location")?;
+
+    let mut value_ranges = puzzle_input.seed_ranges()?;
+    ranges::normalize(&mut value_ranges);
+    let mut stages = vec![value_ranges];
+    for rule_set in &chain {
+        let mut converted = rule_set.convert_ranges(stages.last().unwrap());
+        ranges::normalize(&mut converted);
+        stages.push(converted);
+    }
+    let categories = std::iter::once("seed").chain(chain.iter().map(|set| set.to.as_str()));
+
+    let max_value = stages
+        .iter()
+        .flatten()
+        .map(|&(_, end)| end)
+        .chain(chain.iter().flat_map(|set| {
+            set.rules.iter().flat_map(|rule| {
+                [
+                    rule.source_range_start + rule.range_length,
+                    rule.dest_range_start + rule.range_length,
+                ]
+            })
+        }))
+        .max()
+        .unwrap_or(1)
+        .max(1);
+    let scale = |value: usize| value as f64 / max_value as f64 * PIPELINE_WIDTH;
+    let band_y = |stage: usize| stage as f64 * BAND_SPACING;
+
+    let mut renderer = GridRenderer::new();
+    for (stage, (category, value_ranges)) in categories.zip(&stages).enumerate() {
+        let y = band_y(stage);
+        renderer.add_colored_rect(y, 0.0, 1.0, PIPELINE_WIDTH, BAND_COLOR.to_owned(), None);
+        // Centered above the left end of the band
+        renderer.add_labeled_tile(y - 1.0, 2.0, category.to_owned(), "black".to_owned());
+        for &(start, end) in value_ranges {
+            renderer.add_colored_rect(
+                y,
+                scale(start),
+                1.0,
+                (scale(end) - scale(start)).max(MIN_RANGE_WIDTH),
+                RANGE_COLOR.to_owned(),
+                Some(format!("{}: {}..{}", category, start, end)),
+            );
+        }
+    }
+    // Polygon corners are cell centers, hence the offsets of half a cell
+    for (stage, rule_set) in chain.iter().enumerate() {
+        let top = band_y(stage) + 1.0 - 0.5;
+        let bottom = band_y(stage + 1) - 0.5;
+        for rule in &rule_set.rules {
+            let source = (
+                rule.source_range_start,
+                rule.source_range_start + rule.range_length,
+            );
+            let dest = (
+                rule.dest_range_start,
+                rule.dest_range_start + rule.range_length,
+            );
+            renderer.add_polygon(
+                [
+                    (top, scale(source.0) - 0.5),
+                    (top, scale(source.1) - 0.5),
+                    (bottom, scale(dest.1) - 0.5),
+                    (bottom, scale(dest.0) - 0.5),
+                ],
+                RULE_COLOR.to_owned(),
+            );
+        }
+    }
+
+    // Follow the lowest location back to its seed, preferring values that are actually reached
+    if let Some(&(lowest, _)) = stages.last().and_then(|ranges| ranges.first()) {
+        let mut trace = vec![lowest];
+        for (rule_set, value_ranges) in chain.iter().zip(&stages).rev() {
+            let value = *trace.last().unwrap();
+            let previous = rule_set.preimages(value).into_iter().find(|v| {
+                value_ranges
+                    .iter()
+                    .any(|&(start, end)| (start..end).contains(v))
+            });
+            match previous {
+                Some(previous) => trace.push(previous),
+                None => break,
+            }
+        }
+        let points = trace
+            .iter()
+            .rev()
+            .enumerate()
+            .map(|(stage, &value)| (band_y(stage), scale(value) - 0.5));
+        renderer.add_path(points, MINIMUM_COLOR.to_owned(), 0.2);
+    }
+    renderer.add_legend(&[
+        (RANGE_COLOR, "value range"),
+        (RULE_COLOR, "rule"),
+        (MINIMUM_COLOR, "lowest location"),
+    ]);
+    Ok(renderer)
+}
+
+struct Day05;
+
+impl AocDay for Day05 {
+    const YEAR: u16 = 2023;
+    const DAY: u8 = 5;
+
+    fn part1(input: &Path) -> Result<Answer> {
+        Ok(part1(input)?.into())
+    }
+
+    /// `--visualize PATH` additionally stores an SVG of the range pipeline, see
+    /// [`render_pipeline`].
+    fn part2(input: &Path) -> Result<Answer> {
+        if let Some(path) = options().get::<PathBuf>("visualize")? {
+            render_pipeline(input)?.store_svg(path)?;
+        }
+        Ok(part2(input)?.into())
+    }
+
+    fn part1_alternatives() -> Vec<(&'static str, Solver)> {
+        vec![("stages", |input| Ok(part1_stages(input)?.into()))]
+    }
+
+    /// `--debug` prints how many ranges are left after each stage.
+    fn part2_alternatives() -> Vec<(&'static str, Solver)> {
+        vec![
+            ("stages", |input| {
+                Ok(part2_stages(input, options().flag("debug"))?.into())
+            }),
+            ("reverse", |input| Ok(part2_reverse(input)?.into())),
+        ]
+    }
+}
 
-    Ok(())
+fn main() -> Result<()> {
+    run::<Day05>()
 }
 
 #[cfg(test)]
@@ -319,10 +735,7 @@ mod tests_day05 {
     use advent_of_code_2023::test_helpers::create_example_file;
     use indoc::indoc;
 
-    #[test]
-    fn test_example() {
-        let (dir, file) = create_example_file(
-            indoc! {"
+    const EXAMPLE: &str = indoc! {"
             seeds: 79 14 55 13
 
             seed-to-soil map:
@@ -356,11 +769,202 @@ mod tests_day05 {
             humidity-to-location map:
             60 56 37
             56 93 4
+        "};
+
+    #[test]
+    fn test_example() {
+        let (dir, file) = create_example_file(EXAMPLE, None);
+        assert_eq!(part1(&file).unwrap(), 35);
+        assert_eq!(part2(&file).unwrap(), 46);
+        assert_eq!(part1_stages(&file).unwrap(), 35);
+        assert_eq!(part2_stages(&file, false).unwrap(), 46);
+        assert_eq!(part2_reverse(&file).unwrap(), 46);
+        drop(dir);
+    }
+
+    #[test]
+    fn test_reverse_matches_forward() {
+        // Different seed ranges on the example almanac, all methods need to agree
+        for seeds in ["seeds: 0 10", "seeds: 90 20 5 3", "seeds: 14 1 60 2 200 7"] {
+            let input = EXAMPLE.replacen("seeds: 79 14 55 13", seeds, 1);
+            let (dir, file) = create_example_file(&input, None);
+            let expected = part2_stages(&file, false).unwrap();
+            assert_eq!(part2(&file).unwrap(), expected, "{}", seeds);
+            assert_eq!(part2_reverse(&file).unwrap(), expected, "{}", seeds);
+            drop(dir);
+        }
+    }
+
+    fn parse_error(input: &str) -> AlmanacError {
+        let (dir, file) = create_example_file(input, None);
+        let error = PuzzleInput::try_from(WrappedValue(&file)).err().unwrap();
+        drop(dir);
+        error.downcast().unwrap()
+    }
+
+    #[test]
+    fn test_malformed_almanacs() {
+        assert_eq!(
+            parse_error("79 14 55 13\n"),
+            AlmanacError::InvalidSeeds("79 14 55 13".to_owned())
+        );
+        assert_eq!(parse_error(""), AlmanacError::InvalidSeeds(String::new()));
+        assert_eq!(
+            parse_error("seeds: 79 x\n"),
+            AlmanacError::InvalidSeeds("seeds: 79 x".to_owned())
+        );
+        assert_eq!(
+            parse_error("seeds: 79\n\nseed-soil map:\n50 98 2\n"),
+            AlmanacError::InvalidHeader("seed-soil map:".to_owned())
+        );
+        let truncated = parse_error("seeds: 79\n\nseed-to-soil map:\n50 98 2\n52 50\n");
+        assert_eq!(
+            truncated,
+            AlmanacError::InvalidRule {
+                header: "seed-to-soil map:".to_owned(),
+                line: "52 50".to_owned()
+            }
+        );
+        assert_eq!(
+            truncated.to_string(),
+            "Invalid rule \"52 50\" in \"seed-to-soil map:\", expected three numbers"
+        );
+
+        let (dir, file) = create_example_file(&EXAMPLE.replacen(" 13", "", 1), None);
+        assert!(part2(&file).is_err());
+        drop(dir);
+    }
+
+    #[test]
+    fn test_unreachable_locations() {
+        // Converting back and forth between seed and soil never gets anywhere
+        let (dir, file) = create_example_file(
+            indoc! {"
+            seeds: 79 14
+
+            seed-to-soil map:
+            50 98 2
+
+            soil-to-seed map:
+            98 50 2
         "},
             None,
         );
-        assert_eq!(part1(&file).unwrap(), 35);
-        assert_eq!(part2(&file).unwrap(), 46);
+        for error in [
+            part1(&file).unwrap_err(),
+            part2(&file).unwrap_err(),
+            part1_stages(&file).unwrap_err(),
+            part2_stages(&file, false).unwrap_err(),
+        ] {
+            assert_eq!(
+                error.to_string(),
+                "The conversions from seed run in circles through seed without reaching location"
+            );
+        }
+        drop(dir);
+
+        // A missing conversion fails instead of panicking
+        let (dir, file) = create_example_file(&EXAMPLE.replace("light-to", "lite-to"), None);
+        assert!(part1_stages(&file).is_err());
+        assert!(part2_stages(&file, false).is_err());
+        drop(dir);
+
+        let (dir, file) = create_example_file(&EXAMPLE.replacen("79 14 55 13", "", 1), None);
+        assert!(part1_stages(&file).is_err());
+        assert!(part2_stages(&file, false).is_err());
+        assert!(part2_reverse(&file).is_err());
+        drop(dir);
+
+        // Only empty seed ranges
+        let (dir, file) = create_example_file(&EXAMPLE.replacen("79 14 55 13", "79 0", 1), None);
+        assert_eq!(
+            part2_reverse(&file).unwrap_err().to_string(),
+            "No seeds to place"
+        );
+        assert!(part2(&file).is_err());
+        drop(dir);
+    }
+
+    #[test]
+    fn test_render_pipeline() {
+        let (dir, file) = create_example_file(EXAMPLE, None);
+        let mut svg = Vec::new();
+        render_pipeline(&file)
+            .unwrap()
+            .render_svg(&mut svg)
+            .unwrap();
+        let svg = String::from_utf8(svg).unwrap();
+        let count = |color: &str| svg.matches(&format!(r#"fill="{}""#, color)).count();
+        assert_eq!(count(BAND_COLOR), 8);
+        assert!(svg.contains("<title>seed: 55..68</title>"));
+        assert!(svg.contains("<title>location: 46..61</title>"));
+        // One polygon per rule plus the legend swatch
+        assert_eq!(count(RULE_COLOR), 18 + 1);
+        // Seed 82 is the one ending up at location 46
+        assert!(svg.contains(r#"<polyline points="81.5,0 83.5,6"#));
+        drop(dir);
+    }
+
+    #[test]
+    fn test_preimages() {
+        let (dir, file) = create_example_file(EXAMPLE, None);
+        let almanac = PuzzleInput::try_from(WrappedValue(&file)).unwrap().almanac;
+        let seed_to_soil = &almanac.rule_sets["seed"];
+        // 50 is only the image of 98, as 50 itself is converted to 52
+        assert_eq!(seed_to_soil.preimages(50), vec![98]);
+        assert_eq!(seed_to_soil.preimages(99), vec![97]);
+        assert_eq!(seed_to_soil.preimages(10), vec![10]);
+        drop(dir);
+    }
+
+    #[test]
+    fn test_rule_lookup() {
+        let (dir, file) = create_example_file(EXAMPLE, None);
+        let almanac = PuzzleInput::try_from(WrappedValue(&file)).unwrap().almanac;
+        for rule_set in almanac.rule_sets.values() {
+            assert!(rule_set
+                .rules
+                .windows(2)
+                .all(|w| w[0].source_range_start <= w[1].source_range_start));
+            // The binary search finds the same rule as scanning all of them
+            for value in 0..120 {
+                let scanned = rule_set
+                    .rules
+                    .iter()
+                    .find_map(|range| range.try_convert(value))
+                    .unwrap_or(value);
+                assert_eq!(rule_set.convert_value(value), scanned);
+            }
+        }
+        drop(dir);
+    }
+
+    #[test]
+    fn test_composed_map() {
+        let (dir, file) = create_example_file(EXAMPLE, None);
+        let almanac = PuzzleInput::try_from(WrappedValue(&file)).unwrap().almanac;
+        let seed_to_soil = almanac.compose("seed", "soil").unwrap();
+        assert_eq!(
+            seed_to_soil.pieces,
+            vec![
+                (0, 50, 0),
+                (50, 98, 2),
+                (98, 100, -48),
+                (100, usize::MAX, 0)
+            ]
+        );
+        let seed_to_location = almanac.compose("seed", "location").unwrap();
+        // Every seed ends up where converting category by category takes it
+        let chain = almanac.chain("seed", "location").unwrap();
+        for seed in 0..120 {
+            let value = chain
+                .iter()
+                .fold(seed, |value, rule_set| rule_set.convert_value(value));
+            assert_eq!(seed_to_location.apply(seed), value, "seed {}", seed);
+        }
+        assert_eq!(seed_to_location.min_image(79, 93), Some(46));
+        assert_eq!(seed_to_location.min_image(79, 79), None);
+        assert!(almanac.compose("seed", "nowhere").is_err());
         drop(dir);
     }
 }
@@ -1,44 +1,14 @@
 use std::path::Path;
 
-use advent_of_code_2023::read_lines;
+use advent_of_code_2023::{
+    answer::Answer,
+    maths::quadratic::count_beating,
+    read_lines,
+    runner::{run, AocDay},
+};
 use anyhow::Result;
 
-const INPUT: &str = "input/day06.txt";
-
-/* Given a time-limit T and distance record D, we can calculate our distance in the race like this:
- * d(t) = t * (T - t) = -t^2 + T*t
- *
- * From that, we can calculate by how much we would beat the record for a given acceleration time t
- * using this formula: d(t) = -t^2 + T*t - R
- *
- * Solving the polynomial for the roots gives:
- *
- * x_1/2 = (-T +- sqrt(T^2 - 4*R)) / (-2)
- *
- * This is derived from the general formula for roots of quadratic functions :
- *
- * x_1/2 = (-b +- sqrt(b^2 - 4ac)) / (2a)
- *
- * given the function f(x) = ax^2 + bx + c
- * so a=-1, b=T and c=R
- */
-
-fn get_beating_range(time_limit: usize, distance_record: usize) -> (usize, usize) {
-    let sqrt_term = time_limit.pow(2) - (4 * distance_record);
-    let sqrt_value = (sqrt_term as f64).sqrt() / (2f64);
-    let linear_part = time_limit as f64 / 2f64;
-
-    // The small addition and subtraction value is a dirty workaround.
-    // If the root falls exactly on an integer value, we still need the ceil() and floor() calls
-    // below to add/subtract one, because the exact value would result in us matching the record
-    // distance exactly.
-    let lower = linear_part - sqrt_value + 0.00001;
-    let upper = linear_part + sqrt_value - 0.00001;
-
-    (lower.ceil() as usize, upper.floor() as usize)
-}
-
-fn part1<P: AsRef<Path>>(input: P) -> Result<usize> {
+fn part1<P: AsRef<Path>>(input: P) -> Result<u64> {
     let mut lines = read_lines(input)?;
     let times = lines
         .next()
@@ -47,7 +17,7 @@ fn part1<P: AsRef<Path>>(input: P) -> Result<usize> {
         .unwrap()
         .1
         .split_whitespace()
-        .map(|v| v.parse::<usize>().map_err(|e| e.into()))
+        .map(|v| v.parse::<u64>().map_err(|e| e.into()))
         .collect::<Result<Vec<_>>>()?;
     let distances = lines
         .next()
@@ -56,16 +26,17 @@ fn part1<P: AsRef<Path>>(input: P) -> Result<usize> {
         .unwrap()
         .1
         .split_whitespace()
-        .map(|v| v.parse::<usize>().map_err(|e| e.into()))
+        .map(|v| v.parse::<u64>().map_err(|e| e.into()))
         .collect::<Result<Vec<_>>>()?;
-    let res = times.into_iter().zip(distances.into_iter()).map(|(t, d)| {
-        let (lower, upper) = get_beating_range(t, d);
-        upper - lower + 1
-    }).product();
+    let res = times
+        .into_iter()
+        .zip(distances)
+        .map(|(t, d)| count_beating(t, d))
+        .product();
     Ok(res)
 }
 
-fn part2<P: AsRef<Path>>(input: P) -> Result<usize> {
+fn part2<P: AsRef<Path>>(input: P) -> Result<u64> {
     let mut lines = read_lines(input)?;
     let time = lines
         .next()
@@ -75,7 +46,7 @@ fn part2<P: AsRef<Path>>(input: P) -> Result<usize> {
         .1
         .split_whitespace()
         .collect::<String>()
-        .parse::<usize>()?;
+        .parse::<u64>()?;
     let distance = lines
         .next()
         .unwrap()?
@@ -84,27 +55,73 @@ fn part2<P: AsRef<Path>>(input: P) -> Result<usize> {
         .1
         .split_whitespace()
         .collect::<String>()
-        .parse::<usize>()?;
-    let (lower, upper) = get_beating_range(time, distance);
-    Ok(upper - lower + 1)
+        .parse::<u64>()?;
+    Ok(count_beating(time, distance))
 }
 
-fn main() -> Result<()> {
-    println!("Answer for part 1: {}", part1(INPUT)?);
-    println!("Answer for part 2: {}", part2(INPUT)?);
+struct Day06;
+
+impl AocDay for Day06 {
+    const YEAR: u16 = 2023;
+    const DAY: u8 = 6;
 
-    Ok(())
+    fn part1(input: &Path) -> Result<Answer> {
+        Ok(part1(input)?.into())
+    }
+
+    fn part2(input: &Path) -> Result<Answer> {
+        Ok(part2(input)?.into())
+    }
+}
+
+fn main() -> Result<()> {
+    run::<Day06>()
 }
 
 #[cfg(test)]
 mod tests_day06 {
     use super::*;
-    use advent_of_code_2023::test_helpers::create_example_file;
+    use advent_of_code_2023::{maths::quadratic::beating_range, test_helpers::create_example_file};
     use indoc::indoc;
 
+    /// The previous float based solution, which needs an epsilon to handle roots on integers
+    fn get_beating_range_float(time_limit: usize, distance_record: usize) -> (usize, usize) {
+        let sqrt_term = time_limit.pow(2) - (4 * distance_record);
+        let sqrt_value = (sqrt_term as f64).sqrt() / (2f64);
+        let linear_part = time_limit as f64 / 2f64;
+        let lower = linear_part - sqrt_value + 0.00001;
+        let upper = linear_part + sqrt_value - 0.00001;
+        (lower.ceil() as usize, upper.floor() as usize)
+    }
+
     #[test]
-    fn test_range() {
-        assert_eq!(get_beating_range(7, 9), (2, 5));
+    fn test_matches_float_solution() {
+        for time_limit in 1..60 {
+            for distance_record in 0..time_limit * time_limit / 4 {
+                let range = beating_range(time_limit as u64, distance_record as u64);
+                assert_eq!(
+                    (*range.start() as usize, *range.end() as usize),
+                    get_beating_range_float(time_limit, distance_record),
+                    "T={}, R={}",
+                    time_limit,
+                    distance_record
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_root_close_to_integer() {
+        // The lower root is about 1e-6 below 1000, which the float epsilon pushes above it
+        let (time_limit, distance_record) = (1_000_000, 1000 * 999_000 - 1);
+        assert_eq!(
+            beating_range(time_limit as u64, distance_record as u64),
+            1000..=999_000
+        );
+        assert_eq!(
+            get_beating_range_float(time_limit, distance_record),
+            (1001, 998_999)
+        );
     }
 
     #[test]
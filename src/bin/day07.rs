@@ -1,187 +1,260 @@
 use std::{collections::HashMap, path::Path, str::FromStr};
 
-use advent_of_code_2023::stream_items_from_file;
-use anyhow::{anyhow, Result};
-use std::cmp::Ordering;
+use advent_of_code_2023::{
+    answer::Answer,
+    runner::{options, run, AocDay},
+    stream_items_from_file, timed,
+};
+use anyhow::{Context, Result};
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq, Eq)]
+enum HandError {
+    #[error("Missing ' ' between the hand and the bid")]
+    MissingBid,
+    #[error("Invalid bid {0:?}")]
+    InvalidBid(String),
+    #[error("Invalid card {card:?} at position {position}")]
+    InvalidCard { card: char, position: usize },
+    #[error("Expected {expected} cards, found {found}")]
+    WrongHandSize { expected: usize, found: usize },
+}
 
-const INPUT: &str = "input/day07.txt";
+const STANDARD_RANKS: &str = "23456789TJQKA";
+const HAND_SIZE: usize = 5;
+
+/// Category of a hand: how often each card occurs, from the most frequent to the least frequent
+/// one.
+///
+/// Comparing these lexicographically ranks the categories of any hand size. For five cards,
+/// five of a kind `[5]` beats four of a kind `[4, 1]`, which beats a full house `[3, 2]` and so
+/// on down to high card `[1, 1, 1, 1, 1]`.
+type Category = Vec<usize>;
+
+/// The rules of a game of Camel Cards: how strong the cards are and how hands are classified.
+trait HandRules {
+    /// Strength of a card when breaking ties between hands of the same category, or `None` if
+    /// the card is not part of the deck
+    fn card_value(&self, card: char) -> Option<usize>;
+
+    /// Number of cards in each hand
+    fn hand_size(&self) -> usize {
+        HAND_SIZE
+    }
 
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
-struct Card(usize);
-
-impl TryFrom<char> for Card {
-    type Error = anyhow::Error;
-
-    fn try_from(value: char) -> Result<Self, Self::Error> {
-        Ok(Card(
-            value
-                .to_digit(10)
-                .or_else(|| {
-                    // alpha cards from highest to lowest: A, K, Q, J, T
-                    match value {
-                        'A' => Some(14),
-                        'K' => Some(13),
-                        'Q' => Some(12),
-                        'J' => Some(11),
-                        'T' => Some(10),
-                        _ => None,
-                    }
-                })
-                .ok_or_else(|| anyhow!("Invalid card"))? as usize,
-        ))
+    /// Whether the card stands in for whatever card makes the hand strongest
+    fn is_wildcard(&self, _card: char) -> bool {
+        false
+    }
+
+    fn classify(&self, cards: &[char]) -> Category {
+        let mut card_map: HashMap<char, usize> = HashMap::new();
+        let mut wildcards = 0;
+        for &card in cards {
+            if self.is_wildcard(card) {
+                wildcards += 1;
+            } else {
+                *card_map.entry(card).or_insert(0) += 1;
+            }
+        }
+        let mut counts = card_map.into_values().collect::<Vec<_>>();
+        counts.sort_unstable_by(|a, b| b.cmp(a));
+        // It is always best to turn the wildcards into the most frequent card
+        match counts.first_mut() {
+            Some(most_frequent) => *most_frequent += wildcards,
+            None if wildcards > 0 => counts.push(wildcards),
+            None => {}
+        }
+        counts
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Clone)]
-struct Hand(Vec<Card>);
+/// Ranks the cards by their position in `ranks`, from the weakest to the strongest one
+fn rank_of(ranks: &str, card: char) -> Option<usize> {
+    ranks.chars().position(|c| c == card)
+}
 
-impl FromStr for Hand {
-    type Err = anyhow::Error;
+/// The rules of part 1
+struct Standard;
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(Hand(
-            s.chars()
-                .map(|c| Card::try_from(c))
-                .collect::<Result<Vec<_>, _>>()?,
-        ))
+impl HandRules for Standard {
+    fn card_value(&self, card: char) -> Option<usize> {
+        rank_of(STANDARD_RANKS, card)
     }
 }
 
-impl Hand {
-    fn classify(&self) -> usize {
-        let mut card_map: HashMap<usize, usize> = HashMap::new();
+/// The rules of part 2, where J cards are jokers: the weakest card, but they count as any card
+/// for the category.
+struct JokerRules;
 
-        for card in &self.0 {
-            *card_map.entry(card.0).or_insert(0) += 1;
-        }
+impl HandRules for JokerRules {
+    fn card_value(&self, card: char) -> Option<usize> {
+        rank_of("J23456789TQKA", card)
+    }
 
-        match card_map.len() {
-            5 => 0, // Five distinct cards, lowest category
-            4 => 1, // One pair and three distinct cards
-            3 => {
-                // Either two pair or three of a kind
-                if card_map.values().any(|v| *v == 3) {
-                    3 // three of a kind
-                } else {
-                    2 // two pair
-                }
-            }
-            2 => {
-                // Either full house or four of a kind
-                if card_map.values().any(|v| *v == 3) {
-                    4 // Full house
-                } else {
-                    5 // Four of a kind
-                }
-            }
-            1 => 6, // Five of a kind, highest rating
+    fn is_wildcard(&self, card: char) -> bool {
+        card == 'J'
+    }
+}
+
+/// Rules with a configurable deck, e.g. for puzzle variants with more or different cards.
+struct CustomRules {
+    /// All cards from the weakest to the strongest one
+    ranks: String,
+    wildcard: Option<char>,
+    hand_size: usize,
+}
 
-            l @ _ => panic!("Unexpected number of different cards: {}", l),
+impl CustomRules {
+    /// The ranks given with `--ranks` and the size given with `--hand-size`, if any of them is
+    /// set. Part 2 turns the `--wildcard` card (J by default) into the weakest card like the
+    /// jokers of the puzzle.
+    fn from_options(with_wildcard: bool) -> Result<Option<Self>> {
+        let ranks = options().get::<String>("ranks")?;
+        let hand_size = options().get::<usize>("hand-size")?;
+        if ranks.is_none() && hand_size.is_none() {
+            return Ok(None);
         }
+        let mut rules = CustomRules {
+            ranks: ranks.unwrap_or_else(|| STANDARD_RANKS.to_owned()),
+            wildcard: None,
+            hand_size: hand_size.unwrap_or(HAND_SIZE),
+        };
+        if with_wildcard {
+            let wildcard = options().get::<char>("wildcard")?.unwrap_or('J');
+            rules.ranks = std::iter::once(wildcard)
+                .chain(rules.ranks.chars().filter(|&c| c != wildcard))
+                .collect();
+            rules.wildcard = Some(wildcard);
+        }
+        Ok(Some(rules))
     }
+}
 
-    fn patch_jokers(&mut self) {
-        self.0.iter_mut().for_each(|card| {
-            if card.0 == 11 {
-                card.0 = 1;
-            }
-        });
+impl HandRules for CustomRules {
+    fn card_value(&self, card: char) -> Option<usize> {
+        rank_of(&self.ranks, card)
     }
 
-    fn classify_with_jokers(&self) -> usize {
-        // FIXME: Instead of doing this for every comparison, we should calculate this when we
-        // create the hand and cache the value.
-        self.0
-            .iter()
-            // We can assume that it will always be best to replace jokers by other cards in the
-            // hand
-            .map(|joker_replacement| {
-                let mut copy = (*self).clone();
-                // Filter and replace the jokers
-                copy.0
-                    .iter_mut()
-                    .filter(|c| c.0 == 1)
-                    .for_each(|c| c.0 = joker_replacement.0);
-                copy.classify()
-            })
-            .max()
-            .unwrap()
+    fn is_wildcard(&self, card: char) -> bool {
+        self.wildcard == Some(card)
     }
 
-    fn cmp_with_jokers(&self, other: &Self) -> Ordering {
-        match self.classify_with_jokers().cmp(&other.classify_with_jokers()) {
-            Ordering::Equal => self.0.cmp(&other.0),
-            o @ _ => o,
-        }
+    fn hand_size(&self) -> usize {
+        self.hand_size
     }
 }
 
-impl PartialOrd for Hand {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.cmp(other))
-    }
+/// A hand and its category, which is computed once so sorting only compares the cached values.
+///
+/// Hands are ordered by their category first and their cards second, as the field order implies.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
+struct Hand {
+    category: Category,
+    cards: Vec<usize>,
 }
 
-impl Ord for Hand {
-    fn cmp(&self, other: &Self) -> Ordering {
-        match self.classify().cmp(&other.classify()) {
-            Ordering::Equal => self.0.cmp(&other.0),
-            o @ _ => o,
+impl Hand {
+    fn new<R: HandRules>(cards: &str, rules: &R) -> Result<Self, HandError> {
+        let chars = cards.chars().collect::<Vec<_>>();
+        if chars.len() != rules.hand_size() {
+            return Err(HandError::WrongHandSize {
+                expected: rules.hand_size(),
+                found: chars.len(),
+            });
         }
+        let cards = chars
+            .iter()
+            .enumerate()
+            .map(|(idx, &card)| {
+                rules.card_value(card).ok_or(HandError::InvalidCard {
+                    card,
+                    position: idx + 1,
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Hand {
+            category: rules.classify(&chars),
+            cards,
+        })
     }
 }
 
-struct HandWithBid(Hand, usize);
+struct HandWithBid(String, usize);
 
 impl FromStr for HandWithBid {
-    type Err = anyhow::Error;
+    type Err = HandError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (hand, bid) = s.split_once(" ").ok_or_else(|| anyhow!("Invalid input"))?;
-        Ok(HandWithBid(Hand::from_str(hand)?, bid.parse::<usize>()?))
+        let (hand, bid) = s.split_once(" ").ok_or(HandError::MissingBid)?;
+        let bid = bid
+            .parse::<usize>()
+            .map_err(|_| HandError::InvalidBid(bid.to_owned()))?;
+        Ok(HandWithBid(hand.to_owned(), bid))
     }
 }
 
-fn part1<P: AsRef<Path>>(input: P) -> Result<usize> {
-    let mut hands_with_bids = stream_items_from_file::<_, HandWithBid>(input)?
-        .map(|r| r.unwrap())
-        .collect::<Vec<_>>();
+fn read_hands<P: AsRef<Path>, R: HandRules>(input: P, rules: &R) -> Result<Vec<(Hand, usize)>> {
+    stream_items_from_file::<_, HandWithBid>(input)?
+        .enumerate()
+        .map(|(idx, line)| {
+            let hand_with_bid =
+                line.and_then(|HandWithBid(hand, bid)| Ok((Hand::new(&hand, rules)?, bid)));
+            hand_with_bid.with_context(|| format!("Invalid hand in line {}", idx + 1))
+        })
+        .collect()
+}
+
+fn total_winnings<P: AsRef<Path>, R: HandRules>(input: P, rules: &R) -> Result<usize> {
+    let mut hands_with_bids = timed!("parse", read_hands(input, rules)?);
 
     hands_with_bids.sort_by(|a, b| a.0.cmp(&b.0));
 
     let total_winnings = hands_with_bids
         .into_iter()
         .enumerate()
-        .map(|(idx, hand_with_bid)| (idx + 1) * hand_with_bid.1)
+        .map(|(idx, (_, bid))| (idx + 1) * bid)
         .sum();
 
     Ok(total_winnings)
 }
 
+fn part1<P: AsRef<Path>>(input: P) -> Result<usize> {
+    total_winnings(input, &Standard)
+}
+
 fn part2<P: AsRef<Path>>(input: P) -> Result<usize> {
-    let mut hands_with_bids = stream_items_from_file::<_, HandWithBid>(input)?
-        .map(|r| r.unwrap())
-        .collect::<Vec<_>>();
+    total_winnings(input, &JokerRules)
+}
 
-    // Make J cards joker cards
-    hands_with_bids.iter_mut().for_each(|h| h.0.patch_jokers());
+struct Day07;
 
-    hands_with_bids.sort_by(|a, b| a.0.cmp_with_jokers(&b.0));
+impl AocDay for Day07 {
+    const YEAR: u16 = 2023;
+    const DAY: u8 = 7;
 
-    let total_winnings = hands_with_bids
-        .into_iter()
-        .enumerate()
-        .map(|(idx, hand_with_bid)| (idx + 1) * hand_with_bid.1)
-        .sum();
+    /// `--ranks CARDS` plays with a different deck, given from the weakest to the strongest
+    /// card, and `--wildcard CARD` sets the joker of part 2 for it. `--hand-size N` plays with
+    /// hands of N cards instead of 5.
+    fn part1(input: &Path) -> Result<Answer> {
+        Ok(match CustomRules::from_options(false)? {
+            Some(rules) => total_winnings(input, &rules)?,
+            None => part1(input)?,
+        }
+        .into())
+    }
 
-    Ok(total_winnings)
+    fn part2(input: &Path) -> Result<Answer> {
+        Ok(match CustomRules::from_options(true)? {
+            Some(rules) => total_winnings(input, &rules)?,
+            None => part2(input)?,
+        }
+        .into())
+    }
 }
 
 fn main() -> Result<()> {
-    println!("Answer for part 1: {}", part1(INPUT)?);
-    println!("Answer for part 2: {}", part2(INPUT)?);
-
-    Ok(())
+    run::<Day07>()
 }
 
 #[cfg(test)]
@@ -206,4 +279,101 @@ mod tests_day07 {
         assert_eq!(part2(&file).unwrap(), 5905);
         drop(dir);
     }
+
+    #[test]
+    fn test_rules() {
+        let hand = Hand::new("KTJJT", &Standard).unwrap();
+        assert_eq!(hand.category, vec![2, 2, 1]);
+        let with_jokers = Hand::new("KTJJT", &JokerRules).unwrap();
+        assert_eq!(with_jokers.category, vec![4, 1]);
+        // Jokers are weaker than any other card for ties
+        assert!(with_jokers < Hand::new("KTTTT", &JokerRules).unwrap());
+        assert_eq!(Hand::new("JJJJJ", &JokerRules).unwrap().category, vec![5]);
+    }
+
+    /// Twos are wild, but still the weakest card
+    struct WildTwos;
+
+    impl HandRules for WildTwos {
+        fn card_value(&self, card: char) -> Option<usize> {
+            Standard.card_value(card)
+        }
+
+        fn is_wildcard(&self, card: char) -> bool {
+            card == '2'
+        }
+    }
+
+    #[test]
+    fn test_custom_rules() {
+        assert_eq!(
+            Hand::new("2345A", &WildTwos).unwrap().category,
+            vec![2, 1, 1, 1]
+        );
+        assert_eq!(Hand::new("22QQA", &WildTwos).unwrap().category, vec![4, 1]);
+        assert!(Hand::new("2QQQQ", &WildTwos).unwrap() < Hand::new("QQQQQ", &WildTwos).unwrap());
+    }
+
+    fn extended_deck(wildcard: Option<char>, hand_size: usize) -> CustomRules {
+        CustomRules {
+            ranks: "J23456789TQKAZ".to_owned(),
+            wildcard,
+            hand_size,
+        }
+    }
+
+    #[test]
+    fn test_other_hand_sizes() {
+        let rules = extended_deck(None, 6);
+        let hand = |cards| Hand::new(cards, &rules).unwrap();
+        // Three pairs are weaker than three of a kind with six cards as well
+        assert!(hand("ZZ2233") < hand("ZZZ234"));
+        assert!(hand("ZZZ234") < hand("222333"));
+
+        let rules = extended_deck(None, 2);
+        assert!(Hand::new("ZZ", &rules).unwrap() > Hand::new("AK", &rules).unwrap());
+
+        let jokers = extended_deck(Some('J'), 7);
+        assert_eq!(
+            Hand::new("ZJ23456", &jokers).unwrap().category,
+            vec![2, 1, 1, 1, 1, 1]
+        );
+    }
+
+    #[test]
+    fn test_invalid_hands() {
+        assert_eq!(
+            Hand::new("A1", &extended_deck(None, 2)),
+            Err(HandError::InvalidCard {
+                card: '1',
+                position: 2
+            })
+        );
+        assert_eq!(
+            Hand::new("AKQJ", &Standard),
+            Err(HandError::WrongHandSize {
+                expected: 5,
+                found: 4
+            })
+        );
+
+        let (dir, file) = create_example_file(
+            indoc! {"
+            32T3K 765
+            T55X5 684
+        "},
+            None,
+        );
+        assert_eq!(
+            format!("{:#}", part1(&file).unwrap_err()),
+            "Invalid hand in line 2: Conversion error: Invalid card 'X' at position 4"
+        );
+        let (dir2, file) = create_example_file("32T3K 765\nKK677\n", None);
+        assert_eq!(
+            format!("{:#}", part2(&file).unwrap_err()),
+            "Invalid hand in line 2: Conversion error: Missing ' ' between the hand and the bid"
+        );
+        drop(dir);
+        drop(dir2);
+    }
 }
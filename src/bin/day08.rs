@@ -1,11 +1,21 @@
-use std::{collections::HashMap, path::Path};
+use std::{
+    collections::{BTreeSet, HashMap},
+    fmt,
+    path::{Path, PathBuf},
+};
 
-use advent_of_code_2023::read_lines;
+use advent_of_code_2023::{
+    answer::Answer,
+    maths::crt,
+    read_lines,
+    render_graph::GraphRenderer,
+    runner::{options, run, AocDay, Solver},
+    timed,
+};
 use anyhow::{anyhow, Result};
+use itertools::Itertools;
 use regex::Regex;
 
-const INPUT: &str = "input/day08.txt";
-
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Direction {
     Left,
@@ -26,6 +36,13 @@ fn triple_to_number(triple: &str) -> usize {
         .fold(0, |acc, n| (acc * 26) + (n as usize))
 }
 
+fn number_to_triple(number: usize) -> String {
+    [number / 676, number / 26 % 26, number % 26]
+        .into_iter()
+        .map(|n| char::from(b'A' + n as u8))
+        .collect()
+}
+
 impl PuzzleInput {
     fn try_from_input<P: AsRef<Path>>(input: P) -> Result<Self> {
         let triple_re = Regex::new(r"\w{3}")?;
@@ -63,7 +80,7 @@ impl PuzzleInput {
 }
 
 fn part1<P: AsRef<Path>>(input: P) -> Result<usize> {
-    let input = PuzzleInput::try_from_input(input)?;
+    let input = timed!("parse", PuzzleInput::try_from_input(input))?;
     let steps = input
         .instructions
         .iter()
@@ -87,73 +104,268 @@ fn part1<P: AsRef<Path>>(input: P) -> Result<usize> {
     Ok(steps + 1)
 }
 
-fn search_loop(position: usize, input: &PuzzleInput) -> usize {
-    // By playing around it was obvious that all starting positions will:
-    //      1. Only reach exactly one end node
-    //      2. Do so on a fixed schedule, i.e. the event of reaching said end node will take place
-    //         every n cycles, where n differs per start node.
-    //
-    // Property 1. has to be carefully crafted for the input, so this will NOT be a general
-    // solution. I think that property 2 is also not guaranteed for all inputs, it likely
-    // depends on the input node network and the layout of the movement instructions.
-    //
-    // This function will just find the first step that reaches an exit node, assuming that we can
-    // later find a solution by simply calculating the lcm over all nodes.
-
-    input
-        .instructions
-        .iter()
-        .cloned()
-        .cycle()
-        .enumerate()
-        .scan(position, |pos, (step, direction)| {
-            let (left, right) = input.network.get(pos).unwrap();
-            match direction {
-                Direction::Left => {
-                    *pos = *left;
-                }
-                Direction::Right => {
-                    *pos = *right;
-                }
+fn is_end_node(position: usize) -> bool {
+    position % 26 == usize::from(b'Z' - b'A')
+}
+
+/// When a ghost stands on end nodes.
+///
+/// A ghost's state is its node together with the position in the instructions. There are only
+/// finitely many states, so the ghost eventually runs into a cycle of `length` steps that it
+/// enters after `offset` steps. Before that, it may pass end nodes that it never sees again.
+#[derive(Debug, PartialEq, Eq)]
+struct Trajectory {
+    start: usize,
+    offset: usize,
+    length: usize,
+    /// Steps before entering the cycle at which the ghost is on an end node
+    ends_before_cycle: Vec<usize>,
+    /// Steps in `offset..offset + length` at which the ghost is on an end node, which repeat
+    /// every `length` steps
+    ends_in_cycle: Vec<usize>,
+    /// All end nodes the ghost ever visits
+    end_nodes: BTreeSet<usize>,
+}
+
+impl Trajectory {
+    fn follow(start: usize, input: &PuzzleInput) -> Self {
+        let mut first_seen = HashMap::new();
+        let mut ends = Vec::new();
+        let mut end_nodes = BTreeSet::new();
+        let mut position = start;
+        let mut step = 0;
+        loop {
+            let instruction = step % input.instructions.len();
+            if let Some(&offset) = first_seen.get(&(position, instruction)) {
+                let (ends_before_cycle, ends_in_cycle) =
+                    ends.into_iter().partition(|&end| end < offset);
+                return Trajectory {
+                    start,
+                    offset,
+                    length: step - offset,
+                    ends_before_cycle,
+                    ends_in_cycle,
+                    end_nodes,
+                };
             }
+            first_seen.insert((position, instruction), step);
+            if is_end_node(position) {
+                ends.push(step);
+                end_nodes.insert(position);
+            }
+            let (left, right) = input.network.get(&position).unwrap();
+            position = match input.instructions[instruction] {
+                Direction::Left => *left,
+                Direction::Right => *right,
+            };
+            step += 1;
+        }
+    }
+
+    fn is_end_at(&self, step: usize) -> bool {
+        if step < self.offset {
+            self.ends_before_cycle.contains(&step)
+        } else {
+            let in_cycle = self.offset + (step - self.offset) % self.length;
+            self.ends_in_cycle.contains(&in_cycle)
+        }
+    }
+}
 
-            if *pos % 26 == (b'Z' - b'A').into() {
-                // We are at an end node, report this
-                Some(Some((step, *pos)))
-            } else {
-                // We are not at an end node, report that we are still going, but don't report any
-                // output
-                Some(None)
+/// One line per ghost for `--diagnose`, which shows whether the input has the structure usually
+/// assumed for the puzzle: no end nodes before the cycle and exactly one at the end of it.
+impl fmt::Display for Trajectory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: cycle of {} steps after an offset of {}, {} distinct end nodes, on end nodes at \
+             steps {:?} before and {:?} within the cycle",
+            number_to_triple(self.start),
+            self.length,
+            self.offset,
+            self.end_nodes.len(),
+            self.ends_before_cycle,
+            self.ends_in_cycle
+        )
+    }
+}
+
+/// The first step at which all ghosts stand on end nodes, if there is one.
+///
+/// Until the last ghost entered its cycle, the steps are simply checked one by one. From then
+/// on, every ghost is on an end node exactly at the steps of some congruences, and the Chinese
+/// remainder theorem finds the first step satisfying one congruence of each ghost.
+fn first_common_end(trajectories: &[Trajectory]) -> Result<Option<usize>, crt::Overflow> {
+    let all_cycling = trajectories.iter().map(|t| t.offset).max().unwrap_or(0);
+    if let Some(step) =
+        (1..all_cycling).find(|&step| trajectories.iter().all(|t| t.is_end_at(step)))
+    {
+        return Ok(Some(step));
+    }
+
+    let mut systems = vec![(0, 1)];
+    for trajectory in trajectories {
+        let length = trajectory.length as u64;
+        let mut combined = Vec::new();
+        for &system in &systems {
+            for &end in &trajectory.ends_in_cycle {
+                combined.extend(crt::combine(system, (end as u64 % length, length))?);
             }
-        })
-        .find_map(|x| x)
-        .unwrap()
-        .0
-        + 1
+        }
+        systems = combined.into_iter().unique().collect();
+    }
+    // The first step that is at least `all_cycling` (and not the start) in each class
+    let first = all_cycling.max(1) as u64;
+    let mut steps = Vec::new();
+    for (residue, modulus) in systems {
+        let step = first
+            .saturating_sub(residue)
+            .div_ceil(modulus)
+            .checked_mul(modulus)
+            .and_then(|offset| offset.checked_add(residue))
+            .ok_or(crt::Overflow)?;
+        steps.push(step as usize);
+    }
+    Ok(steps.into_iter().min())
 }
 
-fn part2<P: AsRef<Path>>(input: P) -> Result<usize> {
-    let input = PuzzleInput::try_from_input(input)?;
-    let initial_positions = input
+fn part2<P: AsRef<Path>>(input: P, diagnose: bool) -> Result<usize> {
+    let input = timed!("parse", PuzzleInput::try_from_input(input))?;
+    let trajectories = input
+        .network
+        .keys()
+        .cloned()
+        .filter(|pos| pos % 26 == 0)
+        .sorted()
+        .map(|pos| Trajectory::follow(pos, &input))
+        .collect::<Vec<_>>();
+    if diagnose {
+        for trajectory in &trajectories {
+            eprintln!("{}", trajectory);
+        }
+    }
+
+    first_common_end(&trajectories)?.ok_or_else(|| {
+        let stuck = trajectories
+            .iter()
+            .filter(|t| t.ends_in_cycle.is_empty())
+            .map(|t| number_to_triple(t.start))
+            .collect::<Vec<_>>();
+        if stuck.is_empty() {
+            anyhow!("The ghosts are never on end nodes at the same time, their cycles don't align")
+        } else {
+            anyhow!(
+                "The ghosts are never on end nodes at the same time, the cycles of the ghosts \
+                 starting at {} contain no end node",
+                stuck.join(", ")
+            )
+        }
+    })
+}
+
+/// Step cap of the simulation unless set with `--max-steps`
+const DEFAULT_MAX_STEPS: usize = 10_000_000;
+
+/// Moves all ghosts step by step until they are on end nodes at the same time, to cross-check
+/// [`first_common_end`] on small networks. Gives up after `max_steps` steps.
+fn part2_simulate<P: AsRef<Path>>(input: P, max_steps: usize) -> Result<usize> {
+    let input = timed!("parse", PuzzleInput::try_from_input(input))?;
+    let mut positions = input
         .network
         .keys()
         .cloned()
         .filter(|pos| pos % 26 == 0)
         .collect::<Vec<_>>();
 
-    let coinciding_end_cycle = initial_positions
+    for (step, direction) in input
+        .instructions
         .iter()
-        .map(|pos| search_loop(*pos, &input))
-        .fold(1, num::integer::lcm);
+        .cycle()
+        .enumerate()
+        .take(max_steps)
+    {
+        for position in positions.iter_mut() {
+            let (left, right) = input.network.get(position).unwrap();
+            *position = match direction {
+                Direction::Left => *left,
+                Direction::Right => *right,
+            };
+        }
+        if positions.iter().all(|&position| is_end_node(position)) {
+            return Ok(step + 1);
+        }
+    }
+    Err(anyhow!(
+        "The ghosts are not on end nodes at the same time within {} steps",
+        max_steps
+    ))
+}
+
+const START_COLOR: &str = "#59a14f";
+const END_COLOR: &str = "#e15759";
+
+/// The network as a directed graph for Graphviz, with an edge labeled `L` or `R` for each
+/// direction, or `LR` if both lead to the same node. Nodes ending with `A` and `Z` are colored as
+/// the starts and ends of the ghosts.
+fn render_network<P: AsRef<Path>>(input: P) -> Result<GraphRenderer> {
+    let input = PuzzleInput::try_from_input(input)?;
+    let mut graph = GraphRenderer::new(true);
+    for (&node, &(left, right)) in input.network.iter().sorted() {
+        let name = number_to_triple(node);
+        if node % 26 == 0 {
+            graph.add_node(name.clone(), None, Some(START_COLOR.to_owned()));
+        } else if is_end_node(node) {
+            graph.add_node(name.clone(), None, Some(END_COLOR.to_owned()));
+        }
+        let edges = if left == right {
+            vec![(left, "LR")]
+        } else {
+            vec![(left, "L"), (right, "R")]
+        };
+        for (next, label) in edges {
+            graph.add_edge(
+                name.clone(),
+                number_to_triple(next),
+                Some(label.to_owned()),
+                None,
+            );
+        }
+    }
+    Ok(graph)
+}
+
+struct Day08;
+
+impl AocDay for Day08 {
+    const YEAR: u16 = 2023;
+    const DAY: u8 = 8;
+
+    /// `--dot PATH` additionally stores the network in the DOT language, see [`render_network`].
+    fn part1(input: &Path) -> Result<Answer> {
+        if let Some(path) = options().get::<PathBuf>("dot")? {
+            render_network(input)?.store_dot(path)?;
+        }
+        Ok(part1(input)?.into())
+    }
+
+    /// `--diagnose` prints the cycle structure of each ghost, see [`Trajectory`].
+    fn part2(input: &Path) -> Result<Answer> {
+        Ok(part2(input, options().flag("diagnose"))?.into())
+    }
 
-    Ok(coinciding_end_cycle)
+    /// `--algo simulate` moves the ghosts step by step, at most `--max-steps N` steps.
+    fn part2_alternatives() -> Vec<(&'static str, Solver)> {
+        vec![("simulate", |input| {
+            let max_steps = options()
+                .get::<usize>("max-steps")?
+                .unwrap_or(DEFAULT_MAX_STEPS);
+            Ok(part2_simulate(input, max_steps)?.into())
+        })]
+    }
 }
 
 fn main() -> Result<()> {
-    println!("Answer for part 1: {}", part1(INPUT)?);
-    println!("Answer for part 2: {}", part2(INPUT)?);
-
-    Ok(())
+    run::<Day08>()
 }
 
 #[cfg(test)]
@@ -182,6 +394,39 @@ mod tests_day08 {
         drop(dir);
     }
 
+    #[test]
+    fn test_render_network() {
+        let (dir, file) = create_example_file(
+            indoc! {"
+            LLR
+
+            AAA = (BBB, BBB)
+            BBB = (AAA, ZZZ)
+            ZZZ = (ZZZ, ZZZ)
+        "},
+            None,
+        );
+        let mut dot = Vec::new();
+        render_network(&file).unwrap().render_dot(&mut dot).unwrap();
+        let dot = String::from_utf8(dot).unwrap();
+        let edges = dot
+            .lines()
+            .filter(|line| line.contains("->"))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            edges,
+            [
+                r#"  "AAA" -> "BBB" [label="LR"];"#,
+                r#"  "BBB" -> "AAA" [label="L"];"#,
+                r#"  "BBB" -> "ZZZ" [label="R"];"#,
+                r#"  "ZZZ" -> "ZZZ" [label="LR"];"#,
+            ]
+        );
+        assert!(dot.contains(&format!(r#"  "AAA" [color="{}"];"#, START_COLOR)));
+        assert!(dot.contains(&format!(r#"  "ZZZ" [color="{}"];"#, END_COLOR)));
+        drop(dir);
+    }
+
     #[test]
     fn test_example_part1_02() {
         let (dir, file) = create_example_file(
@@ -217,7 +462,92 @@ mod tests_day08 {
         "},
             None,
         );
-        assert_eq!(part2(&file).unwrap(), 6);
+        assert_eq!(part2(&file, false).unwrap(), 6);
+        assert_eq!(part2_simulate(&file, 100).unwrap(), 6);
+        assert!(part2_simulate(&file, 5).is_err());
+        drop(dir);
+    }
+
+    #[test]
+    fn test_offsets_and_multiple_ends() {
+        // Ghost A is on end nodes at step 1 and every odd step from 3 on, ghost E at steps 2, 5,
+        // 8 and so on. Assuming pure cycles starting at step 0 would give lcm(1, 2) = 2.
+        let (dir, file) = create_example_file(
+            indoc! {"
+            L
+
+            AAA = (BBZ, XXX)
+            BBZ = (CCC, XXX)
+            CCC = (DDZ, XXX)
+            DDZ = (CCC, XXX)
+            EEA = (FFB, XXX)
+            FFB = (GGZ, XXX)
+            GGZ = (HHB, XXX)
+            HHB = (IIB, XXX)
+            IIB = (GGZ, XXX)
+            XXX = (XXX, XXX)
+        "},
+            None,
+        );
+        let input = PuzzleInput::try_from_input(&file).unwrap();
+        assert_eq!(
+            Trajectory::follow(0, &input),
+            Trajectory {
+                start: 0,
+                offset: 2,
+                length: 2,
+                ends_before_cycle: vec![1],
+                ends_in_cycle: vec![3],
+                end_nodes: [triple_to_number("BBZ"), triple_to_number("DDZ")].into(),
+            }
+        );
+        assert_eq!(
+            Trajectory::follow(0, &input).to_string(),
+            "AAA: cycle of 2 steps after an offset of 2, 2 distinct end nodes, on end nodes at \
+             steps [1] before and [3] within the cycle"
+        );
+        assert_eq!(part2(&file, false).unwrap(), 5);
+        assert_eq!(part2_simulate(&file, 100).unwrap(), 5);
+        drop(dir);
+    }
+
+    #[test]
+    fn test_huge_cycles() {
+        // The cycle lengths are coprime, so the ghosts only meet after more than 2^64 steps
+        let trajectories = [(1 << 31) - 1, 1 << 31, (1 << 31) + 1].map(|length| Trajectory {
+            start: 0,
+            offset: 0,
+            length,
+            ends_before_cycle: Vec::new(),
+            ends_in_cycle: vec![1],
+            end_nodes: BTreeSet::new(),
+        });
+        assert_eq!(first_common_end(&trajectories), Err(crt::Overflow));
+        assert_eq!(first_common_end(&trajectories[..2]), Ok(Some(1)));
+    }
+
+    #[test]
+    fn test_unsolvable() {
+        let (dir, file) = create_example_file(
+            indoc! {"
+            L
+
+            AAA = (AAZ, AAA)
+            AAZ = (BBB, AAA)
+            BBB = (BBB, BBB)
+            CCA = (CCB, CCA)
+            CCB = (CCZ, CCA)
+            CCZ = (CCA, CCA)
+        "},
+            None,
+        );
+        // AAA only passes an end node at step 1, when the other ghost isn't on one yet
+        assert_eq!(
+            part2(&file, false).unwrap_err().to_string(),
+            "The ghosts are never on end nodes at the same time, the cycles of the ghosts \
+             starting at AAA contain no end node"
+        );
+        assert!(part2_simulate(&file, 100).is_err());
         drop(dir);
     }
 }
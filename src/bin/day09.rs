@@ -1,84 +1,196 @@
-use std::{path::Path, str::FromStr};
+use std::{num::ParseIntError, path::Path, str::FromStr};
 
-use advent_of_code_2023::stream_items_from_file;
-use anyhow::Result;
-
-const INPUT: &str = "input/day09.txt";
+use advent_of_code_2023::{
+    answer::Answer,
+    runner::{options, run, AocDay, Solver},
+    stream_items_from_file,
+};
+use anyhow::{Context, Result};
+use thiserror::Error;
 
+/// Values are stored as `i128`, so differences and predictions of any `i64` input fit easily.
+/// All arithmetic is checked anyway, so a result is never silently wrong.
 #[derive(Debug, Clone)]
-struct Sequence(Vec<isize>);
+struct Sequence(Vec<i128>);
+
+#[derive(Error, Debug, PartialEq, Eq)]
+#[error("Overflow: the values exceed the range of 128 bit integers")]
+struct Overflow;
 
 impl FromStr for Sequence {
-    type Err = anyhow::Error;
+    type Err = ParseIntError;
 
-    fn from_str(s: &str) -> Result<Self> {
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
         let sequence = s
             .split_whitespace()
-            .map(|item| item.parse().map_err(Into::into))
-            .collect::<Result<Vec<_>>>()?;
+            .map(|item| item.parse())
+            .collect::<Result<Vec<_>, _>>()?;
         Ok(Self(sequence))
     }
 }
 
 impl Sequence {
-    fn derive(&self) -> Sequence {
-        Self(
+    fn derive(&self) -> Result<Sequence, Overflow> {
+        Ok(Self(
             self.0
                 .iter()
                 .skip(1)
                 .zip(self.0.iter())
-                .map(|(next, prev)| next - prev)
-                .collect(),
-        )
+                .map(|(next, prev)| next.checked_sub(*prev).ok_or(Overflow))
+                .collect::<Result<_, _>>()?,
+        ))
     }
 
-    fn all_derivations(&self) -> Vec<Sequence> {
-        std::iter::successors(Some(self.clone()), |pred| {
-            if pred.0.iter().any(|val| *val != 0) {
-                Some(pred.derive())
-            } else {
-                None
-            }
-        })
-        .collect()
+    fn all_derivations(&self) -> Result<Vec<Sequence>, Overflow> {
+        let mut derivations = vec![self.clone()];
+        while let Some(pred) = derivations
+            .last()
+            .filter(|pred| pred.0.iter().any(|val| *val != 0))
+        {
+            derivations.push(pred.derive()?);
+        }
+        Ok(derivations)
     }
 
-    fn predict(&self) -> isize {
-        let derivations = self.all_derivations();
+    /// Predicts the value `offset` steps after the last value, or `-offset` steps before the
+    /// first value for negative offsets. Offset 0 is the last value itself.
+    ///
+    /// Every step extends all derivations by one value, from the deepest (constant) one up. If
+    /// the derivations only become constant with a single value, the next one is empty and
+    /// counts as all zeros.
+    fn predict_at(&self, offset: isize) -> Result<i128, Overflow> {
+        let derivations = self.all_derivations()?;
+        let levels = derivations.len();
 
-        derivations
-            .iter()
-            .rev()
-            .fold(0, |acc, seq| acc + seq.0.last().unwrap())
+        if offset >= 0 {
+            let mut lasts = derivations
+                .iter()
+                .map(|seq| seq.0.last().copied().unwrap_or(0))
+                .collect::<Vec<_>>();
+            for _ in 0..offset {
+                for level in (0..levels - 1).rev() {
+                    lasts[level] = lasts[level].checked_add(lasts[level + 1]).ok_or(Overflow)?;
+                }
+            }
+            Ok(lasts[0])
+        } else {
+            let mut firsts = derivations
+                .iter()
+                .map(|seq| seq.0.first().copied().unwrap_or(0))
+                .collect::<Vec<_>>();
+            for _ in 0..-offset {
+                for level in (0..levels - 1).rev() {
+                    firsts[level] = firsts[level]
+                        .checked_sub(firsts[level + 1])
+                        .ok_or(Overflow)?;
+                }
+            }
+            Ok(firsts[0])
+        }
     }
 
-    fn predict_backwards(&self) -> isize {
-        let derivations = self.all_derivations();
+    /// Evaluates the polynomial through the sequence at any index `x`, where the first value is
+    /// at 0, with Newton's forward difference formula: the sum of `binomial(x, k)` times the
+    /// first value of the k-th derivation.
+    ///
+    /// Index `len - 1 + offset` predicts the same value as [`predict_at`](Self::predict_at) for
+    /// non-negative offsets, negative indices are the same as negative offsets. Like there, an
+    /// empty sequence is 0 everywhere.
+    fn interpolate(&self, x: isize) -> Result<i128, Overflow> {
+        let x = x as i128;
+        let mut binomial: i128 = 1;
+        let mut value: i128 = 0;
+        for (k, derivation) in self.all_derivations()?.iter().enumerate() {
+            let Some(first) = derivation.0.first() else {
+                break;
+            };
+            value = binomial
+                .checked_mul(*first)
+                .and_then(|term| value.checked_add(term))
+                .ok_or(Overflow)?;
+            // binomial(x, k + 1) = binomial(x, k) * (x - k) / (k + 1), which divides exactly
+            let k = k as i128;
+            binomial = binomial.checked_mul(x - k).ok_or(Overflow)? / (k + 1);
+        }
+        Ok(value)
+    }
+}
 
-        derivations
-            .iter()
-            .rev()
-            .fold(0, |acc, seq| seq.0.first().unwrap() - acc)
+/// Sums up the prediction for each sequence
+fn sum_predictions<P, F>(input: P, predict: F) -> Result<i128>
+where
+    P: AsRef<Path>,
+    F: Fn(&Sequence) -> Result<i128, Overflow>,
+{
+    let mut sum: i128 = 0;
+    for (idx, seq) in stream_items_from_file::<_, Sequence>(input)?.enumerate() {
+        let seq = seq.with_context(|| format!("Invalid sequence in line {}", idx + 1))?;
+        let prediction =
+            predict(&seq).with_context(|| format!("Invalid prediction in line {}", idx + 1))?;
+        sum = sum.checked_add(prediction).ok_or(Overflow)?;
     }
+    Ok(sum)
 }
 
-fn part1<P: AsRef<Path>>(input: P) -> Result<isize> {
-    Ok(stream_items_from_file::<_, Sequence>(input)?
-        .map(|seq| seq.unwrap().predict())
-        .sum())
+/// Sum of the predictions `ahead` steps after each sequence, the puzzle asks for the next value
+fn part1<P: AsRef<Path>>(input: P, ahead: usize) -> Result<i128> {
+    sum_predictions(input, |seq| seq.predict_at(ahead as isize))
 }
 
-fn part2<P: AsRef<Path>>(input: P) -> Result<isize> {
-    Ok(stream_items_from_file::<_, Sequence>(input)?
-        .map(|seq| seq.unwrap().predict_backwards())
-        .sum())
+/// Sum of the predictions `behind` steps before each sequence, the puzzle asks for the previous
+/// value
+fn part2<P: AsRef<Path>>(input: P, behind: usize) -> Result<i128> {
+    sum_predictions(input, |seq| seq.predict_at(-(behind as isize)))
 }
 
-fn main() -> Result<()> {
-    println!("Answer for part 1: {}", part1(INPUT)?);
-    println!("Answer for part 2: {}", part2(INPUT)?);
+fn part1_newton<P: AsRef<Path>>(input: P, ahead: usize) -> Result<i128> {
+    // The last value is at index -1 for an empty sequence, e.g. from a blank line
+    sum_predictions(input, |seq| {
+        seq.interpolate(seq.0.len() as isize - 1 + ahead as isize)
+    })
+}
+
+fn part2_newton<P: AsRef<Path>>(input: P, behind: usize) -> Result<i128> {
+    sum_predictions(input, |seq| seq.interpolate(-(behind as isize)))
+}
+
+fn ahead() -> Result<usize> {
+    Ok(options().get("ahead")?.unwrap_or(1))
+}
+
+fn behind() -> Result<usize> {
+    Ok(options().get("behind")?.unwrap_or(1))
+}
+
+struct Day09;
+
+impl AocDay for Day09 {
+    const YEAR: u16 = 2023;
+    const DAY: u8 = 9;
+
+    /// `--ahead N` predicts N steps after the sequences instead of one.
+    fn part1(input: &Path) -> Result<Answer> {
+        Ok(part1(input, ahead()?)?.into())
+    }
 
-    Ok(())
+    /// `--behind N` predicts N steps before the sequences instead of one.
+    fn part2(input: &Path) -> Result<Answer> {
+        Ok(part2(input, behind()?)?.into())
+    }
+
+    /// `--algo newton` evaluates the interpolating polynomial directly, see
+    /// [`Sequence::interpolate`].
+    fn part1_alternatives() -> Vec<(&'static str, Solver)> {
+        vec![("newton", |input| Ok(part1_newton(input, ahead()?)?.into()))]
+    }
+
+    fn part2_alternatives() -> Vec<(&'static str, Solver)> {
+        vec![("newton", |input| Ok(part2_newton(input, behind()?)?.into()))]
+    }
+}
+
+fn main() -> Result<()> {
+    run::<Day09>()
 }
 
 #[cfg(test)]
@@ -97,8 +209,94 @@ mod tests_day09 {
         "},
             None,
         );
-        assert_eq!(part1(&file).unwrap(), 114);
-        assert_eq!(part2(&file).unwrap(), 2);
+        assert_eq!(part1(&file, 1).unwrap(), 114);
+        assert_eq!(part2(&file, 1).unwrap(), 2);
+        assert_eq!(part1_newton(&file, 1).unwrap(), 114);
+        assert_eq!(part2_newton(&file, 1).unwrap(), 2);
+        drop(dir);
+    }
+
+    #[test]
+    fn test_empty_sequence() {
+        let empty = "".parse::<Sequence>().unwrap();
+        assert_eq!(empty.predict_at(1), Ok(0));
+        assert_eq!(empty.interpolate(0), Ok(0));
+        assert_eq!(empty.interpolate(-1), Ok(0));
+
+        let (dir, file) = create_example_file("0 3 6 9 12 15\n\n1 3 6 10 15 21\n", None);
+        for ahead in [1, 3] {
+            assert_eq!(
+                part1_newton(&file, ahead).unwrap(),
+                part1(&file, ahead).unwrap()
+            );
+            assert_eq!(
+                part2_newton(&file, ahead).unwrap(),
+                part2(&file, ahead).unwrap()
+            );
+        }
+        assert_eq!(part1_newton(&file, 1).unwrap(), 18 + 28);
+        drop(dir);
+    }
+
+    #[test]
+    fn test_interpolate() {
+        let squares = "0 1 4 9 16".parse::<Sequence>().unwrap();
+        assert_eq!(squares.interpolate(2), Ok(4));
+        assert_eq!(squares.interpolate(10), Ok(100));
+        assert_eq!(squares.interpolate(-3), Ok(9));
+        let sequence = "10 13 16 21 30 45".parse::<Sequence>().unwrap();
+        assert_eq!(sequence.interpolate(6), sequence.predict_at(1));
+        assert_eq!(sequence.interpolate(-1), sequence.predict_at(-1));
+    }
+
+    #[test]
+    fn test_predict_at() {
+        let squares = "0 1 4 9 16".parse::<Sequence>().unwrap();
+        assert_eq!(squares.predict_at(0), Ok(16));
+        assert_eq!(squares.predict_at(1), Ok(25));
+        assert_eq!(squares.predict_at(5), Ok(81));
+        assert_eq!(squares.predict_at(-3), Ok(9));
+        for offset in -10..10 {
+            let index = if offset >= 0 { 4 + offset } else { offset };
+            assert_eq!(squares.predict_at(offset), squares.interpolate(index));
+        }
+    }
+
+    #[test]
+    fn test_large_values() {
+        // The differences and the prediction exceed 64 bits
+        let max = i64::MAX;
+        let sequence = format!("{} 0 {} 0 {}", -max, max, -max)
+            .parse::<Sequence>()
+            .unwrap();
+        let prediction = sequence.predict_at(1).unwrap();
+        assert_eq!(prediction, 4 * i128::from(max));
+        assert_eq!(sequence.interpolate(5), Ok(prediction));
+
+        let huge = format!("0 {} 0", i128::MAX).parse::<Sequence>().unwrap();
+        assert_eq!(huge.predict_at(1), Err(Overflow));
+        assert_eq!(huge.interpolate(3), Err(Overflow));
+
+        let (dir, file) = create_example_file(&format!("1 2 3\n0 {} 0\n", i128::MAX), None);
+        assert_eq!(
+            format!("{:#}", part1(&file, 1).unwrap_err()),
+            "Invalid prediction in line 2: Overflow: the values exceed the range of 128 bit integers"
+        );
+        drop(dir);
+    }
+
+    #[test]
+    fn test_invalid_sequence() {
+        let (dir, file) = create_example_file(
+            "1 2 3
+1 two 3
+",
+            None,
+        );
+        assert_eq!(
+            format!("{:#}", part1(&file, 1).unwrap_err()),
+            "Invalid sequence in line 2: Conversion error: invalid digit found in string"
+        );
         drop(dir);
     }
 }
@@ -1,20 +1,72 @@
 use std::{
     collections::{HashMap, HashSet},
-    path::Path,
+    path::{Path, PathBuf},
 };
 
-use advent_of_code_2023::read_lines;
-use anyhow::{bail, Result};
-use itertools::Itertools;
-use petgraph::{graphmap::DiGraphMap, Direction};
+use advent_of_code_2023::{
+    answer::Answer,
+    geometry,
+    grid::{Direction, Grid},
+    read_lines,
+    render_graph::GraphRenderer,
+    runner::{options, run, AocDay, Solver},
+    timed,
+};
+use anyhow::Result;
+use petgraph::{graphmap::DiGraphMap, Direction::Incoming};
+use thiserror::Error;
 
-const INPUT: &str = "input/day10.txt";
+#[derive(Error, Debug, PartialEq, Eq)]
+enum PipeError {
+    #[error("Invalid tile {tile:?} in line {y}, column {x}")]
+    InvalidTile { x: usize, y: usize, tile: char },
+    #[error("There is no start tile 'S'")]
+    MissingStart,
+    #[error("Can't tell the pipe under the start in line {y}, column {x}, it needs at least two connecting neighbors")]
+    UnresolvedStart { x: usize, y: usize },
+    #[error("The loop is broken in line {y}, column {x}: {reason}")]
+    BrokenLoop {
+        x: usize,
+        y: usize,
+        reason: &'static str,
+    },
+}
 
 type PipeGraph = DiGraphMap<(usize, usize), ()>;
+
+/*
+   | is a vertical pipe connecting north and south.
+   - is a horizontal pipe connecting east and west.
+   L is a 90-degree bend connecting north and east.
+   J is a 90-degree bend connecting north and west.
+   7 is a 90-degree bend connecting south and west.
+   F is a 90-degree bend connecting south and east.
+   . is ground; there is no pipe in this tile.
+   S is the starting position of the animal; there is a pipe on this tile, but your sketch doesn't show what shape the pipe has.
+*/
+/// The pipes and the two directions they connect
+const PIPES: [(char, [Direction; 2]); 6] = [
+    ('|', [Direction::Up, Direction::Down]),
+    ('-', [Direction::Left, Direction::Right]),
+    ('L', [Direction::Up, Direction::Right]),
+    ('J', [Direction::Up, Direction::Left]),
+    ('7', [Direction::Down, Direction::Left]),
+    ('F', [Direction::Right, Direction::Down]),
+];
+
+fn connections(kind: char) -> &'static [Direction] {
+    PIPES
+        .iter()
+        .find(|(pipe, _)| *pipe == kind)
+        .map_or(&[], |(_, directions)| directions)
+}
+
 struct PipeInfo {
+    /// The tiles indexed by `(y, x)`, with the pipe under the start instead of `S`
+    kinds: Grid<char>,
+    /// Edges from each pipe to the tiles it connects to, including junk pipes
     graph: PipeGraph,
-    start: (usize, usize),
-    kinds: HashMap<(usize, usize), char>,
+    loop_tiles: Vec<(usize, usize)>,
 }
 
 impl PipeInfo {
@@ -22,237 +74,330 @@ impl PipeInfo {
     where
         P: AsRef<Path>,
     {
-        let mut kinds = HashMap::new();
+        let lines = read_lines(input)?.collect::<Result<Vec<_>, _>>()?;
+        let mut kinds = Grid::parse(lines, Ok)?;
         let mut start = None;
-        let mut graph = PipeGraph::from_edges(
-            read_lines(input)?
-                .enumerate()
-                .map(|(i, v)| (i + 1, v))
-                .flat_map(|(y, maybe_line)| {
-                    let line = maybe_line.unwrap();
-                    line.chars()
-                        .enumerate()
-                        .map(|(i, v)| (i + 1, v))
-                        .flat_map(|(x, sym)| {
-                            if sym != '.' && sym != 'S' {
-                                kinds.insert((x, y), sym);
-                            }
-                            /*
-                               | is a vertical pipe connecting north and south.
-                               - is a horizontal pipe connecting east and west.
-                               L is a 90-degree bend connecting north and east.
-                               J is a 90-degree bend connecting north and west.
-                               7 is a 90-degree bend connecting south and west.
-                               F is a 90-degree bend connecting south and east.
-                               . is ground; there is no pipe in this tile.
-                               S is the starting position of the animal; there is a pipe on this tile, but your sketch doesn't show what shape the pipe has.
-                            */
-                            match sym {
-                                '|' => vec![((x, y), (x, y + 1)), ((x, y), (x, y - 1))],
-                                '-' => vec![((x, y), (x + 1, y)), ((x, y), (x - 1, y))],
-                                'L' => vec![((x, y), (x + 1, y)), ((x, y), (x, y - 1))],
-                                'J' => vec![((x, y), (x - 1, y)), ((x, y), (x, y - 1))],
-                                '7' => vec![((x, y), (x - 1, y)), ((x, y), (x, y + 1))],
-                                'F' => vec![((x, y), (x + 1, y)), ((x, y), (x, y + 1))],
-                                '.' => vec![],
-                                'S' => {
-                                    start = Some((x, y));
-                                    vec![]
-                                }
-                                _ => panic!(),
-                            }
-                        })
-                        .collect::<Vec<_>>()
-                }),
-        );
+        let mut edges = Vec::new();
+        for (y, x, &tile) in kinds.iter() {
+            match tile {
+                'S' => start = Some((y, x)),
+                '.' => {}
+                _ if connections(tile).is_empty() => {
+                    return Err(PipeError::InvalidTile {
+                        x: x + 1,
+                        y: y + 1,
+                        tile,
+                    }
+                    .into())
+                }
+                _ => {}
+            }
+            edges.extend(
+                connections(tile)
+                    .iter()
+                    .filter_map(|&direction| kinds.step(y, x, direction))
+                    .map(|next| ((y, x), next)),
+            );
+        }
+        let mut graph = PipeGraph::from_edges(edges);
 
-        let start = start.unwrap();
+        let start = start.ok_or(PipeError::MissingStart)?;
 
-        // We need to "patch" the start by adding inverted edges for all incoming edges
+        // We need to "patch" the start by adding inverted edges for all incoming edges. Junk pipes
+        // can point at the start as well, so these are only candidates for the loop.
         let edges_to_insert = graph
-            .edges_directed(start, Direction::Incoming)
+            .edges_directed(start, Incoming)
             .map(|(from, to, _)| (to, from))
             .collect::<Vec<_>>();
         for (from, to) in edges_to_insert.iter() {
             graph.add_edge(*from, *to, ());
         }
 
-        if edges_to_insert.contains(&(start, (start.0, start.1 - 1)))
-            && edges_to_insert.contains(&(start, (start.0, start.1 + 1)))
-        {
-            kinds.insert(start, '|');
-        } else if edges_to_insert.contains(&(start, (start.0 - 1, start.1)))
-            && edges_to_insert.contains(&(start, (start.0 + 1, start.1)))
-        {
-            kinds.insert(start, '-');
-        } else if edges_to_insert.contains(&(start, (start.0 + 1, start.1)))
-            && edges_to_insert.contains(&(start, (start.0, start.1 - 1)))
-        {
-            kinds.insert(start, 'L');
-        } else if edges_to_insert.contains(&(start, (start.0 - 1, start.1)))
-            && edges_to_insert.contains(&(start, (start.0, start.1 - 1)))
-        {
-            kinds.insert(start, 'J');
-        } else if edges_to_insert.contains(&(start, (start.0 - 1, start.1)))
-            && edges_to_insert.contains(&(start, (start.0, start.1 + 1)))
-        {
-            kinds.insert(start, '7');
-        } else if edges_to_insert.contains(&(start, (start.0 + 1, start.1)))
-            && edges_to_insert.contains(&(start, (start.0, start.1 + 1)))
-        {
-            kinds.insert(start, 'F');
-        } else {
-            bail!("Could not detect start type");
-        }
+        let loop_tiles = Self::find_loop(&kinds, &graph, start)?;
+        let first = loop_tiles[1];
+        let last = loop_tiles[loop_tiles.len() - 1];
+        kinds[start] = start_kind(&kinds, start, first, last);
 
         Ok(PipeInfo {
-            graph,
-            start,
             kinds,
+            graph,
+            loop_tiles,
         })
     }
 
-    fn get_loop(&self) -> Vec<(usize, usize)> {
-        let mut res = Vec::new();
-        let mut cur = self.start;
-        let mut prev = self.start;
+    /// Follows the pipes from the start in each candidate direction until one of them leads
+    /// back to the start.
+    ///
+    /// Every tile has to connect to both its neighbors in the loop, so the walk can't wander off
+    /// into pipes that only lead into the loop from outside. If no direction closes the loop, the
+    /// first break is reported.
+    fn find_loop(
+        kinds: &Grid<char>,
+        graph: &PipeGraph,
+        start: (usize, usize),
+    ) -> Result<Vec<(usize, usize)>, PipeError> {
+        let (y, x) = start;
+        let candidates = Direction::ALL
+            .into_iter()
+            .filter_map(|direction| kinds.step(y, x, direction))
+            .filter(|n| graph.contains_edge(start, *n))
+            .collect::<Vec<_>>();
+        if candidates.len() < 2 {
+            return Err(PipeError::UnresolvedStart { x: x + 1, y: y + 1 });
+        }
+
+        let mut first_error = None;
+        for candidate in candidates {
+            match Self::follow_loop(graph, start, candidate) {
+                Ok(loop_tiles) => return Ok(loop_tiles),
+                Err(error) => {
+                    first_error.get_or_insert(error);
+                }
+            }
+        }
+        Err(first_error.unwrap())
+    }
+
+    /// The tiles from the start through `first` and onwards, until the pipes lead back to the
+    /// start
+    fn follow_loop(
+        graph: &PipeGraph,
+        start: (usize, usize),
+        first: (usize, usize),
+    ) -> Result<Vec<(usize, usize)>, PipeError> {
+        let mut res = vec![start];
+        let mut prev = start;
+        let mut cur = first;
 
-        loop {
+        while cur != start {
             res.push(cur);
-            let next = self
-                .graph
+            let broken = |reason| PipeError::BrokenLoop {
+                x: cur.1 + 1,
+                y: cur.0 + 1,
+                reason,
+            };
+            let next = graph
                 .neighbors(cur)
-                .filter(|n| *n != prev)
-                .next()
-                .unwrap();
+                .find(|n| *n != prev)
+                .ok_or_else(|| broken("the pipe leads nowhere"))?;
+            if !graph.contains_edge(next, cur) {
+                return Err(broken("the next tile doesn't connect back"));
+            }
             prev = cur;
             cur = next;
-            if cur == self.start {
-                return res;
-            }
         }
+        Ok(res)
     }
+
+    /// The tiles of the loop in order, beginning at the start
+    fn get_loop(&self) -> &[(usize, usize)] {
+        &self.loop_tiles
+    }
+}
+
+/// The pipe under the start, which connects it to the `first` and `last` tiles of the loop
+fn start_kind(
+    kinds: &Grid<char>,
+    (y, x): (usize, usize),
+    first: (usize, usize),
+    last: (usize, usize),
+) -> char {
+    let leads_to = |direction, tile| kinds.step(y, x, direction) == Some(tile);
+    PIPES
+        .iter()
+        .find(|(_, [a, b])| {
+            (leads_to(*a, first) && leads_to(*b, last))
+                || (leads_to(*a, last) && leads_to(*b, first))
+        })
+        .map(|(pipe, _)| *pipe)
+        .expect("The loop connects the start to two of its neighbors")
+}
+
+/// Steps from the start to every tile of the loop, along the shorter way around it
+struct LoopDistances {
+    distances: HashMap<(usize, usize), usize>,
+    /// The tile farthest from the start, the one reached last going either way around
+    farthest: (usize, usize),
+}
+
+impl LoopDistances {
+    /// The same distances a breadth-first search from the start gives, but since the loop is a
+    /// single cycle, each tile is just as far as the smaller of its positions counted forwards
+    /// and backwards.
+    fn from_loop(loop_coords: &[(usize, usize)]) -> Self {
+        let length = loop_coords.len();
+        let distances = loop_coords
+            .iter()
+            .enumerate()
+            .map(|(idx, coord)| (*coord, idx.min(length - idx)))
+            .collect();
+        LoopDistances {
+            distances,
+            farthest: loop_coords[length / 2],
+        }
+    }
+
+    fn max_distance(&self) -> usize {
+        self.distances[&self.farthest]
+    }
+}
+
+const START_COLOR: &str = "#59a14f";
+const LOOP_COLOR: &str = "#e15759";
+
+/// The pipe graph for Graphviz, with an edge from every pipe to each tile it connects to. Tiles
+/// are named `line,column` and labeled with their pipe. The loop is highlighted.
+fn render_pipes<P: AsRef<Path>>(input: P) -> Result<GraphRenderer> {
+    let pipe_info = PipeInfo::read_input(input)?;
+    let name = |(y, x): (usize, usize)| format!("{},{}", y + 1, x + 1);
+    let loop_tiles = pipe_info.get_loop();
+    let loop_edges = loop_tiles
+        .iter()
+        .zip(loop_tiles.iter().cycle().skip(1))
+        .flat_map(|(&a, &b)| [(a, b), (b, a)])
+        .collect::<HashSet<_>>();
+
+    let mut graph = GraphRenderer::new(true);
+    for &tile in loop_tiles {
+        let color = if tile == loop_tiles[0] {
+            START_COLOR
+        } else {
+            LOOP_COLOR
+        };
+        graph.add_node(
+            name(tile),
+            Some(pipe_info.kinds[tile].to_string()),
+            Some(color.to_owned()),
+        );
+    }
+    for (from, to, _) in pipe_info.graph.all_edges() {
+        let color = loop_edges
+            .contains(&(from, to))
+            .then(|| LOOP_COLOR.to_owned());
+        graph.add_edge(name(from), name(to), None, color);
+    }
+    Ok(graph)
 }
 
 fn part1<P: AsRef<Path>>(input: P) -> Result<usize> {
-    let puzzle_input = PipeInfo::read_input(input)?;
+    let puzzle_input = timed!("parse", PipeInfo::read_input(input))?;
     let loop_coords = puzzle_input.get_loop();
     Ok(loop_coords.len() / 2)
 }
 
 // This will contain a tilemap version of our pipe world.
-// Each pipe has its own 3x3 tile area in the tile map, so coords in pipe_tiles will be scaled by
-// a factor of 3.
+// Each pipe has its own 3x3 tile area in the tile map, with the pipe running through the center
+// of that area towards the pipes it connects to. The corners of the areas are never part of a
+// pipe, so the outside of the loop is connected all around the border.
 struct TileMap {
-    unscaled_loop: Vec<(usize, usize)>,
-    pipe_tiles: HashSet<(usize, usize)>,
+    pipe_tiles: Grid<bool>,
 }
 
 impl TileMap {
     fn build_from_pipeinfo(pipe_info: &PipeInfo) -> Self {
-        let loop_pipes = pipe_info.get_loop();
-        let pipe_map = HashSet::from_iter(loop_pipes.iter().flat_map(|pos| {
-            let scaled_pos = (pos.0 * 3, pos.1 * 3);
-            match pipe_info.kinds.get(pos).unwrap() {
-                '|' => [
-                    (scaled_pos.0, scaled_pos.1 - 1),
-                    scaled_pos,
-                    (scaled_pos.0, scaled_pos.1 + 1),
-                ],
-                '-' => [
-                    (scaled_pos.0 - 1, scaled_pos.1),
-                    scaled_pos,
-                    (scaled_pos.0 + 1, scaled_pos.1),
-                ],
-                'L' => [
-                    (scaled_pos.0 + 1, scaled_pos.1),
-                    scaled_pos,
-                    (scaled_pos.0, scaled_pos.1 - 1),
-                ],
-                'J' => [
-                    (scaled_pos.0 - 1, scaled_pos.1),
-                    scaled_pos,
-                    (scaled_pos.0, scaled_pos.1 - 1),
-                ],
-                '7' => [
-                    (scaled_pos.0 - 1, scaled_pos.1),
-                    scaled_pos,
-                    (scaled_pos.0, scaled_pos.1 + 1),
-                ],
-                'F' => [
-                    (scaled_pos.0 + 1, scaled_pos.1),
-                    scaled_pos,
-                    (scaled_pos.0, scaled_pos.1 + 1),
-                ],
-                _ => panic!(),
+        let kinds = &pipe_info.kinds;
+        let mut pipe_tiles = Grid::new(kinds.width() * 3, kinds.height() * 3, false);
+        for &(y, x) in pipe_info.get_loop() {
+            let center = (y * 3 + 1, x * 3 + 1);
+            pipe_tiles[center] = true;
+            for &direction in connections(kinds[(y, x)]) {
+                let next = pipe_tiles
+                    .step(center.0, center.1, direction)
+                    .expect("The center of a 3x3 area has all neighbors");
+                pipe_tiles[next] = true;
             }
-        }));
-        TileMap {
-            unscaled_loop: loop_pipes,
-            pipe_tiles: pipe_map,
         }
+        TileMap { pipe_tiles }
     }
 
     fn find_enclosed_tiles(&self) -> Vec<(usize, usize)> {
-        // The not enclosed tiles (in pipe-world coords, i.e. NOT scaled by 3)
-        let mut not_enclosed = HashSet::<(usize, usize)>::new();
+        // Tile world coordinates that can be reached from the outside
+        let mut outside = self.pipe_tiles.map(|_| false);
         // Tile world coordinates that are currently scheduled to be checked
         // We start with 0,0 since we know that it will never be enclosed
         let mut to_check: Vec<(usize, usize)> = vec![(0, 0)];
-        // Tile world coordinates that already were checked and can be discarded
-        let mut checked = HashSet::<(usize, usize)>::new();
-
-        let bounds = (
-            *self.pipe_tiles.iter().map(|(x, _)| x).max().unwrap() + 1,
-            *self.pipe_tiles.iter().map(|(_, y)| y).max().unwrap() + 1,
-        );
-
-        // Helper function to calculate valid neighbors for the given coords
-        let neighbors = |(x, y): (usize, usize)| -> Vec<(usize, usize)> {
-            [(-1, 0), (1, 0), (0, -1), (0, 1)]
-                .iter()
-                .filter_map(|(dx, dy)| {
-                    x.checked_add_signed(*dx)
-                        .and_then(|new_x| y.checked_add_signed(*dy).map(|new_y| (new_x, new_y)))
-                })
-                .filter(|(new_x, new_y)| *new_x <= bounds.0 && *new_y <= bounds.1)
-                .collect::<Vec<_>>()
-        };
 
         // Do a DFS to find all unenclosed tiles (essentially like a flood fill in paint)
-        while let Some(pos) = to_check.pop() {
-            checked.insert(pos);
-            if pos.0 % 3 == 0 && pos.1 % 3 == 0 {
-                not_enclosed.insert((pos.0 / 3, pos.1 / 3));
-            }
-            if !self.pipe_tiles.contains(&pos) {
-                to_check.extend(neighbors(pos).iter().filter(|n| !checked.contains(n)));
+        while let Some((y, x)) = to_check.pop() {
+            if outside[(y, x)] || self.pipe_tiles[(y, x)] {
+                continue;
             }
+            outside[(y, x)] = true;
+            to_check.extend(
+                Direction::ALL
+                    .into_iter()
+                    .filter_map(|direction| self.pipe_tiles.step(y, x, direction)),
+            );
         }
 
         // Now we now all tiles that are NOT enclosed, so we can now just iterate over all tiles
-        // and collect the ones that are not in our not enclosed set
-        (0..=self.unscaled_loop.iter().map(|(x, _)| *x).max().unwrap())
-            .cartesian_product(0..=self.unscaled_loop.iter().map(|(_, y)| *y).max().unwrap())
-            .filter(|cand| {
-                !not_enclosed.contains(cand) && !self.pipe_tiles.contains(&(cand.0 * 3, cand.1 * 3))
-            })
+        // and collect the ones whose center is neither outside nor part of the loop
+        self.pipe_tiles
+            .iter()
+            .filter(|(y, x, pipe)| y % 3 == 1 && x % 3 == 1 && !**pipe && !outside[(*y, *x)])
+            .map(|(y, x, _)| (y / 3, x / 3))
             .collect()
     }
 }
 
+fn part1_distances<P: AsRef<Path>>(input: P) -> Result<usize> {
+    let puzzle_input = timed!("parse", PipeInfo::read_input(input))?;
+    let distances = LoopDistances::from_loop(puzzle_input.get_loop());
+    Ok(distances.max_distance())
+}
+
 fn part2<P: AsRef<Path>>(input: P) -> Result<usize> {
-    let puzzle_input = PipeInfo::read_input(input)?;
+    let puzzle_input = timed!("parse", PipeInfo::read_input(input))?;
+    // The loop is a polygon through the tile centers, so the enclosed tiles are exactly the
+    // integer points inside it, which Pick's theorem gives us from its area and length.
+    let corners = puzzle_input
+        .get_loop()
+        .iter()
+        .map(|&(y, x)| (y as i64, x as i64))
+        .collect::<Vec<_>>();
+    Ok(geometry::interior_points(&corners) as usize)
+}
+
+fn part2_flood_fill<P: AsRef<Path>>(input: P) -> Result<usize> {
+    let puzzle_input = timed!("parse", PipeInfo::read_input(input))?;
     let tile_map = TileMap::build_from_pipeinfo(&puzzle_input);
     let enclosed_tiles = tile_map.find_enclosed_tiles();
     Ok(enclosed_tiles.len())
 }
 
-fn main() -> Result<()> {
-    println!("Answer for part 1: {}", part1(INPUT)?);
-    println!("Answer for part 2: {}", part2(INPUT)?);
+struct Day10;
+
+impl AocDay for Day10 {
+    const YEAR: u16 = 2023;
+    const DAY: u8 = 10;
+
+    /// `--dot PATH` additionally stores the pipe graph in the DOT language, see
+    /// [`render_pipes`].
+    fn part1(input: &Path) -> Result<Answer> {
+        if let Some(path) = options().get::<PathBuf>("dot")? {
+            render_pipes(input)?.store_dot(path)?;
+        }
+        Ok(part1(input)?.into())
+    }
+
+    fn part2(input: &Path) -> Result<Answer> {
+        Ok(part2(input)?.into())
+    }
+
+    /// `--algo distances` looks the answer up in the distance map of the whole loop, see
+    /// [`LoopDistances`].
+    fn part1_alternatives() -> Vec<(&'static str, Solver)> {
+        vec![("distances", |input| Ok(part1_distances(input)?.into()))]
+    }
+
+    /// `--algo flood-fill` flood fills a map of the loop scaled by 3 instead.
+    fn part2_alternatives() -> Vec<(&'static str, Solver)> {
+        vec![("flood-fill", |input| Ok(part2_flood_fill(input)?.into()))]
+    }
+}
 
-    Ok(())
+fn main() -> Result<()> {
+    run::<Day10>()
 }
 
 #[cfg(test)]
@@ -274,6 +419,72 @@ mod tests_day10 {
             None,
         );
         assert_eq!(part1(&file).unwrap(), 4);
+        assert_eq!(part1_distances(&file).unwrap(), 4);
+        drop(dir);
+    }
+
+    #[test]
+    fn test_loop_distances() {
+        let (dir, file) = create_example_file(
+            indoc! {"
+            .....
+            .S-7.
+            .|.|.
+            .L-J.
+            .....
+        "},
+            None,
+        );
+        let pipe_info = PipeInfo::read_input(&file).unwrap();
+        let distances = LoopDistances::from_loop(pipe_info.get_loop());
+        assert_eq!(distances.farthest, (3, 3));
+        assert_eq!(distances.max_distance(), 4);
+        assert_eq!(distances.distances.len(), 8);
+        assert_eq!(distances.distances[&(1, 1)], 0);
+        assert_eq!(distances.distances[&(1, 2)], 1);
+        assert_eq!(distances.distances[&(2, 1)], 1);
+        assert_eq!(distances.distances[&(1, 3)], 2);
+        assert_eq!(distances.distances[&(3, 1)], 2);
+        assert_eq!(distances.distances[&(3, 2)], 3);
+        drop(dir);
+    }
+
+    #[test]
+    fn test_render_pipes() {
+        // A loop with a junk pipe leading into it from below
+        let (dir, file) = create_example_file(
+            indoc! {"
+            S7
+            LJ
+            |.
+        "},
+            None,
+        );
+        let mut dot = Vec::new();
+        render_pipes(&file).unwrap().render_dot(&mut dot).unwrap();
+        let dot = String::from_utf8(dot).unwrap();
+        let mut edges = dot
+            .lines()
+            .filter(|line| line.contains("->"))
+            .collect::<Vec<_>>();
+        edges.sort();
+        let loop_edge =
+            |from: &str, to: &str| format!(r#"  "{}" -> "{}" [color="{}"];"#, from, to, LOOP_COLOR);
+        assert_eq!(
+            edges,
+            [
+                loop_edge("1,1", "1,2"),
+                loop_edge("1,1", "2,1"),
+                loop_edge("1,2", "1,1"),
+                loop_edge("1,2", "2,2"),
+                loop_edge("2,1", "1,1"),
+                loop_edge("2,1", "2,2"),
+                loop_edge("2,2", "1,2"),
+                loop_edge("2,2", "2,1"),
+                r#"  "3,1" -> "2,1";"#.to_owned(),
+            ]
+        );
+        assert!(dot.contains(&format!(r#"  "1,1" [label="F", color="{}"];"#, START_COLOR)));
         drop(dir);
     }
 
@@ -311,6 +522,7 @@ mod tests_day10 {
             None,
         );
         assert_eq!(part2(&file).unwrap(), 4);
+        assert_eq!(part2_flood_fill(&file).unwrap(), 4);
         drop(dir);
     }
 
@@ -331,6 +543,7 @@ mod tests_day10 {
             None,
         );
         assert_eq!(part2(&file).unwrap(), 4);
+        assert_eq!(part2_flood_fill(&file).unwrap(), 4);
         drop(dir);
     }
 
@@ -352,6 +565,88 @@ mod tests_day10 {
             None,
         );
         assert_eq!(part2(&file).unwrap(), 8);
+        assert_eq!(part2_flood_fill(&file).unwrap(), 8);
+        drop(dir);
+    }
+
+    fn read_error(map: &str) -> PipeError {
+        let (dir, file) = create_example_file(map, None);
+        let error = part1(&file).unwrap_err().downcast().unwrap();
+        drop(dir);
+        error
+    }
+
+    #[test]
+    fn test_corrupt_maps() {
+        assert_eq!(
+            read_error(".....\n.S-7.\n.|X|.\n"),
+            PipeError::InvalidTile {
+                x: 3,
+                y: 3,
+                tile: 'X'
+            }
+        );
+        assert_eq!(read_error(".....\n.F-7.\n.L-J.\n"), PipeError::MissingStart);
+        assert_eq!(
+            read_error(".....\n.S-..\n.....\n"),
+            PipeError::UnresolvedStart { x: 2, y: 2 }
+        );
+        assert_eq!(
+            read_error(".....\n.S-7.\n.|.|.\n.L-..\n"),
+            PipeError::BrokenLoop {
+                x: 4,
+                y: 3,
+                reason: "the next tile doesn't connect back"
+            }
+        );
+        // The L turns away from the loop into the ground
+        let error = read_error(".....\n.S-7.\n.|.L.\n.L-J.\n");
+        assert_eq!(
+            error.to_string(),
+            "The loop is broken in line 3, column 4: the next tile doesn't connect back"
+        );
+    }
+
+    #[test]
+    fn test_junk_around_the_start() {
+        // Junk pipes point at the start from the north and the west
+        let (dir, file) = create_example_file(
+            indoc! {"
+            ..|..
+            .-S-7
+            ..|.|
+            ..L-J
+        "},
+            None,
+        );
+        let pipe_info = PipeInfo::read_input(&file).unwrap();
+        assert_eq!(pipe_info.kinds[(1, 2)], 'F');
+        assert_eq!(part1(&file).unwrap(), 4);
+        assert_eq!(part2(&file).unwrap(), 1);
+        assert_eq!(part2_flood_fill(&file).unwrap(), 1);
+        drop(dir);
+    }
+
+    #[test]
+    fn test_disjoint_loops_and_junk() {
+        // A second loop in the corner, and junk pipes touching the main loop from all sides
+        let (dir, file) = create_example_file(
+            indoc! {"
+            F7......
+            LJ.|....
+            ..-S-7-.
+            ...|.|..
+            ..-L-J-.
+            ....|...
+        "},
+            None,
+        );
+        let pipe_info = PipeInfo::read_input(&file).unwrap();
+        assert_eq!(pipe_info.kinds[(2, 3)], 'F');
+        assert_eq!(pipe_info.get_loop().len(), 8);
+        assert_eq!(part1(&file).unwrap(), 4);
+        assert_eq!(part2(&file).unwrap(), 1);
+        assert_eq!(part2_flood_fill(&file).unwrap(), 1);
         drop(dir);
     }
 }
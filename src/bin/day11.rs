@@ -1,13 +1,27 @@
-use std::{collections::HashSet, path::Path};
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+};
 
-use advent_of_code_2023::read_lines;
-use anyhow::Result;
+use advent_of_code_2023::{
+    answer::Answer,
+    read_lines,
+    render_grid::GridRenderer,
+    runner::{options, run, AocDay},
+    timed,
+};
+use anyhow::{bail, Result};
 use itertools::Itertools;
+use thiserror::Error;
 
-const INPUT: &str = "input/day11.txt";
-
+/// Galaxy positions as `(x, y)`, which are `u128` so even absurd expansion factors fit.
+/// Everything derived from them is checked anyway, so a result is never silently wrong.
 #[derive(Debug)]
-struct Universe(HashSet<(usize, usize)>);
+struct Universe(HashSet<(u128, u128)>);
+
+#[derive(Error, Debug, PartialEq, Eq)]
+#[error("Overflow: the expanded universe exceeds the range of 128 bit integers")]
+struct Overflow;
 
 impl Universe {
     fn from_input<P: AsRef<Path>>(input: P) -> Result<Self> {
@@ -18,46 +32,91 @@ impl Universe {
                 .flat_map(|(y, line)| {
                     line.chars()
                         .enumerate()
-                        .filter_map(|(x, c)| if c == '#' { Some((x, y)) } else { None })
+                        .filter_map(|(x, c)| (c == '#').then_some((x as u128, y as u128)))
                         .collect::<Vec<_>>()
                 })
                 .collect(),
         ))
     }
 
-    fn width(&self) -> usize {
+    fn width(&self) -> u128 {
         self.0.iter().map(|(x, _)| *x).max().unwrap() + 1
     }
 
-    fn height(&self) -> usize {
+    fn height(&self) -> u128 {
         self.0.iter().map(|(_, y)| *y).max().unwrap() + 1
     }
 
-    fn expand(&self, time_factor: usize) -> Self {
-        let cols_to_insert = (0..self.width())
+    fn empty_columns(&self) -> Vec<u128> {
+        (0..self.width())
             .filter(|x| self.0.iter().all(|cand| cand.0 != *x))
-            .collect::<Vec<_>>();
-        let rows_to_insert = (0..self.height())
+            .collect()
+    }
+
+    fn empty_rows(&self) -> Vec<u128> {
+        (0..self.height())
             .filter(|y| self.0.iter().all(|cand| cand.1 != *y))
-            .collect::<Vec<_>>();
+            .collect()
+    }
 
-        Self(
+    /// Moves a position by `time_factor` for each of the empty columns and rows before it
+    fn expand_position(
+        (old_x, old_y): (u128, u128),
+        cols_to_insert: &[u128],
+        rows_to_insert: &[u128],
+        time_factor: u128,
+    ) -> Result<(u128, u128), Overflow> {
+        let shift = |old: u128, lines_to_insert: &[u128]| {
+            let expansion = lines_to_insert.iter().filter(|l| **l < old).count() as u128;
+            expansion
+                .checked_mul(time_factor)
+                .and_then(|shift| old.checked_add(shift))
+                .ok_or(Overflow)
+        };
+        Ok((shift(old_x, cols_to_insert)?, shift(old_y, rows_to_insert)?))
+    }
+
+    fn expand(&self, time_factor: u128) -> Result<Self, Overflow> {
+        let cols_to_insert = self.empty_columns();
+        let rows_to_insert = self.empty_rows();
+
+        Ok(Self(
             self.0
                 .iter()
-                .map(|(old_x, old_y)| {
-                    let x_expansion = cols_to_insert.iter().filter(|c| *c < old_x).count();
-                    let y_expansion = rows_to_insert.iter().filter(|c| *c < old_y).count();
-
-                    (
-                        old_x + (x_expansion * time_factor),
-                        old_y + (y_expansion * time_factor),
-                    )
+                .map(|pos| {
+                    Self::expand_position(*pos, &cols_to_insert, &rows_to_insert, time_factor)
                 })
-                .collect(),
-        )
+                .collect::<Result<_, _>>()?,
+        ))
+    }
+
+    /// Sum of the Manhattan distances between all pairs of galaxies.
+    ///
+    /// The distances split into one sum per axis. After sorting the coordinates of an axis, each
+    /// one is larger than all of the previous ones, so its distances to them add up to
+    /// `idx * coordinate` minus the sum of the previous coordinates.
+    fn sum_of_pairwise_distances(&self) -> Result<u128, Overflow> {
+        let axis_sum = |mut coords: Vec<u128>| {
+            coords.sort_unstable();
+            let mut prefix_sum: u128 = 0;
+            let mut sum: u128 = 0;
+            for (idx, coord) in coords.into_iter().enumerate() {
+                // The previous coordinates are all smaller, so their sum never exceeds this
+                let distances = (idx as u128).checked_mul(coord).ok_or(Overflow)? - prefix_sum;
+                sum = sum.checked_add(distances).ok_or(Overflow)?;
+                prefix_sum = prefix_sum.checked_add(coord).ok_or(Overflow)?;
+            }
+            Ok(sum)
+        };
+        axis_sum(self.0.iter().map(|(x, _)| *x).collect())?
+            .checked_add(axis_sum(self.0.iter().map(|(_, y)| *y).collect())?)
+            .ok_or(Overflow)
     }
 
-    fn get_some_of_pairwise_distances(&self) -> usize {
+    /// Sums up the distance of every pair of galaxies directly, to verify
+    /// [`sum_of_pairwise_distances`](Self::sum_of_pairwise_distances)
+    #[cfg(test)]
+    fn get_some_of_pairwise_distances(&self) -> u128 {
         self.0
             .iter()
             .cartesian_product(self.0.iter())
@@ -72,31 +131,122 @@ impl Universe {
     }
 }
 
-fn part1<P: AsRef<Path>>(input: P) -> Result<usize> {
-    let universe = Universe::from_input(input)?.expand(1);
-    Ok(universe.get_some_of_pairwise_distances())
+/// Sum of the distances between all pairs of galaxies, after each empty row and column has been
+/// replaced by `factor` empty ones
+fn solve<P: AsRef<Path>>(input: P, factor: u128) -> Result<u128> {
+    if factor == 0 {
+        bail!("The expansion factor must be at least 1");
+    }
+    let universe = timed!("parse", Universe::from_input(input))?.expand(factor - 1)?;
+    Ok(universe.sum_of_pairwise_distances()?)
+}
+
+const ORIGINAL_COLOR: &str = "#bab0ac";
+const EMPTY_COLOR: &str = "#4e79a733";
+const EXPANDED_COLOR: &str = "#f28e2b";
+const MOVE_COLOR: &str = "#f28e2b88";
+
+/// Draws the galaxies before and after the expansion by `factor` on separate layers, with the
+/// empty rows and columns that replace the original ones in between. Each galaxy is connected to
+/// where it moved, and hovering it shows its number and both positions.
+fn render_expansion<P: AsRef<Path>>(input: P, factor: u128) -> Result<GridRenderer<u128>> {
+    if factor == 0 {
+        bail!("The expansion factor must be at least 1");
+    }
+    let universe = Universe::from_input(input)?;
+    let empty_columns = universe.empty_columns();
+    let empty_rows = universe.empty_rows();
+    let expand = |pos| Universe::expand_position(pos, &empty_columns, &empty_rows, factor - 1);
+    let (width, height) = expand((universe.width(), universe.height()))?;
+
+    let mut renderer = GridRenderer::new();
+    renderer.set_layer("expansion");
+    for x in &empty_columns {
+        let (x, _) = expand((*x, 0))?;
+        renderer.add_colored_rect(0, x, height, factor, EMPTY_COLOR.to_owned(), None);
+    }
+    for y in &empty_rows {
+        let (_, y) = expand((0, *y))?;
+        renderer.add_colored_rect(y, 0, factor, width, EMPTY_COLOR.to_owned(), None);
+    }
+
+    // Number the galaxies like the puzzle does, row by row
+    let galaxies = universe
+        .0
+        .iter()
+        .copied()
+        .sorted_by_key(|(x, y)| (*y, *x))
+        .collect::<Vec<_>>();
+    renderer.set_layer("original");
+    for (idx, (x, y)) in galaxies.iter().enumerate() {
+        let tooltip = format!("galaxy {} at ({}, {})", idx + 1, x, y);
+        renderer.add_colored_grid_tile(*y, *x, ORIGINAL_COLOR.to_owned(), Some(tooltip));
+    }
+    renderer.set_layer("expanded");
+    for (idx, &(x, y)) in galaxies.iter().enumerate() {
+        let (new_x, new_y) = expand((x, y))?;
+        let tooltip = format!(
+            "galaxy {} moved from ({}, {}) to ({}, {})",
+            idx + 1,
+            x,
+            y,
+            new_x,
+            new_y
+        );
+        renderer.add_colored_grid_tile(new_y, new_x, EXPANDED_COLOR.to_owned(), Some(tooltip));
+        renderer.add_path([(y, x), (new_y, new_x)], MOVE_COLOR.to_owned(), 0.2);
+    }
+    renderer.add_legend(&[
+        (ORIGINAL_COLOR, "original galaxy"),
+        (EMPTY_COLOR, "empty after expansion"),
+        (EXPANDED_COLOR, "expanded galaxy"),
+    ]);
+    Ok(renderer)
+}
+
+fn part1<P: AsRef<Path>>(input: P) -> Result<u128> {
+    solve(input, 2)
+}
+
+fn part2<P: AsRef<Path>>(input: P) -> Result<u128> {
+    solve(input, 1_000_000)
 }
 
-fn part1and_a_half<P: AsRef<Path>>(input: P) -> Result<usize> {
-    // Just for testing the expansion
-    let universe = Universe::from_input(input)?.expand(9);
-    Ok(universe.get_some_of_pairwise_distances())
+fn factor() -> Result<Option<u128>> {
+    options().get("factor")
 }
 
-fn part2<P: AsRef<Path>>(input: P) -> Result<usize> {
-    let universe = Universe::from_input(input)?.expand(1000000 - 1);
-    Ok(universe.get_some_of_pairwise_distances())
+struct Day11;
+
+impl AocDay for Day11 {
+    const YEAR: u16 = 2023;
+    const DAY: u8 = 11;
+
+    /// `--factor N` replaces each empty row and column by N empty ones instead of 2.
+    /// `--visualize PATH` additionally stores an SVG of the expansion, see [`render_expansion`].
+    fn part1(input: &Path) -> Result<Answer> {
+        if let Some(path) = options().get::<PathBuf>("visualize")? {
+            render_expansion(input, factor()?.unwrap_or(2))?.store_svg(path)?;
+        }
+        Ok(match factor()? {
+            Some(factor) => solve(input, factor)?,
+            None => part1(input)?,
+        }
+        .into())
+    }
+
+    /// `--factor N` replaces each empty row and column by N empty ones instead of a million.
+    fn part2(input: &Path) -> Result<Answer> {
+        Ok(match factor()? {
+            Some(factor) => solve(input, factor)?,
+            None => part2(input)?,
+        }
+        .into())
+    }
 }
 
 fn main() -> Result<()> {
-    println!("Answer for part 1: {}", part1(INPUT)?);
-    println!(
-        "Answer for part 1 with higher factor: {}",
-        part1and_a_half(INPUT)?
-    );
-    println!("Answer for part 2: {}", part2(INPUT)?);
-
-    Ok(())
+    run::<Day11>()
 }
 
 #[cfg(test)]
@@ -123,8 +273,86 @@ mod tests_day11 {
             None,
         );
         assert_eq!(part1(&file).unwrap(), 374);
-        assert_eq!(part1and_a_half(&file).unwrap(), 1030);
+        assert_eq!(solve(&file, 10).unwrap(), 1030);
+        assert_eq!(solve(&file, 100).unwrap(), 8410);
+        // Without expansion
+        assert_eq!(solve(&file, 1).unwrap(), 292);
+        assert!(solve(&file, 0).is_err());
         // No test output for part 2 available
         drop(dir);
     }
+
+    #[test]
+    fn test_sum_of_pairwise_distances() {
+        let universe = Universe(HashSet::from([(0, 0), (3, 1), (3, 7), (10, 2), (5, 5)]));
+        assert_eq!(
+            universe.sum_of_pairwise_distances(),
+            Ok(universe.get_some_of_pairwise_distances())
+        );
+        for factor in [0, 1, 9, 999_999] {
+            let expanded = universe.expand(factor).unwrap();
+            assert_eq!(
+                expanded.sum_of_pairwise_distances(),
+                Ok(expanded.get_some_of_pairwise_distances())
+            );
+        }
+    }
+
+    #[test]
+    fn test_render_expansion() {
+        let (dir, file) = create_example_file(
+            indoc! {"
+            #..
+            ...
+            ..#
+        "},
+            None,
+        );
+        let mut svg = Vec::new();
+        render_expansion(&file, 3)
+            .unwrap()
+            .render_svg(&mut svg)
+            .unwrap();
+        let svg = String::from_utf8(svg).unwrap();
+        let count = |color: &str| svg.matches(&format!(r#"fill="{}""#, color)).count();
+        // One extra swatch per color in the legend
+        assert_eq!(count(ORIGINAL_COLOR), 2 + 1);
+        assert_eq!(count(EMPTY_COLOR), 2 + 1);
+        assert_eq!(count(EXPANDED_COLOR), 2 + 1);
+        assert!(svg.contains("<title>galaxy 2 moved from (2, 2) to (4, 4)</title>"));
+        drop(dir);
+    }
+
+    #[test]
+    fn test_absurd_factors() {
+        let (dir, file) = create_example_file(
+            indoc! {"
+            ...#......
+            .......#..
+            #.........
+            ..........
+            ......#...
+            .#........
+            .........#
+            ..........
+            .......#..
+            #...#.....
+        "},
+            None,
+        );
+        // Each additional empty line adds 82 to the sum, as factors 2 and 10 show
+        let factor = 1 << 100;
+        assert_eq!(solve(&file, factor).unwrap(), 292 + 82 * (factor - 1));
+        assert_eq!(
+            format!("{:#}", solve(&file, u128::MAX / 4).unwrap_err()),
+            "Overflow: the expanded universe exceeds the range of 128 bit integers"
+        );
+        assert_eq!(
+            Universe(HashSet::from([(0, 0), (2, 0)]))
+                .expand(u128::MAX)
+                .unwrap_err(),
+            Overflow
+        );
+        drop(dir);
+    }
 }
@@ -1,46 +1,16 @@
-use std::{collections::HashMap, path::Path, str::FromStr};
+use std::{collections::HashMap, fmt::Display, path::Path, str::FromStr};
 
-use advent_of_code_2023::stream_items_from_file;
+use advent_of_code_2023::{
+    answer::Answer,
+    nonogram::{arrangements, count_arrangements, Cell},
+    runner::{options, run, AocDay, Solver},
+    stream_items_from_file, timed,
+};
 use anyhow::Result;
-
-const INPUT: &str = "input/day12.txt";
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum SpringInfo {
-    Operational,
-    Damaged,
-    Unknown,
-}
-
-impl SpringInfo {
-    fn from_char(c: char) -> Self {
-        match c {
-            '.' => SpringInfo::Operational,
-            '#' => SpringInfo::Damaged,
-            '?' => SpringInfo::Unknown,
-            _ => panic!("Invalid char"),
-        }
-    }
-
-    fn could_be_damaged(&self) -> bool {
-        match self {
-            SpringInfo::Operational => false,
-            SpringInfo::Damaged => true,
-            SpringInfo::Unknown => true,
-        }
-    }
-
-    fn could_be_working(&self) -> bool {
-        match self {
-            SpringInfo::Operational => true,
-            SpringInfo::Damaged => false,
-            SpringInfo::Unknown => true,
-        }
-    }
-}
+use itertools::Itertools;
 
 struct DamagedSpringReport {
-    records: Vec<SpringInfo>,
+    records: Vec<Cell>,
     groups: Vec<usize>,
 }
 
@@ -50,7 +20,10 @@ impl FromStr for DamagedSpringReport {
     fn from_str(s: &str) -> Result<Self> {
         let (input_records, input_groups) = s.split_once(" ").unwrap();
 
-        let records = input_records.chars().map(SpringInfo::from_char).collect();
+        let records = input_records
+            .chars()
+            .map(|c| Cell::from_char(c).expect("Invalid char"))
+            .collect();
 
         let groups = input_groups
             .split(',')
@@ -60,13 +33,22 @@ impl FromStr for DamagedSpringReport {
     }
 }
 
+impl Display for DamagedSpringReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for record in &self.records {
+            write!(f, "{}", record)?;
+        }
+        write!(f, " {}", self.groups.iter().join(","))
+    }
+}
+
 impl DamagedSpringReport {
     fn consume_broken_group(&self, spring_pos: usize, group_idx: usize) -> Option<(usize, usize)> {
         if let Some(group_len) = self.groups.get(group_idx) {
             if (spring_pos..spring_pos + group_len).all(|pos| {
                 self.records
                     .get(pos)
-                    .map(|s| s.could_be_damaged())
+                    .map(|s| s.could_be_filled())
                     .unwrap_or(false)
             }) {
                 Some((spring_pos + group_len, group_idx + 1))
@@ -80,7 +62,7 @@ impl DamagedSpringReport {
 
     fn consume_working(&self, spring_pos: usize) -> Option<usize> {
         self.records.get(spring_pos).and_then(|i| {
-            if i.could_be_working() {
+            if i.could_be_empty() {
                 Some(spring_pos + 1)
             } else {
                 None
@@ -101,7 +83,7 @@ impl DamagedSpringReport {
             solutions = 1;
         } else {
             if let Some((intermediate_pos, new_group)) = self.consume_broken_group(pos, group) {
-                if let Some(new_pos) = self.consume_working(intermediate_pos).or_else(|| {
+                if let Some(new_pos) = self.consume_working(intermediate_pos).or({
                     if intermediate_pos == self.records.len() {
                         Some(intermediate_pos)
                     } else {
@@ -137,14 +119,32 @@ impl DamagedSpringReport {
         }
     }
 
-    fn count_solutions(&self) -> usize {
+    fn count_solutions_recursive(&self) -> usize {
         let mut cache = HashMap::new();
         self.count_rec(0, 0, &mut cache)
     }
 
+    fn count_solutions(&self) -> usize {
+        count_arrangements(&self.records, &self.groups)
+    }
+
+    /// The report with its count, followed by at most `limit` of the concrete arrangements
+    fn describe_arrangements(&self, limit: usize) -> String {
+        let count = self.count_solutions();
+        let mut description = format!("{}: {} arrangements", self, count);
+        for row in arrangements(&self.records, &self.groups).take(limit) {
+            description.push_str("\n  ");
+            description.extend(row.iter().map(Cell::to_string));
+        }
+        if count > limit {
+            description.push_str("\n  ...");
+        }
+        description
+    }
+
     fn unfold(mut self) -> Self {
         // Hacky way to get the '?' separation: Push to the non-duplicated list...
-        self.records.push(SpringInfo::Unknown);
+        self.records.push(Cell::Unknown);
         let mut records = self.records.repeat(5);
         // ...and pop later.
         records.pop();
@@ -154,25 +154,120 @@ impl DamagedSpringReport {
     }
 }
 
-fn part1<P: AsRef<Path>>(input: P) -> Result<usize> {
+fn read_reports<P: AsRef<Path>>(input: P) -> Result<Vec<DamagedSpringReport>> {
     Ok(stream_items_from_file::<_, DamagedSpringReport>(input)?
-        .map(|report| report.unwrap().count_solutions())
-        .sum())
+        .map(|report| report.unwrap())
+        .collect())
 }
 
-fn part2<P: AsRef<Path>>(input: P) -> Result<usize> {
-    Ok(stream_items_from_file::<_, DamagedSpringReport>(input)?
-        .map(|report| report.unwrap().unfold().count_solutions())
-        .sum())
+/// Sums up the solutions of all reports, one after the other.
+#[cfg(not(feature = "parallel"))]
+fn sum_solutions<F>(reports: &[DamagedSpringReport], count: F) -> usize
+where
+    F: Fn(&DamagedSpringReport) -> usize + Send + Sync,
+{
+    reports.iter().map(count).sum()
 }
 
-fn main() -> Result<()> {
-    println!("Answer for part 1: {}", part1(INPUT)?);
-    println!("Answer for part 2: {}", part2(INPUT)?);
+/// Sums up the solutions of all reports, spread over all cores since they are independent of
+/// each other.
+#[cfg(feature = "parallel")]
+fn sum_solutions<F>(reports: &[DamagedSpringReport], count: F) -> usize
+where
+    F: Fn(&DamagedSpringReport) -> usize + Send + Sync,
+{
+    use rayon::prelude::*;
+    reports.par_iter().map(count).sum()
+}
 
+const DEFAULT_LIST_LIMIT: usize = 10;
+
+/// Prints the arrangements of each report for `--list`, at most `--list-limit N` of them
+fn list_arrangements(reports: &[DamagedSpringReport]) -> Result<()> {
+    let limit = options().get("list-limit")?.unwrap_or(DEFAULT_LIST_LIMIT);
+    for (idx, report) in reports.iter().enumerate() {
+        println!("Line {}: {}", idx + 1, report.describe_arrangements(limit));
+    }
     Ok(())
 }
 
+fn part1<P: AsRef<Path>>(input: P) -> Result<usize> {
+    let reports = timed!("parse", read_reports(input))?;
+    Ok(sum_solutions(
+        &reports,
+        DamagedSpringReport::count_solutions,
+    ))
+}
+
+fn part2<P: AsRef<Path>>(input: P) -> Result<usize> {
+    let reports = timed!("parse", read_reports(input))?
+        .into_iter()
+        .map(DamagedSpringReport::unfold)
+        .collect::<Vec<_>>();
+    Ok(sum_solutions(
+        &reports,
+        DamagedSpringReport::count_solutions,
+    ))
+}
+
+fn part1_recursive<P: AsRef<Path>>(input: P) -> Result<usize> {
+    let reports = timed!("parse", read_reports(input))?;
+    Ok(sum_solutions(
+        &reports,
+        DamagedSpringReport::count_solutions_recursive,
+    ))
+}
+
+fn part2_recursive<P: AsRef<Path>>(input: P) -> Result<usize> {
+    let reports = timed!("parse", read_reports(input))?
+        .into_iter()
+        .map(DamagedSpringReport::unfold)
+        .collect::<Vec<_>>();
+    Ok(sum_solutions(
+        &reports,
+        DamagedSpringReport::count_solutions_recursive,
+    ))
+}
+
+struct Day12;
+
+impl AocDay for Day12 {
+    const YEAR: u16 = 2023;
+    const DAY: u8 = 12;
+
+    /// `--list` prints the concrete arrangements of each report, see [`list_arrangements`].
+    fn part1(input: &Path) -> Result<Answer> {
+        if options().flag("list") {
+            list_arrangements(&read_reports(input)?)?;
+        }
+        Ok(part1(input)?.into())
+    }
+
+    fn part2(input: &Path) -> Result<Answer> {
+        if options().flag("list") {
+            let reports = read_reports(input)?
+                .into_iter()
+                .map(DamagedSpringReport::unfold)
+                .collect::<Vec<_>>();
+            list_arrangements(&reports)?;
+        }
+        Ok(part2(input)?.into())
+    }
+
+    /// `--algo recursive` counts with the memoized recursion instead of the table.
+    fn part1_alternatives() -> Vec<(&'static str, Solver)> {
+        vec![("recursive", |input| Ok(part1_recursive(input)?.into()))]
+    }
+
+    fn part2_alternatives() -> Vec<(&'static str, Solver)> {
+        vec![("recursive", |input| Ok(part2_recursive(input)?.into()))]
+    }
+}
+
+fn main() -> Result<()> {
+    run::<Day12>()
+}
+
 #[cfg(test)]
 mod tests_day12 {
     use super::*;
@@ -194,6 +289,68 @@ mod tests_day12 {
         );
         assert_eq!(part1(&file).unwrap(), 21);
         assert_eq!(part2(&file).unwrap(), 525152);
+        assert_eq!(part1_recursive(&file).unwrap(), 21);
+        assert_eq!(part2_recursive(&file).unwrap(), 525152);
         drop(dir);
     }
+
+    #[test]
+    fn test_table_matches_recursion() {
+        let patterns = [
+            "",
+            "?",
+            "#",
+            ".",
+            "??",
+            "#?#",
+            "?.?.?",
+            "?#??#?.??",
+            "???.###????",
+        ];
+        let groups: [&[usize]; 6] = [&[], &[1], &[2], &[1, 1], &[3, 1], &[1, 2, 1]];
+        for pattern in patterns {
+            for groups in groups {
+                let report = DamagedSpringReport {
+                    records: pattern
+                        .chars()
+                        .map(|c| Cell::from_char(c).expect("Invalid char"))
+                        .collect(),
+                    groups: groups.to_vec(),
+                };
+                assert_eq!(
+                    report.count_solutions(),
+                    report.count_solutions_recursive(),
+                    "{} {:?}",
+                    pattern,
+                    groups
+                );
+                let unfolded = report.unfold();
+                assert_eq!(
+                    unfolded.count_solutions(),
+                    unfolded.count_solutions_recursive()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_describe_arrangements() {
+        let report = ".??..??...?##. 1,1,3"
+            .parse::<DamagedSpringReport>()
+            .unwrap();
+        assert_eq!(
+            report.describe_arrangements(3),
+            indoc! {"
+                .??..??...?##. 1,1,3: 4 arrangements
+                  ..#...#...###.
+                  ..#..#....###.
+                  .#....#...###.
+                  ..."}
+        );
+        assert_eq!(
+            report.unfold().describe_arrangements(0),
+            ".??..??...?##.?.??..??...?##.?.??..??...?##.?.??..??...?##.?.??..??...?##. \
+             1,1,3,1,1,3,1,1,3,1,1,3,1,1,3: 16384 arrangements\n  ..."
+        );
+    }
 }
@@ -1,9 +1,12 @@
 use std::{ops::BitXor, path::Path};
 
-use advent_of_code_2023::stream_file_blocks;
+use advent_of_code_2023::{
+    answer::Answer,
+    runner::{run, AocDay},
+    stream_file_blocks,
+};
 use anyhow::Result;
-
-const INPUT: &str = "input/day13.txt";
+use thiserror::Error;
 
 // We store the pattern as u32 bitmaps. To know how many bits are valid, we use the additional
 // length parameter in the second slot.
@@ -37,25 +40,36 @@ fn get_bit(val: u32, idx: usize) -> u32 {
     }
 }
 
-impl Pattern {
-    fn find_symmetry_between_lines(&self) -> Option<usize> {
-        (1..self.0.len())
-            .filter(|axis| {
-                (0..*axis).rev().all(|delta| {
-                    self.0
-                        .get(axis - 1 - delta)
-                        .and_then(|before| self.0.get(axis + delta).map(|after| before == after))
-                        .unwrap_or(true)
-                })
-            })
-            .next()
+/// The lines of reflection of a pattern, as the number of rows above or columns left of them
+#[derive(Debug, PartialEq, Eq)]
+struct Axes {
+    horizontal: Vec<usize>,
+    vertical: Vec<usize>,
+}
+
+impl Axes {
+    /// Sum of 100 times each horizontal axis plus each vertical axis. Puzzle inputs have a
+    /// single axis per pattern, this counts all of them if there are more.
+    fn score(&self) -> usize {
+        self.horizontal.iter().map(|axis| axis * 100).sum::<usize>()
+            + self.vertical.iter().sum::<usize>()
     }
+}
 
-    fn find_symmetry_between_lines_with_smudge(&self) -> Option<usize> {
+#[derive(Error, Debug, PartialEq, Eq)]
+#[error("No line of reflection with {smudges} smudges in pattern {block}")]
+struct NoAxisError {
+    block: usize,
+    smudges: u32,
+}
+
+impl Pattern {
+    /// Axes between lines where the mirrored lines differ in exactly `smudges` cells in total,
+    /// lines mirrored beyond the edge of the pattern don't count.
+    fn find_symmetries_between_lines(&self, smudges: u32) -> Vec<usize> {
         (1..self.0.len())
             .filter(|axis| {
-                let bitdiffs = (0..*axis)
-                    .rev()
+                let differences = (0..*axis)
                     .map(|delta| {
                         self.0
                             .get(axis - 1 - delta)
@@ -63,17 +77,12 @@ impl Pattern {
                                 self.0.get(axis + delta).map(|after| before.bitxor(after))
                             })
                             .unwrap_or(0)
+                            .count_ones()
                     })
-                    .collect::<Vec<_>>();
-                if bitdiffs.iter().all(|d| d.count_ones() <= 1)
-                    && bitdiffs.iter().filter(|d| d.count_ones() == 1).count() == 1
-                {
-                    true
-                } else {
-                    false
-                }
+                    .sum::<u32>();
+                differences == smudges
             })
-            .next()
+            .collect()
     }
 
     fn transpose(&self) -> Self {
@@ -90,44 +99,56 @@ impl Pattern {
         )
     }
 
-    fn score_symmetry(&self) -> usize {
-        if let Some(axis) = self.find_symmetry_between_lines() {
-            axis * 100
-        } else {
-            self.transpose().find_symmetry_between_lines().unwrap()
+    fn symmetry_axes(&self, smudges: u32) -> Axes {
+        Axes {
+            horizontal: self.find_symmetries_between_lines(smudges),
+            vertical: self.transpose().find_symmetries_between_lines(smudges),
         }
     }
+}
 
-    fn score_symmetry_with_smudge(&self) -> usize {
-        if let Some(axis) = self.find_symmetry_between_lines_with_smudge() {
-            axis * 100
-        } else {
-            self.transpose()
-                .find_symmetry_between_lines_with_smudge()
-                .unwrap()
+/// Sums up the scores of all patterns, whose axes have to fix exactly `smudges` smudges
+fn sum_scores<P: AsRef<Path>>(input: P, smudges: u32) -> Result<usize> {
+    let mut sum = 0;
+    for (idx, block) in stream_file_blocks(input)?.enumerate() {
+        let axes = Pattern::from(block).symmetry_axes(smudges);
+        if axes.horizontal.is_empty() && axes.vertical.is_empty() {
+            return Err(NoAxisError {
+                block: idx + 1,
+                smudges,
+            }
+            .into());
         }
+        sum += axes.score();
     }
+    Ok(sum)
 }
 
 fn part1<P: AsRef<Path>>(input: P) -> Result<usize> {
-    let blocks = stream_file_blocks(input)?;
-    Ok(blocks
-        .map(|block| Pattern::from(block).score_symmetry())
-        .sum())
+    sum_scores(input, 0)
 }
 
 fn part2<P: AsRef<Path>>(input: P) -> Result<usize> {
-    let blocks = stream_file_blocks(input)?;
-    Ok(blocks
-        .map(|block| Pattern::from(block).score_symmetry_with_smudge())
-        .sum())
+    sum_scores(input, 1)
 }
 
-fn main() -> Result<()> {
-    println!("Answer for part 1: {}", part1(INPUT)?);
-    println!("Answer for part 2: {}", part2(INPUT)?);
+struct Day13;
+
+impl AocDay for Day13 {
+    const YEAR: u16 = 2023;
+    const DAY: u8 = 13;
+
+    fn part1(input: &Path) -> Result<Answer> {
+        Ok(part1(input)?.into())
+    }
+
+    fn part2(input: &Path) -> Result<Answer> {
+        Ok(part2(input)?.into())
+    }
+}
 
-    Ok(())
+fn main() -> Result<()> {
+    run::<Day13>()
 }
 
 #[cfg(test)]
@@ -162,4 +183,42 @@ mod tests_day13 {
         assert_eq!(part2(&file).unwrap(), 400);
         drop(dir);
     }
+
+    #[test]
+    fn test_all_axes() {
+        let pattern = Pattern::from(["#..#", "#..#", "#..#", "#..#"].map(str::to_owned).to_vec());
+        let axes = pattern.symmetry_axes(0);
+        assert_eq!(
+            axes,
+            Axes {
+                horizontal: vec![1, 2, 3],
+                vertical: vec![2]
+            }
+        );
+        assert_eq!(axes.score(), 602);
+    }
+
+    #[test]
+    fn test_missing_axis() {
+        let (dir, file) = create_example_file(
+            indoc! {"
+            ##
+            ##
+
+            #.
+            ..
+        "},
+            None,
+        );
+        assert_eq!(
+            part1(&file).unwrap_err().to_string(),
+            "No line of reflection with 0 smudges in pattern 2"
+        );
+        // The first pattern is symmetric without fixing a smudge
+        assert_eq!(
+            part2(&file).unwrap_err().to_string(),
+            "No line of reflection with 1 smudges in pattern 1"
+        );
+        drop(dir);
+    }
 }
@@ -1,175 +1,393 @@
-use std::{path::Path, collections::HashMap};
+use std::{
+    collections::HashMap,
+    hash::Hash,
+    path::{Path, PathBuf},
+    time::Duration,
+};
 
-use advent_of_code_2023::read_lines;
-use anyhow::Result;
+use advent_of_code_2023::{
+    answer::Answer,
+    grid::{Direction, Grid},
+    read_lines,
+    render_grid::GridRenderer,
+    runner::{options, run, AocDay, Solver},
+    timed,
+};
+use anyhow::{bail, Result};
 
-const INPUT: &str = "input/day14.txt";
+/// The most cells a row can have, one per bit
+const MAX_WIDTH: usize = 128;
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
-enum FieldState {
-    RoundRock, // O
-    CubeRock,  // #
-    Empty,     // .
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Cell {
+    Round,
+    Cube,
+    Empty,
 }
 
-impl From<char> for FieldState {
-    fn from(c: char) -> Self {
+impl Cell {
+    fn parse(c: char) -> Result<Self> {
         match c {
-            'O' => FieldState::RoundRock,
-            '#' => FieldState::CubeRock,
-            '.' => FieldState::Empty,
-            _ => panic!("Invalid field state"),
+            'O' => Ok(Cell::Round),
+            '#' => Ok(Cell::Cube),
+            '.' => Ok(Cell::Empty),
+            _ => bail!("Invalid field state {:?}", c),
         }
     }
 }
 
+/// The field as one bitset per row, where bit `x` is set if there is a rock in column `x`.
+///
+/// Tilting becomes bit manipulation, and the round rocks are a small key for the cycle history.
+/// [`RockField::to_grid`] converts it back for anything that works on whole cells.
 #[derive(Debug, Clone, Eq, Hash, PartialEq)]
-struct RockField(Vec<Vec<FieldState>>);
+struct RockField {
+    round: Vec<u128>,
+    cubes: Vec<u128>,
+    width: usize,
+}
 
-impl<T> From<T> for RockField
-where
-    T: Iterator<Item = String>,
-{
-    fn from(lines: T) -> Self {
-        let mut field = Vec::new();
-        for line in lines {
-            let mut row = Vec::new();
-            for c in line.chars() {
-                row.push(FieldState::from(c));
-            }
-            field.push(row);
-        }
-        RockField(field)
+/// Bits `from..to`
+fn bits(from: usize, to: usize) -> u128 {
+    match to - from {
+        0 => 0,
+        MAX_WIDTH => !0,
+        len => ((1 << len) - 1) << from,
     }
 }
 
 impl RockField {
-    fn transpose(&self) -> Self {
-        let mut field = Vec::new();
-        for i in 0..self.0.len() {
-            let mut row = Vec::new();
-            for j in 0..self.0[i].len() {
-                row.push(self.0[j][i]);
-            }
-            field.push(row);
+    fn from_grid(grid: &Grid<Cell>) -> Result<Self> {
+        if grid.width() > MAX_WIDTH {
+            bail!("Rows with more than {} cells are not supported", MAX_WIDTH);
         }
-        RockField(field)
+        let row_bits = |kind: Cell| {
+            grid.rows()
+                .map(|row| {
+                    row.iter()
+                        .enumerate()
+                        .filter(|(_, cell)| **cell == kind)
+                        .fold(0, |bits, (x, _)| bits | 1 << x)
+                })
+                .collect()
+        };
+        Ok(RockField {
+            round: row_bits(Cell::Round),
+            cubes: row_bits(Cell::Cube),
+            width: grid.width(),
+        })
     }
 
-    fn mirror_x(&self) -> Self {
-        let mut field = Vec::new();
-        for i in 0..self.0.len() {
-            let mut row = Vec::new();
-            for j in (0..self.0[i].len()).rev() {
-                row.push(self.0[i][j]);
+    fn to_grid(&self) -> Grid<Cell> {
+        let mut grid = Grid::new(self.width, self.round.len(), Cell::Empty);
+        for (y, (round, cubes)) in self.round.iter().zip(&self.cubes).enumerate() {
+            for x in 0..self.width {
+                if round & (1 << x) != 0 {
+                    grid[(y, x)] = Cell::Round;
+                } else if cubes & (1 << x) != 0 {
+                    grid[(y, x)] = Cell::Cube;
+                }
             }
-            field.push(row);
         }
-        RockField(field)
+        grid
     }
 
-    fn rotate_right(&self) -> Self {
-        self.transpose().mirror_x()
+    /// Tilts the field so all round rocks roll as far as they can in the given direction.
+    fn push_rocks(&mut self, direction: Direction) {
+        match direction {
+            Direction::Up | Direction::Down => self.push_rocks_vertically(direction),
+            Direction::Left | Direction::Right => self.push_rocks_horizontally(direction),
+        }
     }
 
-    fn push_rocks_east(&mut self) {
-        for y in 0..self.0.len() {
-            for x in (0..self.0[y].len()).rev() {
-                if self.0[y][x] == FieldState::RoundRock {
-                    let delta = self.0[y][x + 1..]
-                        .iter()
-                        .enumerate()
-                        .filter(|(_, state)| **state != FieldState::Empty)
-                        .next()
-                        .map(|(delta, _)| delta)
-                        .unwrap_or(self.0[y].len() - 1 - x);
-                    self.0[y][x] = FieldState::Empty;
-                    self.0[y][x + delta] = FieldState::RoundRock;
-                }
+    /// Settles one row after the other, starting at the edge the rocks roll towards. The rocks
+    /// of each row move on together as long as the cells in the next row are empty.
+    fn push_rocks_vertically(&mut self, direction: Direction) {
+        let height = self.round.len();
+        let towards = |row: usize| match direction {
+            Direction::Up => row.checked_sub(1),
+            _ => (row + 1 < height).then_some(row + 1),
+        };
+        let rows: Box<dyn Iterator<Item = usize>> = match direction {
+            Direction::Up => Box::new(0..height),
+            _ => Box::new((0..height).rev()),
+        };
+        for start in rows {
+            let mut moving = self.round[start];
+            let mut row = start;
+            while let Some(next) = towards(row).filter(|_| moving != 0) {
+                moving &= !(self.round[next] | self.cubes[next]);
+                self.round[row] &= !moving;
+                self.round[next] |= moving;
+                row = next;
             }
         }
     }
 
-    fn count_east_load(&self) -> usize {
-        self.0
+    /// The cube rocks split each row into segments, whose round rocks all end up at one end.
+    fn push_rocks_horizontally(&mut self, direction: Direction) {
+        for (round, cubes) in self.round.iter_mut().zip(&self.cubes) {
+            let mut start = 0;
+            while start < self.width {
+                let end = (start + (cubes >> start).trailing_zeros() as usize).min(self.width);
+                let segment = bits(start, end);
+                let count = (*round & segment).count_ones() as usize;
+                *round &= !segment;
+                *round |= match direction {
+                    Direction::Left => bits(start, start + count),
+                    _ => bits(end - count, end),
+                };
+                start = end + 1;
+            }
+        }
+    }
+
+    fn count_north_load(&self) -> usize {
+        let height = self.round.len();
+        self.round
             .iter()
-            .map(|row| {
-                row.iter()
-                    .enumerate()
-                    .map(|(idx, state)| match state {
-                        FieldState::RoundRock => idx + 1,
-                        _ => 0,
-                    })
-                    .sum::<usize>()
-            })
+            .enumerate()
+            .map(|(y, row)| row.count_ones() as usize * (height - y))
             .sum()
     }
 
-    fn cycle(&self) -> Self {
-        let mut res = self.clone();
-        // Assuming the original north is currently pointing east, we just push and rotate 4 times.
-        // That way we don't need separate logic for pushing in all 4 directions.
-        // The downside is that this will do a lot of clones because the rotation does not happen
-        // inplace.
-        res.push_rocks_east();
-        res = res.rotate_right();
-        res.push_rocks_east();
-        res = res.rotate_right();
-        res.push_rocks_east();
-        res = res.rotate_right();
-        res.push_rocks_east();
-        res = res.rotate_right();
-
-        res
+    /// Tilts in each direction of the sequence in turn
+    fn cycle(&mut self, sequence: &[Direction]) {
+        for direction in sequence {
+            self.push_rocks(*direction);
+        }
     }
 }
 
-fn part1<P: AsRef<Path>>(input: P) -> Result<usize> {
-    // We change the coordinate system by doing a 90 degree rotation on the input.
-    // That way we can push east instead of north, which made the implementation simpler.
-    let mut field: RockField =
-        RockField::from(read_lines(input)?.map(|l| l.unwrap())).rotate_right();
+/// North, west, south and east
+const SPIN_CYCLE: [Direction; 4] = [
+    Direction::Up,
+    Direction::Left,
+    Direction::Down,
+    Direction::Right,
+];
+const SPIN_COUNT: usize = 1_000_000_000;
 
-    field.push_rocks_east();
-    Ok(field.count_east_load())
+/// Reads a tilt sequence like `NWSE`, commas between the directions are allowed
+fn parse_sequence(sequence: &str) -> Result<Vec<Direction>> {
+    sequence
+        .chars()
+        .filter(|c| *c != ',')
+        .map(|c| match c.to_ascii_uppercase() {
+            'N' => Ok(Direction::Up),
+            'E' => Ok(Direction::Right),
+            'S' => Ok(Direction::Down),
+            'W' => Ok(Direction::Left),
+            _ => bail!("Invalid direction {:?} in tilt sequence {:?}", c, sequence),
+        })
+        .collect()
 }
 
-fn part2<P: AsRef<Path>>(input: P) -> Result<usize> {
-    let mut field: RockField =
-        RockField::from(read_lines(input)?.map(|l| l.unwrap())).rotate_right();
-    let limit = 1000000000;
+/// Runs `count` cycles of the tilt `sequence`.
+///
+/// The states repeat after a while, so this only simulates until the first repetition and then
+/// skips as many whole loops as fit into the remaining cycles.
+fn simulate(field: &mut RockField, sequence: &[Direction], count: usize) {
+    // The cube rocks never move, so the round ones are enough to tell the states apart
+    skip_loops(
+        field,
+        count,
+        |field| field.round.clone(),
+        |field| field.cycle(sequence),
+    );
+}
 
+/// Applies `cycle` `count` times, skipping whole loops once a state repeats. States are
+/// compared by their `key`.
+fn skip_loops<F, K, C>(field: &mut F, count: usize, key: impl Fn(&F) -> K, mut cycle: C)
+where
+    K: Eq + Hash,
+    C: FnMut(&mut F),
+{
     // We keep a state history to identify any loops in the cycles
-    let mut state_history = HashMap::<RockField, usize>::new();
-    for idx in 0..limit {
-        if let Some(prev) = state_history.get(&field) {
+    let mut state_history = HashMap::<K, usize>::new();
+    for idx in 0..count {
+        if let Some(prev) = state_history.get(&key(field)) {
             // Found the loop, now we can do a little time travel :-)
             let loop_length = idx - prev;
-            let remaining_cycles = limit - idx;
+            let remaining_cycles = count - idx;
 
             // Figure out our target location
             let shortcut_target = idx + (remaining_cycles / loop_length) * loop_length;
             // ... Aaand jump.
-            for _ in shortcut_target..limit {
+            for _ in shortcut_target..count {
                 // We can't go all the way to the limit, so "walk" the remaining steps
-                field = field.cycle();
+                cycle(field);
             }
             // We travelled to the end of the requested cycle count, so break the outer loop.
-            break;
+            return;
         } else {
-            state_history.insert(field.clone(), idx);
+            state_history.insert(key(field), idx);
         }
-        field = field.cycle();
+        cycle(field);
     }
+}
 
-    Ok(field.count_east_load())
+/// Rotates the grid by 90 degrees clockwise, so its north ends up pointing east
+fn rotate_right(grid: &Grid<Cell>) -> Grid<Cell> {
+    let mut rotated = Grid::new(grid.height(), grid.width(), Cell::Empty);
+    for (y, x, cell) in grid.iter() {
+        rotated[(x, grid.height() - 1 - y)] = *cell;
+    }
+    rotated
 }
 
-fn main() -> Result<()> {
-    println!("Answer for part 1: {}", part1(INPUT)?);
-    println!("Answer for part 2: {}", part2(INPUT)?);
+fn push_rocks_east(grid: &mut Grid<Cell>) {
+    for y in 0..grid.height() {
+        let mut free = grid.width();
+        for x in (0..grid.width()).rev() {
+            match grid[(y, x)] {
+                Cell::Cube => free = x,
+                Cell::Round => {
+                    free -= 1;
+                    grid[(y, x)] = Cell::Empty;
+                    grid[(y, free)] = Cell::Round;
+                }
+                Cell::Empty => {}
+            }
+        }
+    }
+}
+
+fn count_east_load(grid: &Grid<Cell>) -> usize {
+    grid.iter()
+        .filter(|(_, _, cell)| **cell == Cell::Round)
+        .map(|(_, x, _)| x + 1)
+        .sum()
+}
+
+/// One spin cycle on a grid whose north points east. We just push east and rotate 4 times, so
+/// we don't need separate logic for pushing in all 4 directions. Every rotation allocates a new
+/// grid though, which is what [`RockField::push_rocks`] avoids.
+fn rotate_cycle(grid: &mut Grid<Cell>) {
+    for _ in 0..4 {
+        push_rocks_east(grid);
+        *grid = rotate_right(grid);
+    }
+}
+
+const ROUND_COLOR: &str = "#f28e2b";
+const CUBE_COLOR: &str = "#4e79a7";
+const DEFAULT_ANIMATED_CYCLES: usize = 3;
+const FRAME_DELAY: Duration = Duration::from_millis(200);
+const GIF_SCALE: u32 = 4;
 
-    Ok(())
+/// Records the field before the first tilt and after every tilt of the first `cycles` cycles of
+/// the `sequence`, one animation frame each. The cube rocks never move, so they are drawn once
+/// in the background.
+fn animate_spin_cycles(
+    field: &RockField,
+    sequence: &[Direction],
+    cycles: usize,
+) -> GridRenderer<usize> {
+    let color = |kind: Cell, color: &'static str| {
+        move |cell: &Cell| (*cell == kind).then(|| color.to_owned())
+    };
+    let mut renderer = GridRenderer::from_grid(&field.to_grid(), color(Cell::Cube, CUBE_COLOR));
+    renderer.add_legend(&[(ROUND_COLOR, "round rock"), (CUBE_COLOR, "cube rock")]);
+
+    let mut field = field.clone();
+    let mut record = |field: &RockField| {
+        renderer.start_frame();
+        renderer.add_grid(&field.to_grid(), color(Cell::Round, ROUND_COLOR));
+        renderer.end_frame();
+    };
+    record(&field);
+    for _ in 0..cycles {
+        for direction in sequence {
+            field.push_rocks(*direction);
+            record(&field);
+        }
+    }
+    renderer
+}
+
+fn read_field<P: AsRef<Path>>(input: P) -> Result<RockField> {
+    let lines = read_lines(input)?.collect::<Result<Vec<_>, _>>()?;
+    RockField::from_grid(&Grid::parse(lines, Cell::parse)?)
+}
+
+fn part1<P: AsRef<Path>>(input: P) -> Result<usize> {
+    let mut field = timed!("parse", read_field(input))?;
+
+    field.push_rocks(Direction::Up);
+    Ok(field.count_north_load())
+}
+
+/// North load after `count` cycles of the tilt `sequence`, the puzzle asks for a billion spin
+/// cycles
+fn part2<P: AsRef<Path>>(input: P, sequence: &[Direction], count: usize) -> Result<usize> {
+    let mut field = timed!("parse", read_field(input))?;
+    simulate(&mut field, sequence, count);
+    Ok(field.count_north_load())
+}
+
+/// Like [`part2`] for the spin cycle, but with the field rotated so the rocks always roll east
+fn part2_rotate<P: AsRef<Path>>(input: P, count: usize) -> Result<usize> {
+    let lines = read_lines(input)?.collect::<Result<Vec<_>, _>>()?;
+    // We change the coordinate system by doing a 90 degree rotation on the input.
+    // That way we can push east instead of north.
+    let mut grid = rotate_right(&timed!("parse", Grid::parse(lines, Cell::parse))?);
+    skip_loops(&mut grid, count, Grid::clone, rotate_cycle);
+    Ok(count_east_load(&grid))
+}
+
+struct Day14;
+
+impl AocDay for Day14 {
+    const YEAR: u16 = 2023;
+    const DAY: u8 = 14;
+
+    fn part1(input: &Path) -> Result<Answer> {
+        Ok(part1(input)?.into())
+    }
+
+    /// `--cycles N` runs N cycles instead of a billion, `--sequence DIRECTIONS` tilts in
+    /// another order than `NWSE` in each cycle, see [`parse_sequence`].
+    ///
+    /// `--animate PATH` additionally stores an animation of the first `--animate-cycles K`
+    /// cycles (3 by default) as a GIF if the path ends in `.gif` and an SVG otherwise, see
+    /// [`animate_spin_cycles`].
+    fn part2(input: &Path) -> Result<Answer> {
+        let sequence = match options().get::<String>("sequence")? {
+            Some(sequence) => parse_sequence(&sequence)?,
+            None => SPIN_CYCLE.to_vec(),
+        };
+        let count = options().get("cycles")?.unwrap_or(SPIN_COUNT);
+        if let Some(path) = options().get::<PathBuf>("animate")? {
+            let cycles = options()
+                .get("animate-cycles")?
+                .unwrap_or(DEFAULT_ANIMATED_CYCLES);
+            let renderer = animate_spin_cycles(&read_field(input)?, &sequence, cycles);
+            if path.extension().is_some_and(|ext| ext == "gif") {
+                renderer.store_gif(path, GIF_SCALE, FRAME_DELAY)?;
+            } else {
+                renderer.store_animated_svg(path, FRAME_DELAY)?;
+            }
+        }
+        Ok(part2(input, &sequence, count)?.into())
+    }
+
+    /// `--algo rotate` rotates the whole field between the tilts instead of tilting in place, it
+    /// only supports `--cycles` and the default sequence.
+    fn part2_alternatives() -> Vec<(&'static str, Solver)> {
+        vec![("rotate", |input| {
+            if options().get::<String>("sequence")?.is_some() {
+                bail!("--algo rotate only supports the default tilt sequence");
+            }
+            let count = options().get("cycles")?.unwrap_or(SPIN_COUNT);
+            Ok(part2_rotate(input, count)?.into())
+        })]
+    }
+}
+
+fn main() -> Result<()> {
+    run::<Day14>()
 }
 
 #[cfg(test)]
@@ -196,7 +414,105 @@ mod tests_day14 {
             None,
         );
         assert_eq!(part1(&file).unwrap(), 136);
-        assert_eq!(part2(&file).unwrap(), 64);
+        assert_eq!(part2(&file, &SPIN_CYCLE, SPIN_COUNT).unwrap(), 64);
+        // The loads after the first cycles of the puzzle description
+        assert_eq!(part2(&file, &SPIN_CYCLE, 1).unwrap(), 87);
+        assert_eq!(part2(&file, &SPIN_CYCLE, 3).unwrap(), 69);
+        assert_eq!(part2(&file, &[], SPIN_COUNT).unwrap(), 104);
+        // Tilting north any number of times is part 1
+        let north = parse_sequence("N").unwrap();
+        assert_eq!(part2(&file, &north, 5).unwrap(), 136);
+        for count in [1, 3, SPIN_COUNT] {
+            assert_eq!(
+                part2_rotate(&file, count).unwrap(),
+                part2(&file, &SPIN_CYCLE, count).unwrap()
+            );
+        }
         drop(dir);
     }
+
+    fn field(rows: &str) -> RockField {
+        RockField::from_grid(&Grid::parse(rows.lines(), Cell::parse).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_push_rocks() {
+        let start = indoc! {"
+            O.#
+            .O.
+            ..O
+        "};
+        let cases = [
+            (Direction::Up, "OO#\n..O\n...\n"),
+            (Direction::Down, "..#\n...\nOOO\n"),
+            (Direction::Left, "O.#\nO..\nO..\n"),
+            (Direction::Right, ".O#\n..O\n..O\n"),
+        ];
+        for (direction, expected) in cases {
+            let mut tilted = field(start);
+            tilted.push_rocks(direction);
+            assert_eq!(tilted, field(expected), "{:?}", direction);
+        }
+    }
+
+    #[test]
+    fn test_push_rocks_segments() {
+        let mut tilted = field("O.O#..O.#.OO.#O");
+        tilted.push_rocks(Direction::Left);
+        assert_eq!(tilted, field("OO.#O...#OO..#O"));
+        tilted.push_rocks(Direction::Right);
+        assert_eq!(tilted, field(".OO#...O#..OO#O"));
+
+        let mut wide = field(&"O.".repeat(64));
+        wide.push_rocks(Direction::Right);
+        assert_eq!(wide, field(&(".".repeat(64) + &"O".repeat(64))));
+
+        let too_wide = Grid::parse([".".repeat(MAX_WIDTH + 1)], Cell::parse).unwrap();
+        assert!(RockField::from_grid(&too_wide).is_err());
+    }
+
+    #[test]
+    fn test_simulate() {
+        let start = field(indoc! {"
+            O.#
+            .O.
+            ..O
+        "});
+        let sequence = parse_sequence("nwse").unwrap();
+        assert_eq!(sequence, SPIN_CYCLE);
+        for count in [0, 1, 2, 5, 10, 1000, SPIN_COUNT] {
+            let mut simulated = start.clone();
+            simulate(&mut simulated, &sequence, count);
+            let mut walked = start.clone();
+            for _ in 0..count.min(1000) {
+                walked.cycle(&sequence);
+            }
+            assert_eq!(simulated, walked, "{}", count);
+        }
+        assert_eq!(
+            parse_sequence("NX").unwrap_err().to_string(),
+            "Invalid direction 'X' in tilt sequence \"NX\""
+        );
+    }
+
+    #[test]
+    fn test_animate_spin_cycles() {
+        let start = field(indoc! {"
+            O.#
+            .O.
+            ..O
+        "});
+        assert_eq!(RockField::from_grid(&start.to_grid()).unwrap(), start);
+        let renderer = animate_spin_cycles(&start, &SPIN_CYCLE, 2);
+        assert_eq!(renderer.frame_count(), 1 + 2 * 4);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("spin.svg");
+        renderer.store_animated_svg(&path, FRAME_DELAY).unwrap();
+        let svg = std::fs::read_to_string(&path).unwrap();
+        let count = |color: &str| svg.matches(&format!(r#"fill="{}""#, color)).count();
+        // One extra swatch per color in the legend
+        assert_eq!(count(ROUND_COLOR), 3 * 9 + 1);
+        assert_eq!(count(CUBE_COLOR), 1 + 1);
+    }
 }
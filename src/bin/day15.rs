@@ -1,23 +1,52 @@
-use std::{path::Path, str::FromStr};
-
-use advent_of_code_2023::read_lines;
-use anyhow::{anyhow, bail, Result};
-
-const INPUT: &str = "input/day15.txt";
-
-fn hash(val: &str) -> usize {
-    val.bytes()
-        .fold(0, |acc, v| ((acc + v as usize) * 17) % 256)
+use std::{
+    fs::File,
+    io::{BufRead, BufReader},
+    path::Path,
+    str::FromStr,
+};
+
+use advent_of_code_2023::{
+    answer::Answer,
+    holiday_hash::{hash, LensBoxes},
+    runner::{run, AocDay},
+};
+use anyhow::{Context, Result};
+use thiserror::Error;
+
+/// Reads the comma-separated steps of the initialization sequence one after the other, without
+/// reading the whole input at once. All whitespace is dropped, so the sequence may be wrapped over
+/// several lines, and empty steps (e.g. from a trailing newline or blank lines) are skipped.
+fn read_steps<P: AsRef<Path>>(input: P) -> Result<impl Iterator<Item = Result<String>>> {
+    let reader = BufReader::new(File::open(input)?);
+    Ok(reader
+        .split(b',')
+        .map(|step| -> Result<Vec<u8>> {
+            Ok(step?
+                .into_iter()
+                .filter(|b| !b.is_ascii_whitespace())
+                .collect())
+        })
+        .filter(|step| !matches!(step, Ok(step) if step.is_empty()))
+        .enumerate()
+        .map(|(idx, step)| {
+            String::from_utf8(step?).with_context(|| format!("Invalid step {}", idx + 1))
+        }))
 }
 
 fn part1<P: AsRef<Path>>(input: P) -> Result<usize> {
-    Ok(read_lines(input)?
-        .next()
-        .unwrap()
-        .unwrap()
-        .split(',')
-        .map(|s| hash(s))
-        .sum())
+    let mut sum = 0;
+    for step in read_steps(input)? {
+        sum += hash(&step?);
+    }
+    Ok(sum)
+}
+
+#[derive(Error, Debug, PartialEq, Eq)]
+enum CommandError {
+    #[error("Expected '=' or '-' after the label")]
+    MissingOperation,
+    #[error("Invalid focal length {0:?}")]
+    InvalidFocalLength(String),
 }
 
 #[derive(Debug, Clone)]
@@ -27,104 +56,60 @@ enum Command {
 }
 
 impl FromStr for Command {
-    type Err = anyhow::Error;
+    type Err = CommandError;
 
-    fn from_str(s: &str) -> Result<Self> {
-        Ok(if s.ends_with("-") {
-            Self::TakeLens(s[0..s.len() - 1].to_owned())
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(if let Some(label) = s.strip_suffix('-') {
+            Self::TakeLens(label.to_owned())
         } else if let Some((label, focal_length)) = s.split_once('=') {
             Self::PutLens(
                 label.to_owned(),
                 focal_length
                     .parse()
-                    .map_err(|_| anyhow!("Unexpected focal length {}", focal_length))?,
+                    .map_err(|_| CommandError::InvalidFocalLength(focal_length.to_owned()))?,
             )
         } else {
-            bail!("Unexpected command");
+            return Err(CommandError::MissingOperation);
         })
     }
 }
 
-#[derive(Default, Debug, Clone)]
-struct Box(Vec<(String, usize)>);
-
-#[derive(Debug)]
-struct State([Box; 256]);
-
-impl Default for State {
-    fn default() -> Self {
-        Self(std::array::from_fn(|_| Box::default()))
-    }
-}
-
-impl State {
-    fn execute_command(&mut self, command: Command) {
-        match command {
-            Command::PutLens(label, focal_length) => {
-                let box_number = hash(&label);
-                let lens_list = &mut self.0[box_number].0;
-                let inserted = lens_list.iter_mut().any(|v| {
-                    if v.0 == label {
-                        v.1 = focal_length;
-                        true
-                    } else {
-                        false
-                    }
-                });
-                if !inserted {
-                    self.0[box_number].0.push((label, focal_length));
-                }
-            }
+fn part2<P: AsRef<Path>>(input: P) -> Result<usize> {
+    let mut boxes = LensBoxes::default();
+
+    for (idx, step) in read_steps(input)?.enumerate() {
+        let step = step?;
+        let cmd = step
+            .parse::<Command>()
+            .with_context(|| format!("Invalid step {} {:?}", idx + 1, step))?;
+        match cmd {
+            Command::PutLens(label, focal_length) => boxes.insert(&label, focal_length),
             Command::TakeLens(label) => {
-                let box_number = hash(&label);
-                let lens_list = &mut self.0[box_number].0;
-                if let Some(to_remove) = lens_list
-                    .iter()
-                    .enumerate()
-                    .find(|(_, (lens_label, _))| lens_label == &label)
-                {
-                    lens_list.remove(to_remove.0);
-                }
+                boxes.remove(&label);
             }
         }
     }
+
+    Ok(boxes.focusing_power())
 }
 
-fn part2<P: AsRef<Path>>(input: P) -> Result<usize> {
-    let mut state = State::default();
-
-    for cmd in read_lines(input)?
-        .next()
-        .unwrap()
-        .unwrap()
-        .split(',')
-        .map(|c| c.parse::<Command>().unwrap())
-    {
-        state.execute_command(cmd);
+struct Day15;
+
+impl AocDay for Day15 {
+    const YEAR: u16 = 2023;
+    const DAY: u8 = 15;
+
+    fn part1(input: &Path) -> Result<Answer> {
+        Ok(part1(input)?.into())
     }
 
-    Ok(state
-        .0
-        .iter()
-        .enumerate()
-        .map(|(box_idx, box_content)| {
-            box_content
-                .0
-                .iter()
-                .enumerate()
-                .map(|(slot_index, (_, focal_length))| {
-                    (box_idx + 1) * (slot_index + 1) * focal_length
-                })
-                .sum::<usize>()
-        })
-        .sum())
+    fn part2(input: &Path) -> Result<Answer> {
+        Ok(part2(input)?.into())
+    }
 }
 
 fn main() -> Result<()> {
-    println!("Answer for part 1: {}", part1(INPUT)?);
-    println!("Answer for part 2: {}", part2(INPUT)?);
-
-    Ok(())
+    run::<Day15>()
 }
 
 #[cfg(test)]
@@ -145,4 +130,46 @@ mod tests_day15 {
         assert_eq!(part2(&file).unwrap(), 145);
         drop(dir);
     }
+
+    #[test]
+    fn test_invalid_steps() {
+        let (dir, file) = create_example_file("rn=1,cm-,qp=x,cm=2\n", None);
+        assert_eq!(
+            format!("{:#}", part2(&file).unwrap_err()),
+            "Invalid step 3 \"qp=x\": Invalid focal length \"x\""
+        );
+        let (dir2, file) = create_example_file("rn=1,cm-,qp=3,cm\n", None);
+        assert_eq!(
+            format!("{:#}", part2(&file).unwrap_err()),
+            "Invalid step 4 \"cm\": Expected '=' or '-' after the label"
+        );
+        drop(dir);
+        drop(dir2);
+    }
+
+    #[test]
+    fn test_steps_across_lines() {
+        let (dir, file) = create_example_file(
+            indoc! {"
+            rn=1,cm-,qp=3,cm=2,
+            qp-,pc=4,ot=9,ab
+            =5,pc-,pc=6,ot=7
+        "},
+            None,
+        );
+        assert_eq!(part1(&file).unwrap(), 1320);
+        assert_eq!(part2(&file).unwrap(), 145);
+        drop(dir);
+    }
+
+    #[test]
+    fn test_wrapped_steps() {
+        let (dir, file) = create_example_file(
+            "\n  rn=1,cm-,qp=3,\n\ncm=2 ,qp-,pc=4,ot=9,\tab=5,pc-,pc=6,ot=7,\n\n",
+            None,
+        );
+        assert_eq!(part1(&file).unwrap(), 1320);
+        assert_eq!(part2(&file).unwrap(), 145);
+        drop(dir);
+    }
 }
@@ -1,251 +1,593 @@
 use std::{
     collections::{HashMap, HashSet},
-    path::Path,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
 };
 
-use advent_of_code_2023::read_lines;
-use anyhow::Result;
+use advent_of_code_2023::{
+    answer::Answer,
+    checkpoint::Checkpointer,
+    grid::{Direction, Grid},
+    read_lines,
+    render_grid::GridRenderer,
+    runner::{options, run, AocDay, Solver},
+    timed,
+};
+use anyhow::{bail, Result};
+use petgraph::{algo::tarjan_scc, graphmap::DiGraphMap};
 
-const INPUT: &str = "input/day16.txt";
+/// How an optical element redirects beams, e.g. a mirror or a splitter
+trait TileBehavior {
+    /// Directions the beam leaves the tile in when it enters moving in `dir`, none of them if
+    /// the tile absorbs it
+    fn outputs(&self, dir: Direction) -> Vec<Direction>;
+}
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum Tile {
-    VSplitter, // |
-    HSplitter, // -
-    LUMirror,  // /
-    LDMirror,  // \
+/// Lets beams along its axis pass and splits beams hitting it from the side into both directions
+/// along the axis
+struct Splitter {
+    vertical: bool,
 }
 
-impl Tile {
-    fn optional_from(c: char) -> Option<Self> {
-        Some(match c {
-            '|' => Self::VSplitter,
-            '-' => Self::HSplitter,
-            '/' => Self::LUMirror,
-            '\\' => Self::LDMirror,
-            _ => return None,
-        })
+impl TileBehavior for Splitter {
+    fn outputs(&self, dir: Direction) -> Vec<Direction> {
+        if dir.is_vertical() == self.vertical {
+            vec![dir]
+        } else if self.vertical {
+            vec![Direction::Up, Direction::Down]
+        } else {
+            vec![Direction::Left, Direction::Right]
+        }
     }
 }
 
-#[derive(Debug)]
-struct Field(HashMap<(usize, usize), Tile>);
-
-impl Field {
-    fn from_input<P: AsRef<Path>>(input: P) -> Result<Self> {
-        Ok(Field(
-            read_lines(input)?
-                .enumerate()
-                .flat_map(|(y, l)| {
-                    l.unwrap()
-                        .chars()
-                        .enumerate()
-                        .filter_map(|(x, c)| Tile::optional_from(c).map(|t| ((x, y), t)))
-                        .collect::<Vec<_>>()
-                })
-                .collect(),
-        ))
-    }
+/// Reflects each incoming direction into a single outgoing one
+struct Mirror(fn(Direction) -> Direction);
 
-    fn direct_beam(&self, beam: &Beam) -> Vec<Beam> {
-        if let Some(tile) = self.0.get(&beam.pos) {
-            match tile {
-                Tile::VSplitter => {
-                    if beam.is_horizontal() {
-                        [
-                            beam.with_dir(BeamDir::Up).forward(),
-                            beam.with_dir(BeamDir::Down).forward(),
-                        ]
-                        .into_iter()
-                        .filter_map(|b| b)
-                        .collect()
-                    } else {
-                        beam.forward().into_iter().collect()
-                    }
-                }
-                Tile::HSplitter => {
-                    if beam.is_vertical() {
-                        [
-                            beam.with_dir(BeamDir::Left).forward(),
-                            beam.with_dir(BeamDir::Right).forward(),
-                        ]
-                        .into_iter()
-                        .filter_map(|b| b)
-                        .collect()
-                    } else {
-                        beam.forward().into_iter().collect()
-                    }
-                }
-                Tile::LUMirror => match beam.dir {
-                    BeamDir::Right => beam.with_dir(BeamDir::Up).forward().into_iter().collect(),
-                    BeamDir::Left => beam.with_dir(BeamDir::Down).forward().into_iter().collect(),
-                    BeamDir::Up => beam
-                        .with_dir(BeamDir::Right)
-                        .forward()
-                        .into_iter()
-                        .collect(),
-                    BeamDir::Down => beam.with_dir(BeamDir::Left).forward().into_iter().collect(),
-                },
-                Tile::LDMirror => match beam.dir {
-                    BeamDir::Right => beam.with_dir(BeamDir::Down).forward().into_iter().collect(),
-                    BeamDir::Left => beam.with_dir(BeamDir::Up).forward().into_iter().collect(),
-                    BeamDir::Up => beam.with_dir(BeamDir::Left).forward().into_iter().collect(),
-                    BeamDir::Down => beam
-                        .with_dir(BeamDir::Right)
-                        .forward()
-                        .into_iter()
-                        .collect(),
-                },
-            }
-        } else {
-            beam.forward().into_iter().collect()
-        }
+impl TileBehavior for Mirror {
+    fn outputs(&self, dir: Direction) -> Vec<Direction> {
+        vec![self.0(dir)]
     }
+}
+
+/// The optical elements by their symbol in the input
+type TileBehaviors = HashMap<char, Box<dyn TileBehavior>>;
+
+/// The splitters and mirrors of the puzzle
+fn builtin_behaviors() -> TileBehaviors {
+    let mut behaviors = TileBehaviors::new();
+    behaviors.insert('|', Box::new(Splitter { vertical: true }));
+    behaviors.insert('-', Box::new(Splitter { vertical: false }));
+    behaviors.insert(
+        '/',
+        Box::new(Mirror(|dir| match dir {
+            Direction::Right => Direction::Up,
+            Direction::Left => Direction::Down,
+            Direction::Up => Direction::Right,
+            Direction::Down => Direction::Left,
+        })),
+    );
+    behaviors.insert(
+        '\\',
+        Box::new(Mirror(|dir| match dir {
+            Direction::Right => Direction::Down,
+            Direction::Left => Direction::Up,
+            Direction::Up => Direction::Left,
+            Direction::Down => Direction::Right,
+        })),
+    );
+    behaviors
+}
+
+/// An optical element: its symbol and the outputs of its behavior for each incoming direction,
+/// looked up once while parsing so the simulation doesn't have to
+#[derive(Debug)]
+struct TileKind {
+    symbol: char,
+    outputs: [Vec<Direction>; 4],
+}
+
+/// The optical elements of the field, `None` for empty space. Tiles of the same kind share it, so
+/// the cells stay small.
+type Field = Grid<Option<Arc<TileKind>>>;
+
+fn read_field<P: AsRef<Path>>(input: P) -> Result<Field> {
+    read_field_with(input, &builtin_behaviors())
+}
+
+/// Parses the field with the given optical elements, all other symbols are empty space
+fn read_field_with<P: AsRef<Path>>(input: P, behaviors: &TileBehaviors) -> Result<Field> {
+    let mut kinds = HashMap::new();
+    let lines = read_lines(input)?.collect::<Result<Vec<_>, _>>()?;
+    Grid::parse(lines, |c| {
+        let Some(behavior) = behaviors.get(&c) else {
+            return Ok(None);
+        };
+        let kind = kinds.entry(c).or_insert_with(|| {
+            Arc::new(TileKind {
+                symbol: c,
+                outputs: Direction::ALL.map(|dir| behavior.outputs(dir)),
+            })
+        });
+        Ok(Some(kind.clone()))
+    })
+}
 
-    fn dims(&self) -> (usize, usize) {
-        let width = self.0.keys().map(|(x, _)| x + 1).max().unwrap();
-        let height = self.0.keys().map(|(_, y)| y + 1).max().unwrap();
+fn tile_count(field: &Field) -> usize {
+    field.width() * field.height()
+}
+
+/// Index of the position in a flat list of all tiles
+fn tile_index(field: &Field, (y, x): (usize, usize)) -> usize {
+    y * field.width() + x
+}
 
-        (width, height)
+/// Index of the beam's position and direction in a flat list of all beam states
+fn state_index(field: &Field, beam: &Beam) -> usize {
+    tile_index(field, beam.pos) * 4 + beam.dir as usize
+}
+
+/// Directions the beam leaves its tile in
+fn outputs<'a>(field: &'a Field, beam: &'a Beam) -> &'a [Direction] {
+    match &field[beam.pos] {
+        Some(kind) => &kind.outputs[beam.dir as usize],
+        None => std::slice::from_ref(&beam.dir),
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-enum BeamDir {
-    Right,
-    Left,
-    Up,
-    Down,
+/// The beams leaving the beam's tile, except those leaving the field
+fn direct_beam(field: &Field, beam: &Beam) -> Vec<Beam> {
+    let (y, x) = beam.pos;
+    outputs(field, beam)
+        .iter()
+        .filter_map(|&dir| Some(Beam::new(field.step(y, x, dir)?, dir)))
+        .collect()
+}
+
+/// Whether the beam is split in several beams on its tile
+fn splits(field: &Field, beam: &Beam) -> bool {
+    outputs(field, beam).len() > 1
 }
 
+/// A beam on a tile, with its position as `(y, x)`
 #[derive(Debug, Hash, PartialEq, Eq, Clone)]
 struct Beam {
     pos: (usize, usize),
-    dir: BeamDir,
+    dir: Direction,
 }
 
 impl Default for Beam {
     fn default() -> Self {
-        Self {
-            pos: (0, 0),
-            dir: BeamDir::Right,
-        }
+        Self::new((0, 0), Direction::Right)
+    }
+}
+
+/// Parses `X,Y,DIR` with the direction as `right`, `left`, `up` or `down`, e.g. `3,0,down`
+impl FromStr for Beam {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut parts = s.split(',').map(str::trim);
+        let (Some(x), Some(y), Some(dir), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            bail!("Expected X,Y,DIR");
+        };
+        let dir = match dir {
+            "right" => Direction::Right,
+            "left" => Direction::Left,
+            "up" => Direction::Up,
+            "down" => Direction::Down,
+            _ => bail!("Unknown direction {:?}", dir),
+        };
+        Ok(Beam::new((y.parse()?, x.parse()?), dir))
     }
 }
 
 impl Beam {
-    fn new(x: usize, y: usize, dir: BeamDir) -> Self {
-        Self { pos: (x, y), dir }
+    fn new(pos: (usize, usize), dir: Direction) -> Self {
+        Self { pos, dir }
     }
+}
 
-    fn forward(&self) -> Option<Self> {
-        match self.dir {
-            BeamDir::Right => self
-                .pos
-                .0
-                .checked_add(1)
-                .map(|nx| Self::new(nx, self.pos.1, self.dir)),
-            BeamDir::Left => self
-                .pos
-                .0
-                .checked_sub(1)
-                .map(|nx| Self::new(nx, self.pos.1, self.dir)),
-            BeamDir::Up => self
-                .pos
-                .1
-                .checked_sub(1)
-                .map(|ny| Self::new(self.pos.0, ny, self.dir)),
-            BeamDir::Down => self
-                .pos
-                .1
-                .checked_add(1)
-                .map(|ny| Self::new(self.pos.0, ny, self.dir)),
-        }
+/// A fixed number of flags, packed into 64 bit words
+#[derive(Debug, Clone)]
+struct BitSet(Vec<u64>);
+
+impl BitSet {
+    fn new(len: usize) -> Self {
+        Self(vec![0; len.div_ceil(64)])
     }
 
-    fn is_vertical(&self) -> bool {
-        match self.dir {
-            BeamDir::Right | BeamDir::Left => false,
-            BeamDir::Up | BeamDir::Down => true,
-        }
+    /// Sets the flag, returns whether it was unset before
+    fn insert(&mut self, idx: usize) -> bool {
+        let (word, bit) = (idx / 64, 1 << (idx % 64));
+        let unset = self.0[word] & bit == 0;
+        self.0[word] |= bit;
+        unset
     }
 
-    fn is_horizontal(&self) -> bool {
-        !self.is_vertical()
+    fn union_with(&mut self, other: &Self) {
+        for (bits, other_bits) in self.0.iter_mut().zip(&other.0) {
+            *bits |= other_bits;
+        }
     }
 
-    fn with_dir(&self, dir: BeamDir) -> Self {
-        Self::new(self.pos.0, self.pos.1, dir)
+    /// Number of set flags
+    fn len(&self) -> usize {
+        self.0.iter().map(|bits| bits.count_ones() as usize).sum()
     }
 }
 
-fn simulate(field: &Field, initial_beam: Beam) -> usize {
-    let (width, height) = field.dims();
+/// Calls `visit` once for each position and direction the beam passes through.
+fn propagate<F: FnMut(&Beam)>(field: &Field, initial_beam: Beam, mut visit: F) {
     let mut beams = vec![initial_beam];
-    let mut known_beams = HashSet::<Beam>::new();
+    let mut known_beams = BitSet::new(tile_count(field) * 4);
 
-    loop {
-        beams.retain(|b| b.pos.0 < width && b.pos.1 < height);
-        beams.retain(|b| !known_beams.contains(b));
-        if beams.is_empty() {
-            break;
+    while let Some(beam) = beams.pop() {
+        let (y, x) = beam.pos;
+        if !field.contains(y, x) || !known_beams.insert(state_index(field, &beam)) {
+            continue;
         }
-        known_beams.extend(beams.iter().cloned());
-
-        beams = beams
-            .into_iter()
-            .flat_map(|beam| field.direct_beam(&beam))
-            .collect::<Vec<_>>();
+        visit(&beam);
+        beams.extend(direct_beam(field, &beam));
     }
+}
 
-    known_beams
-        .into_iter()
-        .map(|beam| beam.pos)
-        .collect::<HashSet<_>>()
-        .len()
+fn simulate(field: &Field, initial_beam: Beam) -> usize {
+    let mut energized = BitSet::new(tile_count(field));
+    propagate(field, initial_beam, |beam| {
+        energized.insert(tile_index(field, beam.pos));
+    });
+    energized.len()
 }
 
 fn part1<P: AsRef<Path>>(input: P) -> Result<usize> {
-    let field = Field::from_input(input)?;
+    let field = timed!("parse", read_field(input))?;
 
     let energized = simulate(&field, Beam::default());
 
     Ok(energized)
 }
 
-fn part2<P: AsRef<Path>>(input: P) -> Result<usize> {
-    let field = Field::from_input(input)?;
-    let (width, height) = field.dims();
-
-    let xmax = (0..width)
+fn entry_beams(width: usize, height: usize) -> Vec<Beam> {
+    (0..width)
         .flat_map(|x| {
             [
-                Beam::new(x, 0, BeamDir::Down),
-                Beam::new(x, height - 1, BeamDir::Up),
+                Beam::new((0, x), Direction::Down),
+                Beam::new((height - 1, x), Direction::Up),
             ]
         })
-        .map(|initial_beam| simulate(&field, initial_beam))
-        .max()
-        .unwrap();
-    let ymax = (0..height)
-        .flat_map(|y| {
+        .chain((0..height).flat_map(|y| {
             [
-                Beam::new(0, y, BeamDir::Right),
-                Beam::new(width - 1, y, BeamDir::Left),
+                Beam::new((y, 0), Direction::Right),
+                Beam::new((y, width - 1), Direction::Left),
             ]
-        })
-        .map(|initial_beam| simulate(&field, initial_beam))
+        }))
+        .collect()
+}
+
+/// Most tiles energized by any of the entry beams, simulated one after the other starting at the
+/// entry beam `first`, where `best` is the result for the ones before.
+///
+/// The index of the next entry beam and the best result so far are saved as checkpoint after each
+/// simulation.
+#[cfg(not(feature = "parallel"))]
+fn most_energized(
+    field: &Field,
+    entries: &[Beam],
+    (first, best): (usize, usize),
+    checkpointer: &mut Checkpointer,
+) -> Result<usize> {
+    let mut best = best;
+    for (idx, beam) in entries.iter().enumerate().skip(first) {
+        best = best.max(simulate(field, beam.clone()));
+        checkpointer.maybe_save(&(idx + 1, best))?;
+    }
+    Ok(best)
+}
+
+/// Same as the sequential version, but with the simulations spread over all cores since they are
+/// independent of each other.
+///
+/// They don't finish in order, so no checkpoints are saved in between. Checkpoints of a
+/// sequential run can still be resumed.
+#[cfg(feature = "parallel")]
+fn most_energized(
+    field: &Field,
+    entries: &[Beam],
+    (first, best): (usize, usize),
+    _checkpointer: &mut Checkpointer,
+) -> Result<usize> {
+    use rayon::prelude::*;
+    Ok(entries[first.min(entries.len())..]
+        .par_iter()
+        .map(|beam| simulate(field, beam.clone()))
         .max()
-        .unwrap();
+        .map_or(best, |energized| energized.max(best)))
+}
+
+/// Name of the checkpoint for the progress of part 2
+const CHECKPOINT: &str = "2023-day16-part2";
+
+fn part2<P: AsRef<Path>>(input: P, mut checkpointer: Checkpointer) -> Result<usize> {
+    let field = timed!("parse", read_field(input))?;
+    let entries = entry_beams(field.width(), field.height());
 
-    Ok(std::cmp::max(xmax, ymax))
+    // Progress is tracked as the index of the next entry beam to simulate and the best result so
+    // far, which allows resuming an interrupted run.
+    let progress = checkpointer.load::<(usize, usize)>()?.unwrap_or((0, 0));
+    let best = most_energized(&field, &entries, progress, &mut checkpointer)?;
+    checkpointer.finish()?;
+
+    Ok(best)
 }
 
-fn main() -> Result<()> {
-    println!("Answer for part 1: {}", part1(INPUT)?);
-    println!("Answer for part 2: {}", part2(INPUT)?);
+/// Follows a beam as long as it isn't split, energizing the tiles on its way, until it leaves the
+/// field, is absorbed or is split. Returns the state index of the beam where it is split.
+fn trace(field: &Field, beam: Beam, tiles: &mut BitSet) -> Option<usize> {
+    let mut beam = Some(beam);
+    // Without splitting, the beam has at most one successor state. After more steps than there
+    // are states it runs in circles and all its tiles are energized already.
+    for _ in 0..4 * tile_count(field) {
+        let current = beam?;
+        tiles.insert(tile_index(field, current.pos));
+        if splits(field, &current) {
+            return Some(state_index(field, &current));
+        }
+        beam = direct_beam(field, &current).into_iter().next();
+    }
+    None
+}
 
-    Ok(())
+/// The tiles energized by a beam from each state where it is split, e.g. when hitting a splitter
+/// from the side, computed once for all entry beams.
+///
+/// The split states form a graph, with an edge wherever one of the beams leaving a split runs
+/// into another split. Beams can run in circles between splits, so the states are grouped into
+/// strongly connected components first: all states of a component energize the same tiles,
+/// namely those on the way out of any of them plus the tiles of all components reachable from
+/// there.
+struct BeamCoverage {
+    component: HashMap<usize, usize>,
+    tiles: Vec<BitSet>,
+}
+
+impl BeamCoverage {
+    fn new(field: &Field) -> Self {
+        let mut graph = DiGraphMap::<usize, ()>::new();
+        let mut own_tiles = HashMap::new();
+        for (y, x, _) in field.iter().filter(|(_, _, tile)| tile.is_some()) {
+            for dir in Direction::ALL {
+                let split = Beam::new((y, x), dir);
+                if !splits(field, &split) {
+                    continue;
+                }
+                let state = state_index(field, &split);
+                graph.add_node(state);
+                let mut tiles = BitSet::new(tile_count(field));
+                tiles.insert(tile_index(field, split.pos));
+                for beam in direct_beam(field, &split) {
+                    if let Some(next) = trace(field, beam, &mut tiles) {
+                        graph.add_edge(state, next, ());
+                    }
+                }
+                own_tiles.insert(state, tiles);
+            }
+        }
+
+        // The components come in reverse topological order, so the components reachable from
+        // one are done before it
+        let mut component = HashMap::new();
+        let mut coverage = Vec::new();
+        for states in tarjan_scc(&graph) {
+            let mut tiles = BitSet::new(tile_count(field));
+            for state in &states {
+                tiles.union_with(&own_tiles[state]);
+                for next in graph.neighbors(*state) {
+                    if let Some(&idx) = component.get(&next) {
+                        tiles.union_with(&coverage[idx]);
+                    }
+                }
+            }
+            for state in states {
+                component.insert(state, coverage.len());
+            }
+            coverage.push(tiles);
+        }
+
+        Self {
+            component,
+            tiles: coverage,
+        }
+    }
+
+    /// Number of tiles energized by the beam, same as [`simulate`]
+    fn energized(&self, field: &Field, beam: Beam) -> usize {
+        let mut tiles = BitSet::new(tile_count(field));
+        if let Some(split) = trace(field, beam, &mut tiles) {
+            tiles.union_with(&self.tiles[self.component[&split]]);
+        }
+        tiles.len()
+    }
+}
+
+fn part2_memoized<P: AsRef<Path>>(input: P) -> Result<usize> {
+    let field = timed!("parse", read_field(input))?;
+    let coverage = timed!("coverage", BeamCoverage::new(&field));
+    Ok(entry_beams(field.width(), field.height())
+        .into_iter()
+        .map(|beam| coverage.energized(&field, beam))
+        .max()
+        .unwrap_or(0))
+}
+
+/// The optical elements by position, the way the field was stored before it became a dense
+/// [`Grid`]. Kept along with [`simulate_hashed`] to compare against.
+struct HashedField {
+    tiles: HashMap<(usize, usize), Arc<TileKind>>,
+    width: usize,
+    height: usize,
+}
+
+impl HashedField {
+    fn new(field: &Field) -> Self {
+        HashedField {
+            tiles: field
+                .iter()
+                .filter_map(|(y, x, kind)| Some(((y, x), kind.clone()?)))
+                .collect(),
+            width: field.width(),
+            height: field.height(),
+        }
+    }
+
+    fn step(&self, (y, x): (usize, usize), dir: Direction) -> Option<(usize, usize)> {
+        let (dy, dx) = dir.delta();
+        let pos = (y.checked_add_signed(dy)?, x.checked_add_signed(dx)?);
+        (pos.0 < self.height && pos.1 < self.width).then_some(pos)
+    }
+}
+
+/// Same as [`simulate`], but tracking the known beams and the energized tiles in hash sets
+fn simulate_hashed(field: &HashedField, initial_beam: Beam) -> usize {
+    let mut beams = vec![initial_beam];
+    let mut known_beams = HashSet::new();
+    let mut energized = HashSet::new();
+
+    while let Some(beam) = beams.pop() {
+        if !known_beams.insert(beam.clone()) {
+            continue;
+        }
+        energized.insert(beam.pos);
+        let outputs = match field.tiles.get(&beam.pos) {
+            Some(kind) => &kind.outputs[beam.dir as usize][..],
+            None => std::slice::from_ref(&beam.dir),
+        };
+        beams.extend(
+            outputs
+                .iter()
+                .filter_map(|&dir| Some(Beam::new(field.step(beam.pos, dir)?, dir))),
+        );
+    }
+    energized.len()
+}
+
+fn part1_hashed<P: AsRef<Path>>(input: P) -> Result<usize> {
+    let field = timed!(
+        "parse",
+        read_field(input).map(|field| HashedField::new(&field))
+    )?;
+    Ok(simulate_hashed(&field, Beam::default()))
+}
+
+fn part2_hashed<P: AsRef<Path>>(input: P) -> Result<usize> {
+    let field = timed!(
+        "parse",
+        read_field(input).map(|field| HashedField::new(&field))
+    )?;
+    Ok(entry_beams(field.width, field.height)
+        .into_iter()
+        .map(|beam| simulate_hashed(&field, beam))
+        .max()
+        .unwrap_or(0))
+}
+
+const ENERGIZED_COLOR: &str = "#edc948";
+const BEAM_COLOR: &str = "#f28e2b";
+const OPTICS_COLOR: &str = "#4e79a7";
+
+/// Renders the energized tiles for the beam, with arrows for the directions the beam passes
+/// through them and the mirrors and splitters on top
+fn render_beam(field: &Field, initial_beam: Beam) -> GridRenderer<usize> {
+    let mut renderer = GridRenderer::new();
+    renderer.add_colored_rect(
+        0,
+        0,
+        field.height(),
+        field.width(),
+        "white".to_owned(),
+        None,
+    );
+    let mut energized = BitSet::new(tile_count(field));
+    propagate(field, initial_beam, |beam| {
+        let (y, x) = beam.pos;
+        if energized.insert(tile_index(field, beam.pos)) {
+            renderer.set_layer("energized");
+            renderer.add_colored_grid_tile(y, x, ENERGIZED_COLOR.to_owned(), None);
+        }
+        renderer.set_layer("beams");
+        renderer.add_arrow_tile(y, x, beam.dir, BEAM_COLOR.to_owned());
+    });
+    renderer.set_layer("optics");
+    for (y, x, tile) in field.iter() {
+        if let Some(kind) = tile {
+            renderer.add_labeled_tile(y, x, kind.symbol.to_string(), OPTICS_COLOR.to_owned());
+        }
+    }
+    renderer.add_legend(&[
+        (ENERGIZED_COLOR, "energized"),
+        (BEAM_COLOR, "beam direction"),
+        (OPTICS_COLOR, "mirror or splitter"),
+    ]);
+    renderer
+}
+
+/// The entry beam that energizes the most tiles, the first one of them on ties
+fn best_entry_beam(field: &Field) -> Beam {
+    let coverage = BeamCoverage::new(field);
+    entry_beams(field.width(), field.height())
+        .into_iter()
+        .rev()
+        .max_by_key(|beam| coverage.energized(field, beam.clone()))
+        .expect("The field has entry beams")
+}
+
+struct Day16;
+
+impl AocDay for Day16 {
+    const YEAR: u16 = 2023;
+    const DAY: u8 = 16;
+
+    /// `--visualize PATH` additionally stores an SVG of the energized tiles, see
+    /// [`render_beam`]. `--beam X,Y,DIR` renders another beam than the one entering at the top
+    /// left.
+    fn part1(input: &Path) -> Result<Answer> {
+        if let Some(path) = options().get::<PathBuf>("visualize")? {
+            let beam = options().get("beam")?.unwrap_or_default();
+            render_beam(&read_field(input)?, beam).store_svg(path)?;
+        }
+        Ok(part1(input)?.into())
+    }
+
+    /// `--visualize PATH` renders the best entry beam.
+    fn part2(input: &Path) -> Result<Answer> {
+        if let Some(path) = options().get::<PathBuf>("visualize")? {
+            let field = read_field(input)?;
+            render_beam(&field, best_entry_beam(&field)).store_svg(path)?;
+        }
+        Ok(part2(input, Checkpointer::new(CHECKPOINT, input)?)?.into())
+    }
+
+    /// `--algo hashed` stores the field and the visited beams in hash maps and sets instead of
+    /// dense arrays, see [`simulate_hashed`].
+    fn part1_alternatives() -> Vec<(&'static str, Solver)> {
+        vec![("hashed", |input| Ok(part1_hashed(input)?.into()))]
+    }
+
+    /// `--algo memoized` computes the tiles energized from each splitter once and reuses them
+    /// for all entry beams, see [`BeamCoverage`].
+    fn part2_alternatives() -> Vec<(&'static str, Solver)> {
+        vec![
+            ("memoized", |input| Ok(part2_memoized(input)?.into())),
+            ("hashed", |input| Ok(part2_hashed(input)?.into())),
+        ]
+    }
+}
+
+fn main() -> Result<()> {
+    run::<Day16>()
 }
 
 #[cfg(test)]
@@ -272,7 +614,166 @@ mod tests_day16 {
             None,
         );
         assert_eq!(part1(&file).unwrap(), 46);
-        assert_eq!(part2(&file).unwrap(), 51);
+        let checkpointer = Checkpointer::in_dir(dir.path(), CHECKPOINT, file.as_ref()).unwrap();
+        assert_eq!(part2(&file, checkpointer).unwrap(), 51);
+        assert_eq!(part2_memoized(&file).unwrap(), 51);
+        assert_eq!(part1_hashed(&file).unwrap(), 46);
+        assert_eq!(part2_hashed(&file).unwrap(), 51);
+        drop(dir);
+    }
+
+    #[test]
+    fn test_coverage_matches_simulation() {
+        let (dir, file) = create_example_file(
+            indoc! {r"
+            .|...\....
+            |.-.\.....
+            .....|-...
+            ........|.
+            ..........
+            .........\
+            ..../.\\..
+            .-.-/..|..
+            .|....-|.\
+            ..//.|....
+        "},
+            None,
+        );
+        // Splitters feeding each other in a loop, and mirrors sending beams in circles
+        let (dir2, loops) = create_example_file(
+            indoc! {r"
+            ./.-.\.
+            .|...|.
+            .\.-./-
+            ./..\..
+            -\../.|
+        "},
+            None,
+        );
+        for file in [file, loops] {
+            let field = read_field(&file).unwrap();
+            let coverage = BeamCoverage::new(&field);
+            for beam in entry_beams(field.width(), field.height()) {
+                assert_eq!(
+                    coverage.energized(&field, beam.clone()),
+                    simulate(&field, beam.clone()),
+                    "{:?}",
+                    beam
+                );
+            }
+        }
+        drop(dir);
+        drop(dir2);
+    }
+
+    #[test]
+    fn test_empty_border() {
+        // The field ends with an empty column and row, which used to be cut off
+        let (dir, file) = create_example_file(
+            indoc! {r"
+            .\..
+            .-..
+            ....
+        "},
+            None,
+        );
+        assert_eq!(part1(&file).unwrap(), 6);
+        let checkpointer = Checkpointer::in_dir(dir.path(), CHECKPOINT, file.as_ref()).unwrap();
+        assert_eq!(part2(&file, checkpointer).unwrap(), 6);
+        assert_eq!(part2_memoized(&file).unwrap(), 6);
+        assert_eq!(part2_hashed(&file).unwrap(), 6);
+        drop(dir);
+    }
+
+    #[test]
+    fn test_render_beam() {
+        let (dir, file) = create_example_file(
+            indoc! {r"
+            .|...\....
+            |.-.\.....
+            .....|-...
+            ........|.
+            ..........
+            .........\
+            ..../.\\..
+            .-.-/..|..
+            .|....-|.\
+            ..//.|....
+        "},
+            None,
+        );
+        let field = read_field(&file).unwrap();
+        let mut svg = Vec::new();
+        render_beam(&field, Beam::default())
+            .render_svg(&mut svg)
+            .unwrap();
+        let svg = String::from_utf8(svg).unwrap();
+        let count = |color: &str| svg.matches(&format!(r#"fill="{}""#, color)).count();
+        // One extra swatch per color in the legend
+        assert_eq!(count(ENERGIZED_COLOR), 46 + 1);
+        assert_eq!(count(OPTICS_COLOR), 23 + 1);
+
+        let best = best_entry_beam(&field);
+        assert_eq!((best.pos, best.dir), ((0, 3), Direction::Down));
+        assert_eq!(simulate(&field, best), 51);
+        drop(dir);
+    }
+
+    #[test]
+    fn test_parse_beam() {
+        let beam = "3, 0, down".parse::<Beam>().unwrap();
+        assert_eq!((beam.pos, beam.dir), ((0, 3), Direction::Down));
+        assert!("3,0".parse::<Beam>().is_err());
+        assert!("3,0,sideways".parse::<Beam>().is_err());
+    }
+
+    /// Absorbs all beams
+    struct Absorber;
+
+    impl TileBehavior for Absorber {
+        fn outputs(&self, _dir: Direction) -> Vec<Direction> {
+            vec![]
+        }
+    }
+
+    /// One-way mirror: reflects beams moving right upwards, lets all other beams pass
+    struct OneWayMirror;
+
+    impl TileBehavior for OneWayMirror {
+        fn outputs(&self, dir: Direction) -> Vec<Direction> {
+            match dir {
+                Direction::Right => vec![Direction::Up],
+                _ => vec![dir],
+            }
+        }
+    }
+
+    #[test]
+    fn test_custom_behaviors() {
+        let (dir, file) = create_example_file(
+            indoc! {r"
+            ..\.#.
+            ..-.>.
+            ..#...
+        "},
+            None,
+        );
+        let mut behaviors = builtin_behaviors();
+        behaviors.insert('#', Box::new(Absorber));
+        behaviors.insert('>', Box::new(OneWayMirror));
+        let field = read_field_with(&file, &behaviors).unwrap();
+        // Right along the top, down at the mirror, split left and right, the left beam runs out
+        // of the field and the right one is reflected up by the one-way mirror and absorbed
+        assert_eq!(simulate(&field, Beam::default()), 3 + 5 + 1);
+        // Moving up, the beam passes the one-way mirror and is absorbed at the top
+        assert_eq!(simulate(&field, Beam::new((2, 4), Direction::Up)), 3);
+        let coverage = BeamCoverage::new(&field);
+        for beam in entry_beams(field.width(), field.height()) {
+            assert_eq!(
+                coverage.energized(&field, beam.clone()),
+                simulate(&field, beam)
+            );
+        }
         drop(dir);
     }
 }
@@ -1,333 +1,380 @@
 use std::{
-    collections::{BinaryHeap, HashMap, HashSet},
-    path::Path,
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    str::FromStr,
 };
 
-use advent_of_code_2023::read_lines;
-use anyhow::Result;
-
-const INPUT: &str = "input/day17.txt";
-
-struct HeatLossMap(Vec<Vec<u32>>);
-
-impl HeatLossMap {
-    fn from_input<P: AsRef<Path>>(input: P) -> Result<Self> {
-        Ok(Self(
-            read_lines(input)?
-                .map(|l| {
-                    l.unwrap()
-                        .chars()
-                        .map(|c| c.to_digit(10).unwrap())
-                        .collect::<Vec<_>>()
-                })
-                .collect(),
-        ))
-    }
-
-    fn dims(&self) -> (usize, usize) {
-        let height = self.0.len();
-        let width = self.0[0].len();
+use advent_of_code_2023::{
+    answer::Answer,
+    grid::{Direction, Grid},
+    read_lines,
+    render_grid::GridRenderer,
+    runner::{options, run, AocDay, Solver},
+    search::{BucketQueue, HeapQueue, MinQueue},
+    timed,
+};
+use anyhow::{anyhow, bail, Result};
+use itertools::Itertools;
+
+type HeatLossMap = Grid<usize>;
+
+fn read_map<P: AsRef<Path>>(input: P) -> Result<HeatLossMap> {
+    let lines = read_lines(input)?.collect::<Result<Vec<_>, _>>()?;
+    Grid::parse(lines, |c| {
+        c.to_digit(10)
+            .map(|d| d as usize)
+            .ok_or_else(|| anyhow!("Invalid heat loss {:?}", c))
+    })
+}
 
-        (height, width)
-    }
+fn bottom_right(map: &HeatLossMap) -> (usize, usize) {
+    (map.height() - 1, map.width() - 1)
+}
 
-    fn get(&self, coords: &(usize, usize)) -> u32 {
-        self.0[coords.0][coords.1]
+fn check_coords(map: &HeatLossMap, (y, x): (usize, usize)) -> Result<()> {
+    if !map.contains(y, x) {
+        bail!(
+            "({}, {}) is outside of the {}x{} map",
+            y,
+            x,
+            map.height(),
+            map.width()
+        );
     }
+    Ok(())
 }
 
-#[derive(Debug, Clone, Eq, PartialEq, Hash)]
-enum MovementState {
-    Horizontal(usize, bool), // The first attribute is the number of fields we already moved in
-    // this direction. If the bool is true, we are going right, else left.
-    Vertical(usize, bool), // The first attribute is the number of fields we already moved in
-                           // this direction. If the bool is true, we are going down, else right.
+/// How many blocks a crucible has to move in a straight line before it may turn (or stop at the
+/// end), and how many it may move at most before it has to turn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Crucible {
+    min_straight: usize,
+    max_straight: usize,
 }
 
-const MOVEMENT_LIMIT: usize = 3;
-const ULTRA_MOVEMENT_LIMIT: usize = 10;
-const ULTRA_MIN_MOVEMENT: usize = 4;
+const CRUCIBLE: Crucible = Crucible {
+    min_straight: 1,
+    max_straight: 3,
+};
+const ULTRA_CRUCIBLE: Crucible = Crucible {
+    min_straight: 4,
+    max_straight: 10,
+};
+
+impl Crucible {
+    /// The given crucible, unless overridden with `--min-straight N` or `--max-straight N`
+    fn from_options(default: Self) -> Result<Self> {
+        let crucible = Crucible {
+            min_straight: options()
+                .get("min-straight")?
+                .unwrap_or(default.min_straight),
+            max_straight: options()
+                .get("max-straight")?
+                .unwrap_or(default.max_straight),
+        };
+        if crucible.max_straight == 0 || crucible.max_straight < crucible.min_straight {
+            bail!(
+                "Invalid crucible: it has to move at least {} and at most {} blocks straight",
+                crucible.min_straight,
+                crucible.max_straight
+            );
+        }
+        Ok(crucible)
+    }
+}
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
 struct Node {
     coords: (usize, usize),
-    state: MovementState,
+    direction: Direction,
+    /// Number of blocks moved in the current direction
+    straight: usize,
 }
 
 impl Node {
-    fn new(coords: (usize, usize), state: MovementState) -> Self {
-        Self { coords, state }
+    /// The nodes after the first block in each direction from the start
+    fn start(map: &HeatLossMap, coords: (usize, usize)) -> Vec<Self> {
+        Direction::ALL
+            .into_iter()
+            .filter_map(|direction| Self::step(map, coords, direction, 1))
+            .collect()
     }
 
-    fn start() -> Vec<Self> {
-        vec![
-            Self::new((0, 1), MovementState::Horizontal(1, true)),
-            Self::new((1, 0), MovementState::Vertical(1, true)),
-        ]
+    /// Moves one block in the given direction. Returns `None` if that leaves the map.
+    fn step(
+        map: &HeatLossMap,
+        (y, x): (usize, usize),
+        direction: Direction,
+        straight: usize,
+    ) -> Option<Self> {
+        let coords = map.step(y, x, direction)?;
+        Some(Self {
+            coords,
+            direction,
+            straight,
+        })
     }
 
-    fn neighbors(&self, dims: &(usize, usize)) -> Vec<Self> {
-        match self.state {
-            MovementState::Horizontal(c, right) => {
-                let mut result = [
-                    self.coords.0.checked_sub(1).map(|ny| {
-                        Self::new((ny, self.coords.1), MovementState::Vertical(1, false))
-                    }),
-                    self.coords
-                        .0
-                        .checked_add(1)
-                        .and_then(|ny| if ny < dims.0 { Some(ny) } else { None })
-                        .map(|ny| Self::new((ny, self.coords.1), MovementState::Vertical(1, true))),
-                ]
-                .into_iter()
-                .filter_map(|x| x)
-                .collect::<Vec<_>>();
-
-                if c < MOVEMENT_LIMIT {
-                    if right {
-                        result.extend(
-                            self.coords
-                                .1
-                                .checked_add(1)
-                                .and_then(|nx| if nx < dims.1 { Some(nx) } else { None })
-                                .map(|nx| {
-                                    Self::new(
-                                        (self.coords.0, nx),
-                                        MovementState::Horizontal(c + 1, true),
-                                    )
-                                }),
-                        );
-                    } else {
-                        result.extend(self.coords.1.checked_sub(1).map(|nx| {
-                            Self::new((self.coords.0, nx), MovementState::Horizontal(c + 1, false))
-                        }));
-                    }
-                }
-
-                result
-            }
-            MovementState::Vertical(c, down) => {
-                let mut result = [
-                    self.coords.1.checked_sub(1).map(|nx| {
-                        Self::new((self.coords.0, nx), MovementState::Horizontal(1, false))
-                    }),
-                    self.coords
-                        .1
-                        .checked_add(1)
-                        .and_then(|nx| if nx < dims.1 { Some(nx) } else { None })
-                        .map(|nx| {
-                            Self::new((self.coords.0, nx), MovementState::Horizontal(1, true))
-                        }),
-                ]
-                .into_iter()
-                .filter_map(|x| x)
-                .collect::<Vec<_>>();
-
-                if c < MOVEMENT_LIMIT {
-                    if !down {
-                        result.extend(self.coords.0.checked_sub(1).map(|ny| {
-                            Self::new((ny, self.coords.1), MovementState::Vertical(c + 1, down))
-                        }));
-                    } else {
-                        result.extend(
-                            self.coords
-                                .0
-                                .checked_add(1)
-                                .and_then(|ny| if ny < dims.0 { Some(ny) } else { None })
-                                .map(|ny| {
-                                    Self::new(
-                                        (ny, self.coords.1),
-                                        MovementState::Vertical(c + 1, down),
-                                    )
-                                }),
-                        );
-                    }
-                }
-
-                result
-            }
+    /// The nodes reachable with one more block: turning left or right once we moved at least
+    /// `min_straight` blocks, going straight on while we moved less than `max_straight` blocks.
+    fn neighbors(&self, map: &HeatLossMap, min_straight: usize, max_straight: usize) -> Vec<Self> {
+        let mut result = Vec::with_capacity(3);
+        if self.straight >= min_straight {
+            result.extend(Self::step(map, self.coords, self.direction.turn_left(), 1));
+            result.extend(Self::step(map, self.coords, self.direction.turn_right(), 1));
         }
-    }
-
-    fn ultra_neighbors(&self, dims: &(usize, usize)) -> Vec<Self> {
-        match self.state {
-            MovementState::Horizontal(c, right) => {
-                let mut result = [
-                    self.coords.0.checked_sub(1).map(|ny| {
-                        Self::new((ny, self.coords.1), MovementState::Vertical(1, false))
-                    }),
-                    self.coords
-                        .0
-                        .checked_add(1)
-                        .and_then(|ny| if ny < dims.0 { Some(ny) } else { None })
-                        .map(|ny| Self::new((ny, self.coords.1), MovementState::Vertical(1, true))),
-                ]
-                .into_iter()
-                .filter_map(|x| x)
-                .collect::<Vec<_>>();
-
-                if c < ULTRA_MIN_MOVEMENT {
-                    result.clear();
-                }
-
-                if c < ULTRA_MOVEMENT_LIMIT {
-                    if right {
-                        result.extend(
-                            self.coords
-                                .1
-                                .checked_add(1)
-                                .and_then(|nx| if nx < dims.1 { Some(nx) } else { None })
-                                .map(|nx| {
-                                    Self::new(
-                                        (self.coords.0, nx),
-                                        MovementState::Horizontal(c + 1, right),
-                                    )
-                                }),
-                        );
-                    } else {
-                        result.extend(self.coords.1.checked_sub(1).map(|nx| {
-                            Self::new((self.coords.0, nx), MovementState::Horizontal(c + 1, right))
-                        }));
-                    }
-                }
-
-                result
-            }
-            MovementState::Vertical(c, down) => {
-                let mut result = [
-                    self.coords.1.checked_sub(1).map(|nx| {
-                        Self::new((self.coords.0, nx), MovementState::Horizontal(1, false))
-                    }),
-                    self.coords
-                        .1
-                        .checked_add(1)
-                        .and_then(|nx| if nx < dims.1 { Some(nx) } else { None })
-                        .map(|nx| {
-                            Self::new((self.coords.0, nx), MovementState::Horizontal(1, true))
-                        }),
-                ]
-                .into_iter()
-                .filter_map(|x| x)
-                .collect::<Vec<_>>();
-                if c < ULTRA_MIN_MOVEMENT {
-                    result.clear();
-                }
-
-                if c < ULTRA_MOVEMENT_LIMIT {
-                    if !down {
-                        result.extend(self.coords.0.checked_sub(1).map(|ny| {
-                            Self::new((ny, self.coords.1), MovementState::Vertical(c + 1, down))
-                        }));
-                    } else {
-                        result.extend(
-                            self.coords
-                                .0
-                                .checked_add(1)
-                                .and_then(|ny| if ny < dims.0 { Some(ny) } else { None })
-                                .map(|ny| {
-                                    Self::new(
-                                        (ny, self.coords.1),
-                                        MovementState::Vertical(c + 1, down),
-                                    )
-                                }),
-                        );
-                    }
-                }
-
-                result
-            }
+        if self.straight < max_straight {
+            result.extend(Self::step(
+                map,
+                self.coords,
+                self.direction,
+                self.straight + 1,
+            ));
         }
+        result
     }
 }
 
-#[derive(Debug, Clone, Eq, PartialEq)]
-struct NodeEntry(u32, Node);
-
-impl PartialOrd for NodeEntry {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.cmp(other))
-    }
-}
-
-impl Ord for NodeEntry {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        other.0.cmp(&self.0)
-    }
+/// Least heat loss of the nodes reached by Dijkstra, and their predecessors on the way there
+struct Search {
+    distances: HashMap<Node, usize>,
+    prev: HashMap<Node, Node>,
 }
 
-fn find_shortest_path(map: &HeatLossMap, ultra: bool) -> (u32, Vec<(usize, usize)>) {
-    let dims = map.dims();
-    let mut nodes_to_investigate: BinaryHeap<NodeEntry> = Node::start()
-        .into_iter()
-        .map(|node| NodeEntry(map.get(&node.coords), node))
-        .collect();
+/// Runs Dijkstra from the start block until the crucible can stop at the goal, which it returns
+/// the node for, or until all reachable nodes are done if there is no goal.
+fn dijkstra<Q>(
+    map: &HeatLossMap,
+    crucible: Crucible,
+    start: (usize, usize),
+    goal: Option<(usize, usize)>,
+) -> (Search, Option<Node>)
+where
+    Q: MinQueue<Node> + Default,
+{
+    let mut nodes_to_investigate = Q::default();
     let mut visited_nodes = HashSet::<Node>::new();
-    let mut distances = HashMap::<Node, u32>::new();
-    nodes_to_investigate.iter().for_each(|n| {
-        distances.insert(n.1.clone(), n.0);
-    });
+    let mut distances = HashMap::<Node, usize>::new();
+    for node in Node::start(map, start) {
+        distances.insert(node.clone(), map[node.coords]);
+        nodes_to_investigate.push(map[node.coords], node);
+    }
     let mut prev = HashMap::<Node, Node>::new();
 
-    loop {
-        let NodeEntry(cur_heatloss, cur_node) = nodes_to_investigate.pop().unwrap();
-
-        if cur_node.coords.0 == dims.0 - 1 && cur_node.coords.1 == dims.1 - 1 {
-            let path = std::iter::successors(Some(cur_node), |node| prev.get(&node).cloned())
-                .map(|n| n.coords)
-                .collect::<Vec<_>>();
-            return (cur_heatloss, path);
+    while let Some((cur_heatloss, cur_node)) = nodes_to_investigate.pop() {
+        if visited_nodes.contains(&cur_node) {
+            continue;
         }
 
-        let neighbors = if !ultra {
-            cur_node.neighbors(&dims)
-        } else {
-            cur_node.ultra_neighbors(&dims)
-        };
+        if Some(cur_node.coords) == goal && cur_node.straight >= crucible.min_straight {
+            return (Search { distances, prev }, Some(cur_node));
+        }
 
-        let updates: Vec<_> = neighbors
+        let updates: Vec<_> = cur_node
+            .neighbors(map, crucible.min_straight, crucible.max_straight)
             .into_iter()
             .filter(|n| !visited_nodes.contains(n))
-            .map(|n| (cur_heatloss + map.get(&n.coords), n))
+            .map(|n| (cur_heatloss + map[n.coords], n))
             .filter(|(heatloss, node)| distances.get(node).map(|d| heatloss < d).unwrap_or(true))
             .collect();
         for (heatloss, node) in updates {
             distances.insert(node.clone(), heatloss);
             prev.insert(node.clone(), cur_node.clone());
-            nodes_to_investigate.push(NodeEntry(heatloss, node));
+            nodes_to_investigate.push(heatloss, node);
         }
         visited_nodes.insert(cur_node);
     }
+    (Search { distances, prev }, None)
+}
+
+/// Least heat loss for the crucible from the start to the goal block, and the path from the goal
+/// back to the start
+fn find_shortest_path<Q>(
+    map: &HeatLossMap,
+    crucible: Crucible,
+    start: (usize, usize),
+    goal: (usize, usize),
+) -> Result<(usize, Vec<(usize, usize)>)>
+where
+    Q: MinQueue<Node> + Default,
+{
+    check_coords(map, start)?;
+    check_coords(map, goal)?;
+    if start == goal {
+        return Ok((0, vec![start]));
+    }
+    let (search, Some(end)) = dijkstra::<Q>(map, crucible, start, Some(goal)) else {
+        bail!("The crucible cannot reach the end with {:?}", crucible);
+    };
+    let heatloss = search.distances[&end];
+    let path = std::iter::successors(Some(end), |node| search.prev.get(node).cloned())
+        .map(|n| n.coords)
+        .chain(std::iter::once(start))
+        .collect::<Vec<_>>();
+    Ok((heatloss, path))
 }
 
-fn part1<P: AsRef<Path>>(input: P) -> Result<u32> {
-    let map = HeatLossMap::from_input(input)?;
-    let (heatloss, _path) = find_shortest_path(&map, false);
-    // let mut renderer = GridRenderer::new();
-    // for (y, x) in path {
-    //     renderer.add_grid_tile(y, x);
-    // }
-    // renderer.store_svg("debug.svg");
+/// Least heat loss for the crucible from the start to every block. Blocks the crucible can't stop
+/// at are `None`.
+fn heat_loss_map<Q>(
+    map: &HeatLossMap,
+    crucible: Crucible,
+    start: (usize, usize),
+) -> Result<Grid<Option<usize>>>
+where
+    Q: MinQueue<Node> + Default,
+{
+    check_coords(map, start)?;
+    let mut heat_losses = Grid::new(map.width(), map.height(), None);
+    heat_losses[start] = Some(0);
+    let (search, _) = dijkstra::<Q>(map, crucible, start, None);
+    for (node, heatloss) in search.distances {
+        if node.straight < crucible.min_straight || node.coords == start {
+            continue;
+        }
+        let best = &mut heat_losses[node.coords];
+        *best = Some(best.map_or(heatloss, |best: usize| best.min(heatloss)));
+    }
+    Ok(heat_losses)
+}
+
+fn part1<P: AsRef<Path>>(input: P, crucible: Crucible) -> Result<usize> {
+    let map = timed!("parse", read_map(input))?;
+    let (heatloss, _path) =
+        find_shortest_path::<BucketQueue<_>>(&map, crucible, (0, 0), bottom_right(&map))?;
     Ok(heatloss)
 }
 
-fn part2<P: AsRef<Path>>(input: P) -> Result<u32> {
-    let map = HeatLossMap::from_input(input)?;
-    let (heatloss, _path) = find_shortest_path(&map, true);
-    // let mut renderer = GridRenderer::new();
-    // for (y, x) in path {
-    //     renderer.add_grid_tile(y, x);
-    // }
-    // renderer.store_svg("debug2.svg");
+fn part2<P: AsRef<Path>>(input: P, crucible: Crucible) -> Result<usize> {
+    let map = timed!("parse", read_map(input))?;
+    let (heatloss, _path) =
+        find_shortest_path::<BucketQueue<_>>(&map, crucible, (0, 0), bottom_right(&map))?;
     Ok(heatloss)
 }
 
-fn main() -> Result<()> {
-    println!("Answer for part 1: {}", part1(INPUT)?);
-    println!("Answer for part 2: {}", part2(INPUT)?);
+/// Coordinates of a block on the command line, as `Y,X`
+struct Block((usize, usize));
+
+impl FromStr for Block {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let Some((y, x)) = s.split_once(',') else {
+            bail!("Expected Y,X");
+        };
+        Ok(Block((y.trim().parse()?, x.trim().parse()?)))
+    }
+}
+
+/// The heat losses as a table with right-aligned columns, `.` for blocks the crucible can't stop
+/// at
+fn format_heat_losses(heat_losses: &Grid<Option<usize>>) -> String {
+    let cells = heat_losses
+        .rows()
+        .map(|row| {
+            row.iter()
+                .map(|heatloss| heatloss.map_or(".".to_owned(), |h| h.to_string()))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    let width = cells.iter().flatten().map(String::len).max().unwrap_or(0);
+    cells
+        .iter()
+        .map(|row| row.iter().map(|cell| format!("{:>width$}", cell)).join(" "))
+        .join("\n")
+}
 
+/// Prints the least heat loss to each block for `--heat-loss-map`, starting at `--from Y,X` or
+/// the top left block
+fn print_heat_loss_map(input: &Path, crucible: Crucible) -> Result<()> {
+    let start = options()
+        .get::<Block>("from")?
+        .map_or((0, 0), |block| block.0);
+    let map = read_map(input)?;
+    let heat_losses = heat_loss_map::<BucketQueue<_>>(&map, crucible, start)?;
+    println!("{}", format_heat_losses(&heat_losses));
     Ok(())
 }
 
+const PATH_COLOR: &str = "#4e79a7";
+
+/// Renders the heat loss of each block as a heatmap, with the path of least heat loss for the
+/// crucible on top
+fn render_path<P: AsRef<Path>>(input: P, crucible: Crucible) -> Result<GridRenderer<usize>> {
+    let map = read_map(input)?;
+    let (heatloss, path) =
+        find_shortest_path::<BucketQueue<_>>(&map, crucible, (0, 0), bottom_right(&map))?;
+    let mut renderer = GridRenderer::new();
+    renderer.set_heat_range(1.0, 9.0);
+    for (y, x, block) in map.iter() {
+        renderer.add_heat_tile(y, x, *block as f64);
+    }
+    renderer.set_layer("path");
+    renderer.add_path(path, PATH_COLOR.to_owned(), 0.3);
+    renderer.add_legend(&[(PATH_COLOR, &format!("path with heat loss {}", heatloss))]);
+    Ok(renderer)
+}
+
+/// Same as the default parts, but with the binary heap as queue for comparison
+fn solve_with_heap<P: AsRef<Path>>(input: P, crucible: Crucible) -> Result<usize> {
+    let map = timed!("parse", read_map(input))?;
+    let (heatloss, _path) =
+        find_shortest_path::<HeapQueue<_>>(&map, crucible, (0, 0), bottom_right(&map))?;
+    Ok(heatloss)
+}
+
+struct Day17;
+
+impl AocDay for Day17 {
+    const YEAR: u16 = 2023;
+    const DAY: u8 = 17;
+
+    /// `--min-straight N` and `--max-straight N` explore other crucibles, see
+    /// [`Crucible::from_options`].
+    ///
+    /// `--visualize PATH` additionally stores an SVG of the chosen path, see [`render_path`].
+    ///
+    /// `--heat-loss-map` prints the least heat loss to every block, see [`print_heat_loss_map`].
+    fn part1(input: &Path) -> Result<Answer> {
+        let crucible = Crucible::from_options(CRUCIBLE)?;
+        if let Some(path) = options().get::<PathBuf>("visualize")? {
+            render_path(input, crucible)?.store_svg(path)?;
+        }
+        if options().flag("heat-loss-map") {
+            print_heat_loss_map(input, crucible)?;
+        }
+        Ok(part1(input, crucible)?.into())
+    }
+
+    fn part2(input: &Path) -> Result<Answer> {
+        let crucible = Crucible::from_options(ULTRA_CRUCIBLE)?;
+        if let Some(path) = options().get::<PathBuf>("visualize")? {
+            render_path(input, crucible)?.store_svg(path)?;
+        }
+        if options().flag("heat-loss-map") {
+            print_heat_loss_map(input, crucible)?;
+        }
+        Ok(part2(input, crucible)?.into())
+    }
+
+    /// `--algo heap` uses a binary heap instead of the bucket queue.
+    fn part1_alternatives() -> Vec<(&'static str, Solver)> {
+        vec![("heap", |input| {
+            Ok(solve_with_heap(input, Crucible::from_options(CRUCIBLE)?)?.into())
+        })]
+    }
+
+    fn part2_alternatives() -> Vec<(&'static str, Solver)> {
+        vec![("heap", |input| {
+            Ok(solve_with_heap(input, Crucible::from_options(ULTRA_CRUCIBLE)?)?.into())
+        })]
+    }
+}
+
+fn main() -> Result<()> {
+    run::<Day17>()
+}
+
 #[cfg(test)]
 mod tests_day17 {
     use super::*;
@@ -354,8 +401,141 @@ mod tests_day17 {
         "},
             None,
         );
-        assert_eq!(part1(&file).unwrap(), 102);
-        assert_eq!(part2(&file).unwrap(), 94);
+        assert_eq!(part1(&file, CRUCIBLE).unwrap(), 102);
+        assert_eq!(part2(&file, ULTRA_CRUCIBLE).unwrap(), 94);
+        assert_eq!(solve_with_heap(&file, CRUCIBLE).unwrap(), 102);
+        assert_eq!(solve_with_heap(&file, ULTRA_CRUCIBLE).unwrap(), 94);
+        drop(dir);
+    }
+
+    #[test]
+    fn test_min_straight_at_the_end() {
+        let (dir, file) = create_example_file(
+            indoc! {r"
+            111111111111
+            999999999991
+            999999999991
+            999999999991
+            999999999991
+        "},
+            None,
+        );
+        assert_eq!(part2(&file, ULTRA_CRUCIBLE).unwrap(), 71);
         drop(dir);
     }
+
+    #[test]
+    fn test_custom_crucibles() {
+        let (dir, file) = create_example_file(
+            indoc! {r"
+            1111
+            9991
+            9991
+        "},
+            None,
+        );
+        let crucible = |min_straight, max_straight| Crucible {
+            min_straight,
+            max_straight,
+        };
+        assert_eq!(part1(&file, crucible(1, 3)).unwrap(), 5);
+        // Has to go zig-zag through the expensive blocks
+        assert_eq!(part1(&file, crucible(1, 1)).unwrap(), 29);
+        assert_eq!(
+            part1(&file, crucible(4, 4)).unwrap_err().to_string(),
+            "The crucible cannot reach the end with Crucible { min_straight: 4, max_straight: 4 }"
+        );
+        drop(dir);
+    }
+
+    #[test]
+    fn test_render_path() {
+        let (dir, file) = create_example_file(
+            indoc! {r"
+            1111
+            9991
+            9991
+        "},
+            None,
+        );
+        let mut svg = Vec::new();
+        render_path(&file, CRUCIBLE)
+            .unwrap()
+            .render_svg(&mut svg)
+            .unwrap();
+        let svg = String::from_utf8(svg).unwrap();
+        assert_eq!(svg.matches("data-value=").count(), 12);
+        assert!(svg.contains(r#"points="3,2 3,1 3,0 2,0 1,0 0,0""#));
+        assert!(svg.contains("path with heat loss 5"));
+        drop(dir);
+    }
+
+    #[test]
+    fn test_queries() {
+        let (dir, file) = create_example_file(
+            indoc! {r"
+            2413432311323
+            3215453535623
+            3255245654254
+            3446585845452
+            4546657867536
+            1438598798454
+            4457876987766
+            3637877979653
+            4654967986887
+            4564679986453
+            1224686865563
+            2546548887735
+            4322674655533
+        "},
+            None,
+        );
+        let map = read_map(&file).unwrap();
+        let center = (6, 6);
+        let bottom_left = (12, 0);
+        let heat_losses = heat_loss_map::<BucketQueue<_>>(&map, CRUCIBLE, (0, 0)).unwrap();
+        assert_eq!(heat_losses[(12, 12)], Some(102));
+        assert_eq!(heat_losses[(0, 0)], Some(0));
+        assert_eq!(heat_losses[(0, 1)], Some(4));
+        let (to_center, path) =
+            find_shortest_path::<BucketQueue<_>>(&map, CRUCIBLE, (0, 0), center).unwrap();
+        assert_eq!(heat_losses[(6, 6)], Some(to_center));
+        assert_eq!(path.first(), Some(&center));
+        assert_eq!(path.last(), Some(&(0, 0)));
+        // The heat loss of the path is that of its blocks except the start
+        let blocks = path.iter().rev().dedup().skip(1).map(|&block| map[block]);
+        assert_eq!(blocks.sum::<usize>(), to_center);
+
+        // From the bottom left, with the heat loss map of the reverse direction as check: the
+        // heat loss there includes the end block instead of the start block
+        let (from_bottom_left, _) =
+            find_shortest_path::<HeapQueue<_>>(&map, CRUCIBLE, bottom_left, (0, 12)).unwrap();
+        let reverse = heat_loss_map::<HeapQueue<_>>(&map, CRUCIBLE, (0, 12)).unwrap();
+        assert_eq!(
+            from_bottom_left,
+            reverse[(12, 0)].unwrap() - map[bottom_left] + map[(0, 12)]
+        );
+
+        let ultra = heat_loss_map::<BucketQueue<_>>(&map, ULTRA_CRUCIBLE, (0, 0)).unwrap();
+        assert_eq!(ultra[(12, 12)], Some(94));
+        // The ultra crucible can't stop right after the first block, it has to come back later
+        assert!(ultra[(0, 1)] > heat_losses[(0, 1)]);
+        assert_eq!(
+            find_shortest_path::<BucketQueue<_>>(&map, CRUCIBLE, (0, 0), (13, 0))
+                .unwrap_err()
+                .to_string(),
+            "(13, 0) is outside of the 13x13 map"
+        );
+        drop(dir);
+    }
+
+    #[test]
+    fn test_format_heat_losses() {
+        assert_eq!(
+            format_heat_losses(
+                &Grid::from_rows(vec![vec![Some(0), Some(12)], vec![None, Some(7)]]).unwrap()
+            ),
+            " 0 12\n .  7"
+        );
+    }
 }
@@ -1,9 +1,18 @@
-use std::{collections::HashSet, path::Path, str::FromStr};
-
-use advent_of_code_2023::{render_grid::GridRenderer, stream_items_from_file};
-use anyhow::Result;
-
-const INPUT: &str = "input/day18.txt";
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use advent_of_code_2023::{
+    answer::Answer,
+    geometry::{boundary_points, interior_points},
+    render_grid::GridRenderer,
+    runner::{options, run, AocDay, Solver},
+    stream_items_from_file, timed,
+};
+use anyhow::{bail, Context, Result};
+use thiserror::Error;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Direction {
@@ -13,16 +22,30 @@ enum Direction {
     Down,  // D
 }
 
-impl TryFrom<char> for Direction {
-    type Error = anyhow::Error;
+#[derive(Error, Debug, PartialEq, Eq)]
+enum InstructionError {
+    #[error("Missing direction")]
+    MissingDirection,
+    #[error("Invalid direction {0:?}")]
+    InvalidDirection(String),
+    #[error("Missing length")]
+    MissingLength,
+    #[error("Invalid length {0:?}")]
+    InvalidLength(String),
+    #[error("Invalid color {0:?}, expected (#rrggbb) or #rrggbb")]
+    InvalidColor(String),
+}
+
+impl FromStr for Direction {
+    type Err = InstructionError;
 
-    fn try_from(value: char) -> Result<Self> {
-        match value {
-            'L' => Ok(Self::Left),
-            'R' => Ok(Self::Right),
-            'U' => Ok(Self::Up),
-            'D' => Ok(Self::Down),
-            _ => anyhow::bail!("Invalid direction: {}", value),
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "L" => Ok(Self::Left),
+            "R" => Ok(Self::Right),
+            "U" => Ok(Self::Up),
+            "D" => Ok(Self::Down),
+            _ => Err(InstructionError::InvalidDirection(s.to_owned())),
         }
     }
 }
@@ -31,30 +54,37 @@ impl TryFrom<char> for Direction {
 struct DigInstruction {
     direction: Direction,
     length: i32,
-    color: String,
+    /// The color as `#rrggbb`, if the dig plan has one
+    color: Option<String>,
 }
 
 impl FromStr for DigInstruction {
-    type Err = anyhow::Error;
+    type Err = InstructionError;
 
-    fn from_str(s: &str) -> Result<Self> {
+    /// Parses `DIR LENGTH COLOR`, where the color may be wrapped in parentheses or missing.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
         let mut parts = s.split_whitespace();
         let direction = parts
             .next()
-            .ok_or_else(|| anyhow::anyhow!("Missing direction"))?;
-        let direction = Direction::try_from(direction.chars().next().unwrap())?;
-        let length = parts
-            .next()
-            .ok_or_else(|| anyhow::anyhow!("Missing length"))?
+            .ok_or(InstructionError::MissingDirection)?
             .parse()?;
+        let length = parts.next().ok_or(InstructionError::MissingLength)?;
+        let length = length
+            .parse()
+            .map_err(|_| InstructionError::InvalidLength(length.to_owned()))?;
         let color = parts
             .next()
-            .ok_or_else(|| anyhow::anyhow!("Missing color"))?
-            .strip_prefix('(')
-            .ok_or_else(|| anyhow::anyhow!("Missing color paren left"))?
-            .strip_suffix(')')
-            .ok_or_else(|| anyhow::anyhow!("Missing color paren right"))?
-            .to_string();
+            .map(|color| {
+                let unwrapped = match color.strip_prefix('(') {
+                    Some(inner) => inner.strip_suffix(')'),
+                    None => Some(color),
+                };
+                unwrapped
+                    .filter(|code| code.starts_with('#'))
+                    .map(str::to_owned)
+                    .ok_or_else(|| InstructionError::InvalidColor(color.to_owned()))
+            })
+            .transpose()?;
         Ok(Self {
             direction,
             length,
@@ -74,7 +104,7 @@ fn build_trenches(instructions: impl Iterator<Item = DigInstruction>) -> HashSet
                     Direction::Down => (state.0 + delta, state.1),
                 })
                 .collect();
-            *state = output_steps.last().unwrap().clone();
+            *state = *output_steps.last().unwrap();
             Some(output_steps)
         })
         .flatten()
@@ -102,9 +132,9 @@ fn count_hole_tiles(
                     inside = !inside;
                 }
             } else if inside {
-                debug_renderer.as_mut().map(|debug_renderer| {
-                    debug_renderer.add_colored_grid_tile(y, x, "gray".to_string())
-                });
+                if let Some(debug_renderer) = debug_renderer.as_mut() {
+                    debug_renderer.add_colored_grid_tile(y, x, "gray".to_string(), None)
+                }
                 counter += 1;
             }
         }
@@ -113,12 +143,17 @@ fn count_hole_tiles(
     counter
 }
 
-fn part1<P: AsRef<Path>>(input: P) -> Result<u64> {
-    let raw_instructions: Vec<DigInstruction> =
-        stream_items_from_file(input)?.map(|i| i.unwrap()).collect();
+fn read_instructions<P: AsRef<Path>>(input: P) -> Result<Vec<DigInstruction>> {
+    stream_items_from_file(input)?
+        .enumerate()
+        .map(|(idx, instruction)| {
+            instruction.with_context(|| format!("Invalid instruction in line {}", idx + 1))
+        })
+        .collect()
+}
 
-    // Efficient shoelace solution:
-    let instructions: Vec<_> = raw_instructions
+fn part1<P: AsRef<Path>>(input: P) -> Result<u64> {
+    let instructions: Vec<_> = timed!("parse", read_instructions(input))?
         .iter()
         .map(|i| RealDigInstruction {
             direction: i.direction,
@@ -126,22 +161,50 @@ fn part1<P: AsRef<Path>>(input: P) -> Result<u64> {
         })
         .collect();
     let poly = TrenchPolygon::from(&instructions);
+    Ok(poly.get_area())
+}
 
-    let poly_based = poly.get_area();
+/// Initial naive solution: digs out every tile of the trench and counts the tiles inside row by
+/// row
+fn part1_naive<P: AsRef<Path>>(input: P) -> Result<u64> {
+    let instructions = timed!("parse", read_instructions(input))?;
+    let trench_boundaries = build_trenches(instructions.into_iter());
+    Ok(count_hole_tiles(&trench_boundaries, None) + (trench_boundaries.len() as u64))
+}
 
-    // Initial naive solution:
+/// Renders the trench tiles in black and the tiles inside in gray, as counted by the naive
+/// solution
+fn render_trenches<P: AsRef<Path>>(input: P) -> Result<GridRenderer<i32>> {
     let mut grid_renderer = GridRenderer::new();
-    let trench_boundaries = build_trenches(raw_instructions.iter().cloned());
-
+    let trench_boundaries = build_trenches(read_instructions(input)?.into_iter());
     grid_renderer.extend(trench_boundaries.iter().cloned());
+    count_hole_tiles(&trench_boundaries, Some(&mut grid_renderer));
+    Ok(grid_renderer)
+}
 
-    let hole_tiles = count_hole_tiles(&trench_boundaries, None) + (trench_boundaries.len() as u64);
-
-    grid_renderer.store_svg("debug.svg");
-
-    assert!(poly_based == hole_tiles);
-
-    Ok(hole_tiles)
+const INTERIOR_COLOR: &str = "#bab0ac66";
+
+/// Renders each trench segment in the color of its instruction, with the lagoon inside filled
+fn render_colored_trench<P: AsRef<Path>>(input: P) -> Result<GridRenderer<i32>> {
+    let mut renderer = GridRenderer::new();
+    let mut corners = vec![(0, 0)];
+    for instruction in read_instructions(input)? {
+        let (y, x) = *corners.last().unwrap();
+        let end = match instruction.direction {
+            Direction::Left => (y, x - instruction.length),
+            Direction::Right => (y, x + instruction.length),
+            Direction::Up => (y - instruction.length, x),
+            Direction::Down => (y + instruction.length, x),
+        };
+        renderer.set_layer("trench");
+        let color = instruction.color.unwrap_or_else(|| "black".to_owned());
+        renderer.add_path([(y, x), end], color, 0.8);
+        corners.push(end);
+    }
+    renderer.set_layer("lagoon");
+    renderer.set_layer_z("lagoon", -1);
+    renderer.add_polygon(corners, INTERIOR_COLOR.to_owned());
+    Ok(renderer)
 }
 
 #[derive(Debug)]
@@ -153,8 +216,16 @@ struct RealDigInstruction {
 impl TryFrom<DigInstruction> for RealDigInstruction {
     type Error = anyhow::Error;
 
+    /// Decodes the real instruction from the color, `#` followed by five hex digits for the
+    /// length and one for the direction
     fn try_from(value: DigInstruction) -> Result<Self> {
-        let code = value.color.strip_prefix("#").unwrap();
+        let Some(color) = value.color else {
+            bail!("Missing color, the real instructions are encoded in it");
+        };
+        let code = color.strip_prefix('#').unwrap_or(&color);
+        if code.len() < 2 || !code.is_ascii() {
+            bail!("Invalid color code {:?}", color);
+        }
         let length = i64::from_str_radix(&code[0..code.len() - 1], 16)?;
         let direction = match u8::from_str_radix(&code[code.len() - 1..], 16)? {
             // 0 means R, 1 means D, 2 means L, and 3 means U.
@@ -162,7 +233,7 @@ impl TryFrom<DigInstruction> for RealDigInstruction {
             1 => Direction::Down,
             2 => Direction::Left,
             3 => Direction::Up,
-            _ => anyhow::bail!("Invalid direction code: {}", code),
+            _ => bail!("Invalid direction code: {}", code),
         };
 
         Ok(RealDigInstruction { length, direction })
@@ -172,74 +243,80 @@ impl TryFrom<DigInstruction> for RealDigInstruction {
 #[derive(Debug)]
 struct TrenchPolygon {
     vertices: Vec<(i64, i64)>,
-    perim: u64,
 }
 
 impl From<&Vec<RealDigInstruction>> for TrenchPolygon {
     fn from(instructions: &Vec<RealDigInstruction>) -> Self {
-        let mut vertices = instructions
+        let vertices = instructions
             .iter()
             .scan((0i64, 0i64), |state, ins| {
-                Some(match ins.direction {
-                    Direction::Left => {
-                        *state = (state.0, state.1 - ins.length);
-                        state.clone()
-                    }
-                    Direction::Right => {
-                        *state = (state.0, state.1 + ins.length);
-                        state.clone()
-                    }
-                    Direction::Up => {
-                        *state = (state.0 - ins.length, state.1);
-                        state.clone()
-                    }
-                    Direction::Down => {
-                        *state = (state.0 + ins.length, state.1);
-                        state.clone()
-                    }
-                })
+                *state = match ins.direction {
+                    Direction::Left => (state.0, state.1 - ins.length),
+                    Direction::Right => (state.0, state.1 + ins.length),
+                    Direction::Up => (state.0 - ins.length, state.1),
+                    Direction::Down => (state.0 + ins.length, state.1),
+                };
+                Some(*state)
             })
             .collect::<Vec<_>>();
 
-        vertices.insert(0, (0i64, 0i64));
-        //vertices.reverse();
-
-        let perim = instructions.iter().map(|ins| ins.length.abs() as u64).sum();
-
-        Self { vertices, perim }
+        Self { vertices }
     }
 }
 
 impl TrenchPolygon {
+    /// Number of dug out tiles: the integer points inside the trench plus those on the trench,
+    /// no matter in which direction the trench runs around
     fn get_area(&self) -> u64 {
-        // https://en.wikipedia.org/wiki/Shoelace_formula
-        let shoelace_area = (self
-            .vertices
-            .iter()
-            .zip(self.vertices.iter().skip(1))
-            .map(|(v1, v2)| (v1.1 * v2.0) - (v1.0 * v2.1))
-            .sum::<i64>() as u64)
-            / 2;
-        // https://en.wikipedia.org/wiki/Pick%27s_theorem
-        // shoelace_area is the number of integer points within the polygon, self.perim the number
-        // of integer points on the boundary. The total area of the polygon is:
-        shoelace_area + self.perim / 2 + 1
+        (interior_points(&self.vertices) + boundary_points(&self.vertices)) as u64
     }
 }
 
 fn part2<P: AsRef<Path>>(input: P) -> Result<u64> {
-    let instructions: Vec<_> = stream_items_from_file::<_, DigInstruction>(input)?
-        .map(|mi| RealDigInstruction::try_from(mi.unwrap()).unwrap())
-        .collect();
+    let instructions = timed!("parse", read_instructions(input))?
+        .into_iter()
+        .enumerate()
+        .map(|(idx, instruction)| {
+            RealDigInstruction::try_from(instruction)
+                .with_context(|| format!("Invalid instruction in line {}", idx + 1))
+        })
+        .collect::<Result<Vec<_>>>()?;
     let poly = TrenchPolygon::from(&instructions);
     Ok(poly.get_area())
 }
 
-fn main() -> Result<()> {
-    println!("Answer for part 1: {}", part1(INPUT)?);
-    println!("Answer for part 2: {}", part2(INPUT)?);
+struct Day18;
+
+impl AocDay for Day18 {
+    const YEAR: u16 = 2023;
+    const DAY: u8 = 18;
+
+    /// `--visualize PATH` additionally stores an SVG of the dug out tiles, see
+    /// [`render_trenches`], or of the trench in the colors of the instructions with `--colors`,
+    /// see [`render_colored_trench`].
+    fn part1(input: &Path) -> Result<Answer> {
+        if let Some(path) = options().get::<PathBuf>("visualize")? {
+            if options().flag("colors") {
+                render_colored_trench(input)?.store_svg(path)?;
+            } else {
+                render_trenches(input)?.store_svg(path)?;
+            }
+        }
+        Ok(part1(input)?.into())
+    }
+
+    fn part2(input: &Path) -> Result<Answer> {
+        Ok(part2(input)?.into())
+    }
+
+    /// `--algo naive` counts the tiles one by one instead of using the shoelace formula.
+    fn part1_alternatives() -> Vec<(&'static str, Solver)> {
+        vec![("naive", |input| Ok(part1_naive(input)?.into()))]
+    }
+}
 
-    Ok(())
+fn main() -> Result<()> {
+    run::<Day18>()
 }
 
 #[cfg(test)]
@@ -270,7 +347,147 @@ mod tests_day18 {
             None,
         );
         assert_eq!(part1(&file).unwrap(), 62);
+        assert_eq!(part1_naive(&file).unwrap(), 62);
         assert_eq!(part2(&file).unwrap(), 952408144115);
+
+        let mut svg = Vec::new();
+        render_trenches(&file)
+            .unwrap()
+            .render_svg(&mut svg)
+            .unwrap();
+        let svg = String::from_utf8(svg).unwrap();
+        assert_eq!(svg.matches(r#"fill="black""#).count(), 38);
+        assert_eq!(svg.matches(r#"fill="gray""#).count(), 62 - 38);
+
+        let mut svg = Vec::new();
+        render_colored_trench(&file)
+            .unwrap()
+            .render_svg(&mut svg)
+            .unwrap();
+        let svg = String::from_utf8(svg).unwrap();
+        assert_eq!(svg.matches("<polyline").count(), 14);
+        assert!(svg.contains(r##"points="0,0 6,0" fill="none" stroke="#70c710""##));
+        assert_eq!(
+            svg.matches(&format!(r#"fill="{}""#, INTERIOR_COLOR))
+                .count(),
+            1
+        );
+        drop(dir);
+    }
+
+    /// The same trench, dug in the opposite direction
+    fn reverse(instructions: &[RealDigInstruction]) -> Vec<RealDigInstruction> {
+        instructions
+            .iter()
+            .rev()
+            .map(|ins| RealDigInstruction {
+                direction: match ins.direction {
+                    Direction::Left => Direction::Right,
+                    Direction::Right => Direction::Left,
+                    Direction::Up => Direction::Down,
+                    Direction::Down => Direction::Up,
+                },
+                length: ins.length,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_counter_clockwise() {
+        let (dir, file) = create_example_file(
+            indoc! {r"
+            D 2 (#000000)
+            R 2 (#000000)
+            D 3 (#000000)
+            L 2 (#000000)
+            D 2 (#000000)
+            R 1 (#000000)
+            D 2 (#000000)
+            R 5 (#000000)
+            U 2 (#000000)
+            L 2 (#000000)
+            U 2 (#000000)
+            R 2 (#000000)
+            U 5 (#000000)
+            L 6 (#000000)
+        "},
+            None,
+        );
+        // The example dug the other way round
+        assert_eq!(part1(&file).unwrap(), 62);
+        assert_eq!(part1_naive(&file).unwrap(), 62);
+        drop(dir);
+
+        let (dir, file) = create_example_file(
+            indoc! {r"
+            R 6 (#70c710)
+            D 5 (#0dc571)
+            L 2 (#5713f0)
+            D 2 (#d2c081)
+            R 2 (#59c680)
+            D 2 (#411b91)
+            L 5 (#8ceee2)
+            U 2 (#caa173)
+            L 1 (#1b58a2)
+            U 2 (#caa171)
+            R 2 (#7807d2)
+            U 3 (#a77fa3)
+            L 2 (#015232)
+            U 2 (#7a21e3)
+        "},
+            None,
+        );
+        let instructions = read_instructions(&file)
+            .unwrap()
+            .into_iter()
+            .map(|i| RealDigInstruction::try_from(i).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            TrenchPolygon::from(&reverse(&instructions)).get_area(),
+            952408144115
+        );
+        drop(dir);
+    }
+
+    #[test]
+    fn test_instruction_encodings() {
+        let (dir, file) = create_example_file(
+            indoc! {r"
+            R 6
+            D 5 #0dc571
+            L 2 (#5713f0)
+            D 2
+            R 2
+            D 2
+            L 5
+            U 2
+            L 1
+            U 2
+            R 2
+            U 3
+            L 2
+            U 2
+        "},
+            None,
+        );
+        assert_eq!(part1(&file).unwrap(), 62);
+        assert_eq!(
+            format!("{:#}", part2(&file).unwrap_err()),
+            "Invalid instruction in line 1: Missing color, the real instructions are encoded in it"
+        );
         drop(dir);
+
+        assert_eq!(
+            "D 5 (#0dc571".parse::<DigInstruction>().err(),
+            Some(InstructionError::InvalidColor("(#0dc571".to_owned()))
+        );
+        assert_eq!(
+            "X 5".parse::<DigInstruction>().err(),
+            Some(InstructionError::InvalidDirection("X".to_owned()))
+        );
+        assert_eq!(
+            "D five".parse::<DigInstruction>().err(),
+            Some(InstructionError::InvalidLength("five".to_owned()))
+        );
     }
 }
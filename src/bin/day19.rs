@@ -0,0 +1,355 @@
+use std::{collections::HashMap, path::Path, str::FromStr};
+
+use advent_of_code_2023::{
+    answer::Answer,
+    runner::{run, AocDay},
+    stream_file_blocks, timed,
+};
+use anyhow::{bail, Context, Result};
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq, Eq)]
+enum WorkflowError {
+    #[error("Invalid workflow {0:?}, expected \"<name>{{<rules>}}\"")]
+    InvalidWorkflow(String),
+    #[error("Invalid rule {0:?}, expected \"<category><'<' or '>'><value>:<target>\" or a target")]
+    InvalidRule(String),
+    #[error("The last rule of a workflow needs to be a target without condition")]
+    MissingFallback,
+    #[error("Invalid part {0:?}, expected \"{{x=<value>,m=<value>,a=<value>,s=<value>}}\"")]
+    InvalidPart(String),
+}
+
+/// Index of a rating category in the ratings of a part
+fn category(name: &str) -> Option<usize> {
+    ["x", "m", "a", "s"].iter().position(|c| *c == name)
+}
+
+/// The ratings of a part for extremely cool looking, musical, aerodynamic and shiny
+type Part = [u64; 4];
+
+fn parse_part(s: &str) -> Result<Part, WorkflowError> {
+    let invalid = || WorkflowError::InvalidPart(s.to_owned());
+    let ratings = s
+        .strip_prefix('{')
+        .and_then(|s| s.strip_suffix('}'))
+        .ok_or_else(invalid)?;
+    let mut part = [None; 4];
+    for rating in ratings.split(',') {
+        let (name, value) = rating.split_once('=').ok_or_else(invalid)?;
+        let idx = category(name).ok_or_else(invalid)?;
+        part[idx] = Some(value.parse().map_err(|_| invalid())?);
+    }
+    let [Some(x), Some(m), Some(a), Some(s)] = part else {
+        return Err(invalid());
+    };
+    Ok([x, m, a, s])
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Target {
+    Accept,
+    Reject,
+    Workflow(String),
+}
+
+impl From<&str> for Target {
+    fn from(s: &str) -> Self {
+        match s {
+            "A" => Target::Accept,
+            "R" => Target::Reject,
+            name => Target::Workflow(name.to_owned()),
+        }
+    }
+}
+
+/// `category < value` or `category > value`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Condition {
+    category: usize,
+    less: bool,
+    value: u64,
+}
+
+impl Condition {
+    fn matches(&self, part: &Part) -> bool {
+        if self.less {
+            part[self.category] < self.value
+        } else {
+            part[self.category] > self.value
+        }
+    }
+
+    /// Splits the half-open range of ratings into the ratings that match and those that don't
+    fn split(&self, (start, end): (u64, u64)) -> ((u64, u64), (u64, u64)) {
+        if self.less {
+            ((start, end.min(self.value)), (start.max(self.value), end))
+        } else {
+            let first = self.value + 1;
+            ((start.max(first), end), (start, end.min(first)))
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Rule {
+    condition: Option<Condition>,
+    target: Target,
+}
+
+impl FromStr for Rule {
+    type Err = WorkflowError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let Some((condition, target)) = s.split_once(':') else {
+            return Ok(Rule {
+                condition: None,
+                target: s.into(),
+            });
+        };
+        let invalid = || WorkflowError::InvalidRule(s.to_owned());
+        let split = condition.find(['<', '>']).ok_or_else(invalid)?;
+        let (name, value) = condition.split_at(split);
+        let condition = Condition {
+            category: category(name).ok_or_else(invalid)?,
+            less: value.starts_with('<'),
+            value: value[1..].parse().map_err(|_| invalid())?,
+        };
+        Ok(Rule {
+            condition: Some(condition),
+            target: target.into(),
+        })
+    }
+}
+
+/// Parses `name{rule,rule,...}`
+fn parse_workflow(s: &str) -> Result<(String, Vec<Rule>), WorkflowError> {
+    let (name, rules) = s
+        .strip_suffix('}')
+        .and_then(|s| s.split_once('{'))
+        .ok_or_else(|| WorkflowError::InvalidWorkflow(s.to_owned()))?;
+    let rules = rules
+        .split(',')
+        .map(str::parse)
+        .collect::<Result<Vec<Rule>, _>>()?;
+    if rules.last().is_none_or(|rule| rule.condition.is_some()) {
+        return Err(WorkflowError::MissingFallback);
+    }
+    Ok((name.to_owned(), rules))
+}
+
+const START_WORKFLOW: &str = "in";
+
+struct System {
+    workflows: HashMap<String, Vec<Rule>>,
+    parts: Vec<Part>,
+}
+
+impl System {
+    /// Reads the workflows and, after an empty line, the parts
+    fn read_input<P: AsRef<Path>>(input: P) -> Result<Self> {
+        let mut blocks = stream_file_blocks(input)?;
+        let workflow_lines = blocks.next().unwrap_or_default();
+        let workflows = workflow_lines
+            .iter()
+            .enumerate()
+            .map(|(idx, line)| {
+                parse_workflow(line)
+                    .with_context(|| format!("Invalid workflow in line {}", idx + 1))
+            })
+            .collect::<Result<HashMap<_, _>>>()?;
+        let part_lines = blocks.next().unwrap_or_default();
+        let parts = part_lines
+            .iter()
+            .enumerate()
+            .map(|(idx, line)| {
+                parse_part(line).with_context(|| {
+                    format!("Invalid part in line {}", workflow_lines.len() + idx + 2)
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(System { workflows, parts })
+    }
+
+    fn workflow(&self, name: &str) -> Result<&[Rule]> {
+        match self.workflows.get(name) {
+            Some(rules) => Ok(rules),
+            None => bail!("Unknown workflow {:?}", name),
+        }
+    }
+
+    /// Follows the workflows from `in` until the part is accepted or rejected
+    fn accepts(&self, part: &Part) -> Result<bool> {
+        let mut name = START_WORKFLOW;
+        // Each workflow can be visited at most once, otherwise the part goes round in circles
+        for _ in 0..=self.workflows.len() {
+            let rule = self
+                .workflow(name)?
+                .iter()
+                .find(|rule| rule.condition.is_none_or(|c| c.matches(part)))
+                .expect("The last rule matches all parts");
+            match &rule.target {
+                Target::Accept => return Ok(true),
+                Target::Reject => return Ok(false),
+                Target::Workflow(next) => name = next,
+            }
+        }
+        bail!("The workflows send the part {:?} in circles", part)
+    }
+
+    /// Number of rating combinations within the given half-open ranges that are accepted when
+    /// starting at the given workflow. Each rule splits the ranges into the combinations it
+    /// sends on to its target and those left for the next rule.
+    fn count_accepted(&self, name: &str, mut ranges: [(u64, u64); 4], depth: usize) -> Result<u64> {
+        if depth > self.workflows.len() {
+            bail!("The workflows send parts in circles through {:?}", name);
+        }
+        let mut accepted = 0;
+        for rule in self.workflow(name)? {
+            let matched = match rule.condition {
+                Some(condition) => {
+                    let (matched, rest) = condition.split(ranges[condition.category]);
+                    let mut matched_ranges = ranges;
+                    matched_ranges[condition.category] = matched;
+                    ranges[condition.category] = rest;
+                    matched_ranges
+                }
+                None => ranges,
+            };
+            if matched.iter().all(|(start, end)| start < end) {
+                accepted += match &rule.target {
+                    Target::Accept => matched.iter().map(|(start, end)| end - start).product(),
+                    Target::Reject => 0,
+                    Target::Workflow(next) => self.count_accepted(next, matched, depth + 1)?,
+                };
+            }
+            if ranges.iter().any(|(start, end)| start >= end) {
+                break;
+            }
+        }
+        Ok(accepted)
+    }
+}
+
+/// Ratings range from 1 to 4000
+const RATINGS: (u64, u64) = (1, 4001);
+
+fn part1<P: AsRef<Path>>(input: P) -> Result<u64> {
+    let system = timed!("parse", System::read_input(input))?;
+    let mut sum = 0;
+    for part in &system.parts {
+        if system.accepts(part)? {
+            sum += part.iter().sum::<u64>();
+        }
+    }
+    Ok(sum)
+}
+
+fn part2<P: AsRef<Path>>(input: P) -> Result<u64> {
+    let system = timed!("parse", System::read_input(input))?;
+    system.count_accepted(START_WORKFLOW, [RATINGS; 4], 0)
+}
+
+struct Day19;
+
+impl AocDay for Day19 {
+    const YEAR: u16 = 2023;
+    const DAY: u8 = 19;
+
+    fn part1(input: &Path) -> Result<Answer> {
+        Ok(part1(input)?.into())
+    }
+
+    fn part2(input: &Path) -> Result<Answer> {
+        Ok(part2(input)?.into())
+    }
+}
+
+fn main() -> Result<()> {
+    run::<Day19>()
+}
+
+#[cfg(test)]
+mod tests_day19 {
+    use super::*;
+    use advent_of_code_2023::test_helpers::create_example_file;
+    use indoc::indoc;
+
+    #[test]
+    fn test_example() {
+        let (dir, file) = create_example_file(
+            indoc! {"
+            px{a<2006:qkq,m>2090:A,rfg}
+            pv{a>1716:R,A}
+            lnx{m>1548:A,A}
+            rfg{s<537:gd,x>2440:R,A}
+            qs{s>3448:A,lnx}
+            qkq{x<1416:A,crn}
+            crn{x>2662:A,R}
+            in{s<1351:px,qqz}
+            qqz{s>2770:qs,m<1801:hdj,R}
+            gd{a>3333:R,R}
+            hdj{m>838:A,pv}
+
+            {x=787,m=2655,a=1222,s=2876}
+            {x=1679,m=44,a=2067,s=496}
+            {x=2036,m=264,a=79,s=2244}
+            {x=2461,m=1339,a=466,s=291}
+            {x=2127,m=1623,a=2188,s=1013}
+        "},
+            None,
+        );
+        assert_eq!(part1(&file).unwrap(), 19114);
+        assert_eq!(part2(&file).unwrap(), 167409079868000);
+        drop(dir);
+    }
+
+    #[test]
+    fn test_split_ranges() {
+        let less = Condition {
+            category: 0,
+            less: true,
+            value: 10,
+        };
+        assert_eq!(less.split((1, 4001)), ((1, 10), (10, 4001)));
+        assert_eq!(less.split((20, 30)), ((20, 10), (20, 30)));
+        let greater = Condition {
+            less: false,
+            ..less
+        };
+        assert_eq!(greater.split((1, 4001)), ((11, 4001), (1, 11)));
+        assert_eq!(greater.split((1, 5)), ((11, 5), (1, 5)));
+    }
+
+    #[test]
+    fn test_invalid_systems() {
+        assert_eq!(
+            parse_workflow("in{x<5:A}"),
+            Err(WorkflowError::MissingFallback)
+        );
+        assert_eq!(
+            parse_workflow("in{y<5:A,R}"),
+            Err(WorkflowError::InvalidRule("y<5:A".to_owned()))
+        );
+        assert_eq!(
+            parse_part("{x=1,m=2,a=3}"),
+            Err(WorkflowError::InvalidPart("{x=1,m=2,a=3}".to_owned()))
+        );
+
+        let (dir, file) = create_example_file(
+            indoc! {"
+            in{x<5:loop,R}
+            loop{m>5:in,A}
+
+            {x=1,m=6,a=1,s=1}
+        "},
+            None,
+        );
+        assert_eq!(
+            part1(&file).unwrap_err().to_string(),
+            "The workflows send the part [1, 6, 1, 1] in circles"
+        );
+        assert!(part2(&file).is_err());
+        drop(dir);
+    }
+}
@@ -0,0 +1,227 @@
+use std::path::Path;
+
+use advent_of_code_2023::{
+    answer::Answer,
+    grid::{Direction, Grid},
+    maths::extrapolate,
+    read_lines,
+    runner::{options, run, AocDay},
+    search::bfs_layers,
+    timed,
+};
+use anyhow::{anyhow, bail, Context, Result};
+
+struct Garden {
+    /// Whether there is a rock on the tile
+    rocks: Grid<bool>,
+    start: (usize, usize),
+}
+
+impl Garden {
+    fn read_input<P: AsRef<Path>>(input: P) -> Result<Self> {
+        let lines = read_lines(input)?.collect::<Result<Vec<_>, _>>()?;
+        let tiles = Grid::parse(lines, |c| match c {
+            '.' | '#' | 'S' => Ok(c),
+            _ => Err(anyhow!("Invalid tile {:?}", c)),
+        })?;
+        let mut starts = tiles.iter().filter(|(_, _, tile)| **tile == 'S');
+        let (Some((y, x, _)), None) = (starts.next(), starts.next()) else {
+            bail!("Expected exactly one start tile 'S'");
+        };
+        Ok(Garden {
+            rocks: tiles.map(|tile| *tile == '#'),
+            start: (y, x),
+        })
+    }
+
+    /// Number of garden plots reached with exactly `steps` steps on the map, given the number of
+    /// plots first reached with each number of steps. Going back and forth, every plot reached
+    /// with fewer steps of the same parity is reached again.
+    fn count_reached(layer_sizes: &[usize], steps: usize) -> usize {
+        layer_sizes
+            .iter()
+            .take(steps + 1)
+            .skip(steps % 2)
+            .step_by(2)
+            .sum()
+    }
+
+    /// Number of plots first reached with each number of steps, up to `max_steps`
+    fn layer_sizes(&self, max_steps: usize) -> Vec<usize> {
+        let neighbors = |&(y, x): &(usize, usize)| {
+            Direction::ALL
+                .into_iter()
+                .filter_map(move |direction| self.rocks.step(y, x, direction))
+                .filter(|&pos| !self.rocks[pos])
+        };
+        bfs_layers([self.start], neighbors)
+            .take(max_steps + 1)
+            .map(|layer| layer.len())
+            .collect()
+    }
+
+    /// Number of plots first reached with each number of steps with the map repeated
+    /// infinitely in all directions, as long as they are requested or until no new plots are
+    /// reached if the start is walled in
+    fn wrapping_layer_sizes(&self) -> impl Iterator<Item = usize> + '_ {
+        let start = (self.start.0 as isize, self.start.1 as isize);
+        let neighbors = |&(y, x): &(isize, isize)| {
+            Direction::ALL
+                .into_iter()
+                .map(move |direction| {
+                    let (dy, dx) = direction.delta();
+                    (y + dy, x + dx)
+                })
+                .filter(|&(y, x)| !self.rocks.get_wrapping(y, x))
+        };
+        bfs_layers([start], neighbors).map(|layer| layer.len())
+    }
+}
+
+const PART1_STEPS: usize = 64;
+const PART2_STEPS: usize = 26501365;
+
+/// How many periods of the map to simulate at most until the counts grow quadratically
+const MAX_PERIODS: usize = 20;
+
+/// Number of plots reached with exactly `steps` steps in the infinite garden.
+///
+/// Once the reached area is larger than the map, every `width` steps it covers another ring of
+/// copies of the map, so the counts at `offset`, `offset + width`, `offset + 2 * width`, ... grow
+/// quadratically with the number of rings. This simulates until two second differences of these
+/// counts in a row are the same and extrapolates from there, or until the steps are reached if
+/// that is sooner.
+fn count_infinite(garden: &Garden, steps: usize) -> Result<usize> {
+    let period = garden.rocks.width();
+    if garden.rocks.height() != period {
+        bail!("Only square maps can be extrapolated");
+    }
+    let offset = steps % period;
+    let mut layers = garden.wrapping_layer_sizes();
+    let mut layer_sizes = Vec::new();
+    let mut samples = Vec::new();
+    for k in 0..=MAX_PERIODS {
+        let sample_steps = offset + k * period;
+        layer_sizes.extend(layers.by_ref().take(sample_steps + 1 - layer_sizes.len()));
+        if sample_steps >= steps || layer_sizes.len() <= sample_steps {
+            // Either done, or the reached area is finite and all further counts are known
+            return Ok(Garden::count_reached(&layer_sizes, steps));
+        }
+        let count = Garden::count_reached(&layer_sizes, sample_steps);
+        samples.push((i64::try_from(sample_steps)?, i64::try_from(count)?));
+        if samples.len() >= 4 {
+            let recent = &samples[samples.len() - 4..];
+            if let Some(count) = extrapolate::quadratic(recent, i64::try_from(steps)?) {
+                return usize::try_from(count).context("The count doesn't fit into 64 bits");
+            }
+        }
+    }
+    bail!(
+        "The counts don't grow quadratically within {} periods",
+        MAX_PERIODS
+    )
+}
+
+fn part1<P: AsRef<Path>>(input: P, steps: usize) -> Result<usize> {
+    let garden = timed!("parse", Garden::read_input(input))?;
+    Ok(Garden::count_reached(&garden.layer_sizes(steps), steps))
+}
+
+fn part2<P: AsRef<Path>>(input: P, steps: usize) -> Result<usize> {
+    let garden = timed!("parse", Garden::read_input(input))?;
+    count_infinite(&garden, steps)
+}
+
+struct Day21;
+
+impl AocDay for Day21 {
+    const YEAR: u16 = 2023;
+    const DAY: u8 = 21;
+
+    /// `--steps N` walks N steps instead of 64.
+    fn part1(input: &Path) -> Result<Answer> {
+        let steps = options().get("steps")?.unwrap_or(PART1_STEPS);
+        Ok(part1(input, steps)?.into())
+    }
+
+    /// `--steps N` walks N steps instead of 26501365.
+    fn part2(input: &Path) -> Result<Answer> {
+        let steps = options().get("steps")?.unwrap_or(PART2_STEPS);
+        Ok(part2(input, steps)?.into())
+    }
+}
+
+fn main() -> Result<()> {
+    run::<Day21>()
+}
+
+#[cfg(test)]
+mod tests_day21 {
+    use super::*;
+    use advent_of_code_2023::test_helpers::create_example_file;
+    use indoc::indoc;
+
+    const EXAMPLE: &str = indoc! {"
+        ...........
+        .....###.#.
+        .###.##..#.
+        ..#.#...#..
+        ....#.#....
+        .##..S####.
+        .##..#...#.
+        .......##..
+        .##.#.####.
+        .##..##.##.
+        ...........
+    "};
+
+    #[test]
+    fn test_example() {
+        let (dir, file) = create_example_file(EXAMPLE, None);
+        assert_eq!(part1(&file, 6).unwrap(), 16);
+        for (steps, reached) in [
+            (6, 16),
+            (10, 50),
+            (50, 1594),
+            (100, 6536),
+            (500, 167004),
+            (1000, 668697),
+            (5000, 16733044),
+        ] {
+            assert_eq!(part2(&file, steps).unwrap(), reached, "{}", steps);
+        }
+        drop(dir);
+    }
+
+    #[test]
+    fn test_extrapolation_matches_simulation() {
+        let (dir, file) = create_example_file(EXAMPLE, None);
+        let garden = Garden::read_input(&file).unwrap();
+        let layer_sizes = garden.wrapping_layer_sizes().take(801).collect::<Vec<_>>();
+        for steps in [500, 701, 800] {
+            assert_eq!(
+                count_infinite(&garden, steps).unwrap(),
+                Garden::count_reached(&layer_sizes, steps),
+                "{}",
+                steps
+            );
+        }
+        drop(dir);
+    }
+
+    #[test]
+    fn test_walled_in() {
+        let (dir, file) = create_example_file(
+            indoc! {"
+            .#.
+            #S#
+            .#.
+        "},
+            None,
+        );
+        assert_eq!(part1(&file, 64).unwrap(), 1);
+        assert_eq!(part2(&file, 64).unwrap(), 1);
+        assert_eq!(part2(&file, PART2_STEPS).unwrap(), 0);
+        drop(dir);
+    }
+}
@@ -0,0 +1,233 @@
+use std::{collections::HashMap, path::Path, str::FromStr};
+
+use advent_of_code_2023::{
+    answer::Answer,
+    runner::{run, AocDay},
+    stream_items_from_file, timed,
+};
+use anyhow::{Context, Result};
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq, Eq)]
+enum BrickError {
+    #[error("Expected two ends separated by '~'")]
+    MissingEnd,
+    #[error("Invalid end {0:?}, expected \"x,y,z\"")]
+    InvalidEnd(String),
+}
+
+/// A brick from one cube to another, both included, with `start <= end` in all coordinates
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Brick {
+    start: [usize; 3],
+    end: [usize; 3],
+}
+
+fn parse_end(s: &str) -> Result<[usize; 3], BrickError> {
+    let invalid = || BrickError::InvalidEnd(s.to_owned());
+    let coords = s
+        .split(',')
+        .map(|c| c.trim().parse().map_err(|_| invalid()))
+        .collect::<Result<Vec<_>, _>>()?;
+    coords.try_into().map_err(|_| invalid())
+}
+
+impl FromStr for Brick {
+    type Err = BrickError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (a, b) = s.split_once('~').ok_or(BrickError::MissingEnd)?;
+        let (a, b) = (parse_end(a)?, parse_end(b)?);
+        Ok(Brick {
+            start: [0, 1, 2].map(|i| a[i].min(b[i])),
+            end: [0, 1, 2].map(|i| a[i].max(b[i])),
+        })
+    }
+}
+
+impl Brick {
+    /// The `(x, y)` positions the brick covers seen from above
+    fn footprint(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        (self.start[0]..=self.end[0])
+            .flat_map(move |x| (self.start[1]..=self.end[1]).map(move |y| (x, y)))
+    }
+
+    fn height(&self) -> usize {
+        self.end[2] - self.start[2] + 1
+    }
+}
+
+fn read_bricks<P: AsRef<Path>>(input: P) -> Result<Vec<Brick>> {
+    stream_items_from_file(input)?
+        .enumerate()
+        .map(|(idx, brick)| brick.with_context(|| format!("Invalid brick in line {}", idx + 1)))
+        .collect()
+}
+
+/// Which bricks rest on which after all of them fell as far as they can
+struct Tower {
+    /// The bricks resting on each brick
+    supports: Vec<Vec<usize>>,
+    /// The bricks each brick rests on, none for the bricks on the ground
+    supported_by: Vec<Vec<usize>>,
+}
+
+impl Tower {
+    /// Lets the bricks fall from the lowest to the highest, each one until it hits the ground or
+    /// the highest brick below any of its cubes. The bricks it lands on support it.
+    fn settle(bricks: &[Brick]) -> Self {
+        let mut order = (0..bricks.len()).collect::<Vec<_>>();
+        order.sort_by_key(|&idx| bricks[idx].start[2]);
+
+        // Height of the top and the brick there for each position seen from above
+        let mut tops = HashMap::<(usize, usize), (usize, usize)>::new();
+        let mut supports = vec![Vec::new(); bricks.len()];
+        let mut supported_by = vec![Vec::new(); bricks.len()];
+        for idx in order {
+            let brick = &bricks[idx];
+            let below = brick
+                .footprint()
+                .filter_map(|pos| tops.get(&pos).copied())
+                .collect::<Vec<_>>();
+            let floor = below.iter().map(|(top, _)| *top).max().unwrap_or(0);
+            let mut resting_on = below
+                .iter()
+                .filter(|(top, _)| *top == floor)
+                .map(|(_, other)| *other)
+                .collect::<Vec<_>>();
+            resting_on.sort_unstable();
+            resting_on.dedup();
+            for &other in &resting_on {
+                supports[other].push(idx);
+            }
+            supported_by[idx] = resting_on;
+            let top = floor + brick.height();
+            for pos in brick.footprint() {
+                tops.insert(pos, (top, idx));
+            }
+        }
+        Tower {
+            supports,
+            supported_by,
+        }
+    }
+
+    /// Whether no other brick would fall if the brick was disintegrated, because all bricks on
+    /// top of it rest on another brick as well
+    fn can_disintegrate(&self, brick: usize) -> bool {
+        self.supports[brick]
+            .iter()
+            .all(|&other| self.supported_by[other].len() > 1)
+    }
+
+    /// Number of other bricks that fall in a chain reaction when the brick is disintegrated: a
+    /// brick falls once all bricks it rests on have fallen
+    fn falling(&self, brick: usize) -> usize {
+        let mut fallen = vec![false; self.supports.len()];
+        let mut missing_support = self.supported_by.iter().map(Vec::len).collect::<Vec<_>>();
+        fallen[brick] = true;
+        let mut to_check = vec![brick];
+        let mut count = 0;
+        while let Some(current) = to_check.pop() {
+            for &other in &self.supports[current] {
+                missing_support[other] -= 1;
+                if missing_support[other] == 0 && !fallen[other] {
+                    fallen[other] = true;
+                    count += 1;
+                    to_check.push(other);
+                }
+            }
+        }
+        count
+    }
+}
+
+fn part1<P: AsRef<Path>>(input: P) -> Result<usize> {
+    let bricks = timed!("parse", read_bricks(input))?;
+    let tower = timed!("settle", Tower::settle(&bricks));
+    Ok((0..bricks.len())
+        .filter(|&brick| tower.can_disintegrate(brick))
+        .count())
+}
+
+fn part2<P: AsRef<Path>>(input: P) -> Result<usize> {
+    let bricks = timed!("parse", read_bricks(input))?;
+    let tower = timed!("settle", Tower::settle(&bricks));
+    Ok((0..bricks.len()).map(|brick| tower.falling(brick)).sum())
+}
+
+struct Day22;
+
+impl AocDay for Day22 {
+    const YEAR: u16 = 2023;
+    const DAY: u8 = 22;
+
+    fn part1(input: &Path) -> Result<Answer> {
+        Ok(part1(input)?.into())
+    }
+
+    fn part2(input: &Path) -> Result<Answer> {
+        Ok(part2(input)?.into())
+    }
+}
+
+fn main() -> Result<()> {
+    run::<Day22>()
+}
+
+#[cfg(test)]
+mod tests_day22 {
+    use super::*;
+    use advent_of_code_2023::test_helpers::create_example_file;
+    use indoc::indoc;
+
+    const EXAMPLE: &str = indoc! {"
+        1,0,1~1,2,1
+        0,0,2~2,0,2
+        0,2,3~2,2,3
+        0,0,4~0,2,4
+        2,0,5~2,2,5
+        0,1,6~2,1,6
+        1,1,8~1,1,9
+    "};
+
+    #[test]
+    fn test_example() {
+        let (dir, file) = create_example_file(EXAMPLE, None);
+        assert_eq!(part1(&file).unwrap(), 5);
+        assert_eq!(part2(&file).unwrap(), 7);
+        drop(dir);
+    }
+
+    #[test]
+    fn test_settle() {
+        let (dir, file) = create_example_file(EXAMPLE, None);
+        let bricks = read_bricks(&file).unwrap();
+        let tower = Tower::settle(&bricks);
+        // A supports B and C, which both support D and E, which both support F, which supports G
+        assert_eq!(tower.supports[0], [1, 2]);
+        assert_eq!(tower.supported_by[3], [1, 2]);
+        assert_eq!(tower.supported_by[6], [5]);
+        assert!(tower.supported_by[0].is_empty());
+        assert_eq!(tower.falling(0), 6);
+        assert_eq!(tower.falling(5), 1);
+        drop(dir);
+    }
+
+    #[test]
+    fn test_parse_brick() {
+        // The ends may come in any order
+        assert_eq!(
+            "2,2,2~0,2,1".parse::<Brick>(),
+            Ok(Brick {
+                start: [0, 2, 1],
+                end: [2, 2, 2]
+            })
+        );
+        assert_eq!("1,2,3".parse::<Brick>(), Err(BrickError::MissingEnd));
+        assert_eq!(
+            "1,2~1,2,3".parse::<Brick>(),
+            Err(BrickError::InvalidEnd("1,2".to_owned()))
+        );
+    }
+}
@@ -0,0 +1,342 @@
+use std::{path::Path, str::FromStr};
+
+use advent_of_code_2023::{
+    answer::Answer,
+    maths::linear,
+    runner::{options, run, AocDay},
+    stream_items_from_file, timed,
+};
+use anyhow::{bail, Context, Result};
+use num::{rational::Ratio, BigInt, BigRational, ToPrimitive};
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq, Eq)]
+enum HailstoneError {
+    #[error("Expected position and velocity separated by '@'")]
+    MissingVelocity,
+    #[error("Invalid vector {0:?}, expected three integers separated by commas")]
+    InvalidVector(String),
+}
+
+type Vector = [i64; 3];
+
+fn parse_vector(s: &str) -> Result<Vector, HailstoneError> {
+    let invalid = || HailstoneError::InvalidVector(s.trim().to_owned());
+    let coords = s
+        .split(',')
+        .map(|c| c.trim().parse().map_err(|_| invalid()))
+        .collect::<Result<Vec<_>, _>>()?;
+    coords.try_into().map_err(|_| invalid())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Hailstone {
+    position: Vector,
+    velocity: Vector,
+}
+
+impl FromStr for Hailstone {
+    type Err = HailstoneError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (position, velocity) = s.split_once('@').ok_or(HailstoneError::MissingVelocity)?;
+        Ok(Hailstone {
+            position: parse_vector(position)?,
+            velocity: parse_vector(velocity)?,
+        })
+    }
+}
+
+fn read_hailstones<P: AsRef<Path>>(input: P) -> Result<Vec<Hailstone>> {
+    stream_items_from_file(input)?
+        .enumerate()
+        .map(|(idx, hailstone)| {
+            hailstone.with_context(|| format!("Invalid hailstone in line {}", idx + 1))
+        })
+        .collect()
+}
+
+/// The test area for the X and Y positions of the crossings, both bounds included
+#[derive(Debug, Clone, Copy)]
+struct TestArea(i64, i64);
+
+const TEST_AREA: TestArea = TestArea(200_000_000_000_000, 400_000_000_000_000);
+
+/// Parses `MIN,MAX`
+impl FromStr for TestArea {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let Some((min, max)) = s.split_once(',') else {
+            bail!("Expected MIN,MAX");
+        };
+        Ok(TestArea(min.trim().parse()?, max.trim().parse()?))
+    }
+}
+
+/// Where the paths of two hailstones cross in the future of both, ignoring the Z axis. Parallel
+/// paths never cross.
+fn crossing_2d(a: &Hailstone, b: &Hailstone) -> Option<[Ratio<i128>; 2]> {
+    let r = |n: i64| Ratio::from_integer(i128::from(n));
+    // a.position + t * a.velocity = b.position + s * b.velocity for the X and Y axes
+    let matrix = (0..2)
+        .map(|axis| vec![r(a.velocity[axis]), -r(b.velocity[axis])])
+        .collect();
+    let rhs = (0..2)
+        .map(|axis| r(b.position[axis]) - r(a.position[axis]))
+        .collect();
+    let times = linear::solve(matrix, rhs)?;
+    let (t, s) = (times[0], times[1]);
+    if t < Ratio::from_integer(0) || s < Ratio::from_integer(0) {
+        return None;
+    }
+    Some([0, 1].map(|axis| r(a.position[axis]) + t * r(a.velocity[axis])))
+}
+
+fn part1<P: AsRef<Path>>(input: P, area: TestArea) -> Result<usize> {
+    let hailstones = timed!("parse", read_hailstones(input))?;
+    let (min, max) = (
+        Ratio::from_integer(i128::from(area.0)),
+        Ratio::from_integer(i128::from(area.1)),
+    );
+    let mut count = 0;
+    for (idx, a) in hailstones.iter().enumerate() {
+        for b in &hailstones[idx + 1..] {
+            if let Some(crossing) = crossing_2d(a, b) {
+                if crossing.iter().all(|c| (min..=max).contains(c)) {
+                    count += 1;
+                }
+            }
+        }
+    }
+    Ok(count)
+}
+
+/// Cross product `u × v` of two vectors, written as the coefficients of the unknowns `P` and `V`
+/// of the rock in the linear system, with `u` or `v` being the unknown
+fn cross_coefficients(known: [BigRational; 3]) -> [[BigRational; 3]; 3] {
+    let zero = BigRational::from_integer(BigInt::from(0));
+    let [x, y, z] = known;
+    [
+        [zero.clone(), -z.clone(), y.clone()],
+        [z, zero.clone(), -x.clone()],
+        [-y, x, zero],
+    ]
+}
+
+/// The rock hits each hailstone `i` at some time `t`, so `P + t V = p_i + t v_i`, which means
+/// `P - p_i` and `V - v_i` are parallel: `(P - p_i) × (V - v_i) = 0`. Expanding the cross product
+/// leaves `P × V` as the only non-linear term, which is the same for all hailstones. Subtracting
+/// the equations of two hailstones `i` and `j` removes it:
+///
+/// `P × (v_j - v_i) + (p_j - p_i) × V = p_j × v_j - p_i × v_i`
+///
+/// Two such pairs give six linear equations for the six unknowns. The rock's position, as
+/// `[x, y, z]`, or `None` if the hailstones' paths don't pin it down.
+fn rock_position(a: &Hailstone, b: &Hailstone, c: &Hailstone) -> Option<[BigRational; 3]> {
+    let big = |n: i64| BigRational::from_integer(BigInt::from(n));
+    let vector = |v: Vector| v.map(big);
+    let sub = |u: Vector, v: Vector| [0, 1, 2].map(|i| big(u[i]) - big(v[i]));
+    let cross = |u: Vector, v: Vector| {
+        let [u, v] = [vector(u), vector(v)];
+        [
+            &u[1] * &v[2] - &u[2] * &v[1],
+            &u[2] * &v[0] - &u[0] * &v[2],
+            &u[0] * &v[1] - &u[1] * &v[0],
+        ]
+    };
+
+    let mut matrix = Vec::new();
+    let mut rhs = Vec::new();
+    for other in [b, c] {
+        // P × w = -(w × P), and u × V
+        let w = sub(other.velocity, a.velocity);
+        let u = sub(other.position, a.position);
+        let p_coefficients = cross_coefficients(w).map(|row| row.map(|c| -c));
+        let v_coefficients = cross_coefficients(u);
+        let a_cross = cross(a.position, a.velocity);
+        let other_cross = cross(other.position, other.velocity);
+        for axis in 0..3 {
+            let mut row = p_coefficients[axis].to_vec();
+            row.extend(v_coefficients[axis].iter().cloned());
+            matrix.push(row);
+            rhs.push(&other_cross[axis] - &a_cross[axis]);
+        }
+    }
+    let solution = linear::solve(matrix, rhs)?;
+    Some([
+        solution[0].clone(),
+        solution[1].clone(),
+        solution[2].clone(),
+    ])
+}
+
+fn part2<P: AsRef<Path>>(input: P) -> Result<i64> {
+    let hailstones = timed!("parse", read_hailstones(input))?;
+    // Some triples may have parallel paths that don't determine the rock, try the next ones then
+    let position = hailstones
+        .windows(3)
+        .find_map(|triple| rock_position(&triple[0], &triple[1], &triple[2]));
+    let Some(position) = position else {
+        bail!("The hailstones don't determine the rock's throw");
+    };
+    if !position.iter().all(|c| c.is_integer()) {
+        bail!("The rock would have to start between integer positions");
+    }
+    position
+        .iter()
+        .try_fold(0i64, |sum, c| sum.checked_add(c.to_integer().to_i64()?))
+        .context("The rock's position doesn't fit into 64 bits")
+}
+
+/// Solves part 2 by stating the throw as constraints for Z3: the rock hits each of the first three
+/// hailstones at some time `t_i >= 0`, so `P + t_i V = p_i + t_i v_i`. Three hailstones give nine
+/// equations for the nine unknowns.
+#[cfg(feature = "z3")]
+fn part2_z3<P: AsRef<Path>>(input: P) -> Result<i64> {
+    use advent_of_code_2023::smt::{self, Ast, Int};
+
+    let hailstones = timed!("parse", read_hailstones(input))?;
+    if hailstones.len() < 3 {
+        bail!("The hailstones don't determine the rock's throw");
+    }
+    let names = ["px", "py", "pz", "vx", "vy", "vz", "t0", "t1", "t2"];
+    let solution = timed!(
+        "z3",
+        smt::solve_integers(&names, |ctx, unknowns| {
+            let (position, rest) = unknowns.split_at(3);
+            let (velocity, times) = rest.split_at(3);
+            let int = |n: i64| Int::from_i64(ctx, n);
+            let mut constraints = Vec::new();
+            for (hailstone, t) in hailstones.iter().zip(times) {
+                constraints.push(t.ge(&int(0)));
+                for axis in 0..3 {
+                    let rock = &position[axis] + t * &velocity[axis];
+                    let stone = int(hailstone.position[axis]) + t * int(hailstone.velocity[axis]);
+                    constraints.push(rock._eq(&stone));
+                }
+            }
+            constraints
+        })
+    )?;
+    let Some(solution) = solution else {
+        bail!("The rock can't hit all hailstones");
+    };
+    solution[..3]
+        .iter()
+        .try_fold(0i64, |sum, c| sum.checked_add(*c))
+        .context("The rock's position doesn't fit into 64 bits")
+}
+
+struct Day24;
+
+impl AocDay for Day24 {
+    const YEAR: u16 = 2023;
+    const DAY: u8 = 24;
+
+    /// `--area MIN,MAX` counts the crossings in another test area.
+    fn part1(input: &Path) -> Result<Answer> {
+        let area = options().get("area")?.unwrap_or(TEST_AREA);
+        Ok(part1(input, area)?.into())
+    }
+
+    fn part2(input: &Path) -> Result<Answer> {
+        Ok(part2(input)?.into())
+    }
+
+    /// `--algo z3` lets the Z3 solver find the throw, if built with the `z3` feature.
+    #[cfg(feature = "z3")]
+    fn part2_alternatives() -> Vec<(&'static str, advent_of_code_2023::runner::Solver)> {
+        vec![("z3", |input| Ok(part2_z3(input)?.into()))]
+    }
+}
+
+fn main() -> Result<()> {
+    run::<Day24>()
+}
+
+#[cfg(test)]
+mod tests_day24 {
+    use super::*;
+    use advent_of_code_2023::test_helpers::create_example_file;
+    use indoc::indoc;
+
+    const EXAMPLE: &str = indoc! {"
+        19, 13, 30 @ -2,  1, -2
+        18, 19, 22 @ -1, -1, -2
+        20, 25, 34 @ -2, -2, -4
+        12, 31, 28 @ -1, -2, -1
+        20, 19, 15 @  1, -5, -3
+    "};
+
+    #[test]
+    fn test_example() {
+        let (dir, file) = create_example_file(EXAMPLE, None);
+        assert_eq!(part1(&file, TestArea(7, 27)).unwrap(), 2);
+        assert_eq!(part2(&file).unwrap(), 47);
+        drop(dir);
+    }
+
+    #[cfg(feature = "z3")]
+    #[test]
+    fn test_example_z3() {
+        let (dir, file) = create_example_file(EXAMPLE, None);
+        assert_eq!(part2_z3(&file).unwrap(), 47);
+        drop(dir);
+    }
+
+    #[test]
+    fn test_huge_position() {
+        // The rock stands still at 2^62 in all coordinates, which fit into 64 bits but their sum
+        // doesn't
+        let (dir, file) = create_example_file(
+            indoc! {"
+            4611686018427387903, 4611686018427387904, 4611686018427387904 @ 1, 0, 0
+            4611686018427387904, 4611686018427387902, 4611686018427387904 @ 0, 1, 0
+            4611686018427387904, 4611686018427387904, 4611686018427387901 @ 0, 0, 1
+            4611686018427387900, 4611686018427387900, 4611686018427387900 @ 1, 1, 1
+        "},
+            None,
+        );
+        assert_eq!(
+            part2(&file).unwrap_err().to_string(),
+            "The rock's position doesn't fit into 64 bits"
+        );
+        drop(dir);
+    }
+
+    #[test]
+    fn test_crossings() {
+        let (dir, file) = create_example_file(EXAMPLE, None);
+        let hailstones = read_hailstones(&file).unwrap();
+        let crossing = crossing_2d(&hailstones[0], &hailstones[1]).unwrap();
+        assert_eq!(
+            crossing,
+            [Ratio::new(14 * 3 + 1, 3), Ratio::new(15 * 3 + 1, 3)]
+        );
+        // Parallel paths
+        assert_eq!(crossing_2d(&hailstones[1], &hailstones[2]), None);
+        // Crossed in the past of the first hailstone
+        assert_eq!(crossing_2d(&hailstones[0], &hailstones[4]), None);
+        drop(dir);
+    }
+
+    #[test]
+    fn test_parse_hailstone() {
+        assert_eq!(
+            "19, 13, 30 @ -2,  1, -2".parse::<Hailstone>(),
+            Ok(Hailstone {
+                position: [19, 13, 30],
+                velocity: [-2, 1, -2]
+            })
+        );
+        assert_eq!(
+            "19, 13, 30".parse::<Hailstone>(),
+            Err(HailstoneError::MissingVelocity)
+        );
+        assert_eq!(
+            "19, 13 @ -2, 1, -2".parse::<Hailstone>(),
+            Err(HailstoneError::InvalidVector("19, 13".to_owned()))
+        );
+    }
+}
@@ -0,0 +1,129 @@
+use std::path::Path;
+
+use advent_of_code_2023::{
+    answer::Answer,
+    interner::Interner,
+    min_cut::find_cut,
+    read_lines,
+    runner::{options, run, AocDay},
+    timed,
+};
+use anyhow::{bail, Context, Result};
+
+/// The components connected by wires, as adjacency lists indexed by the interned names
+struct Wiring {
+    names: Interner,
+    adjacency: Vec<Vec<usize>>,
+}
+
+impl Wiring {
+    /// Reads lines like `jqt: rhn xhk nvd`, where the wires connect in both directions
+    fn read_input<P: AsRef<Path>>(input: P) -> Result<Self> {
+        let mut names = Interner::new();
+        let mut adjacency = Vec::<Vec<usize>>::new();
+        for (idx, line) in read_lines(input)?.enumerate() {
+            let line = line?;
+            let (component, others) = line
+                .split_once(':')
+                .with_context(|| format!("Expected ':' after the component in line {}", idx + 1))?;
+            let component = names.intern(component.trim());
+            for other in others.split_whitespace() {
+                let other = names.intern(other);
+                adjacency.resize(names.len(), Vec::new());
+                adjacency[component].push(other);
+                adjacency[other].push(component);
+            }
+        }
+        adjacency.resize(names.len(), Vec::new());
+        Ok(Wiring { names, adjacency })
+    }
+}
+
+/// Number of wires to disconnect according to the puzzle
+const CUT_SIZE: usize = 3;
+
+/// Product of the sizes of the two groups left after disconnecting `cut_size` wires
+fn part1<P: AsRef<Path>>(input: P, cut_size: usize) -> Result<usize> {
+    let wiring = timed!("parse", Wiring::read_input(input))?;
+    let Some(cut) = timed!("cut", find_cut(&wiring.adjacency, cut_size)) else {
+        bail!(
+            "There are no {} wires splitting the components into two groups",
+            cut_size
+        );
+    };
+    if options().flag("show-wires") {
+        for (a, b) in &cut.edges {
+            println!("{}/{}", wiring.names.name(*a), wiring.names.name(*b));
+        }
+    }
+    let (a, b) = cut.sizes();
+    Ok(a * b)
+}
+
+struct Day25;
+
+impl AocDay for Day25 {
+    const YEAR: u16 = 2023;
+    const DAY: u8 = 25;
+
+    /// `--cut N` disconnects N wires instead of 3, `--show-wires` prints them.
+    fn part1(input: &Path) -> Result<Answer> {
+        let cut_size = options().get("cut")?.unwrap_or(CUT_SIZE);
+        Ok(part1(input, cut_size)?.into())
+    }
+
+    /// There is no second puzzle on the last day
+    fn part2(_input: &Path) -> Result<Answer> {
+        Ok("Merry Christmas!".into())
+    }
+}
+
+fn main() -> Result<()> {
+    run::<Day25>()
+}
+
+#[cfg(test)]
+mod tests_day25 {
+    use super::*;
+    use advent_of_code_2023::test_helpers::create_example_file;
+    use indoc::indoc;
+
+    #[test]
+    fn test_example() {
+        let (dir, file) = create_example_file(
+            indoc! {"
+            jqt: rhn xhk nvd
+            rsh: frs pzl lsr
+            xhk: hfx
+            cmg: qnr nvd lhk bvb
+            rhn: xhk bvb hfx
+            bvb: xhk hfx
+            pzl: lsr hfx nvd
+            qnr: nvd
+            ntq: jqt hfx bvb xhk
+            nvd: lhk
+            lsr: lhk
+            rzs: qnr cmg lsr rsh
+            frs: qnr lhk lsr
+        "},
+            None,
+        );
+        assert_eq!(part1(&file, CUT_SIZE).unwrap(), 54);
+
+        let wiring = Wiring::read_input(&file).unwrap();
+        let cut = find_cut(&wiring.adjacency, CUT_SIZE).unwrap();
+        let mut wires = cut
+            .edges
+            .iter()
+            .map(|&(a, b)| {
+                let mut wire = [wiring.names.name(a), wiring.names.name(b)];
+                wire.sort();
+                wire.join("/")
+            })
+            .collect::<Vec<_>>();
+        wires.sort();
+        assert_eq!(wires, ["bvb/cmg", "hfx/pzl", "jqt/nvd"]);
+        assert!(part1(&file, 2).is_err());
+        drop(dir);
+    }
+}
@@ -0,0 +1,252 @@
+use std::{
+    fs::{self, File},
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+
+use crate::runner::options;
+
+const HEADER: &str = "aoc-checkpoint v1";
+const DEFAULT_INTERVAL: Duration = Duration::from_secs(30);
+const DEFAULT_DIR: &str = "checkpoints";
+
+/// Progress of a long running solver that can be stored on disk and picked up again later.
+///
+/// States are encoded as a flat list of whitespace-free tokens, which keeps the on-disk format
+/// trivial to write and to inspect by hand.
+pub trait Checkpoint: Sized {
+    fn encode(&self, tokens: &mut Vec<String>);
+    fn decode<'a, I: Iterator<Item = &'a str>>(tokens: &mut I) -> Result<Self>;
+}
+
+macro_rules! impl_checkpoint_for_numbers {
+    ($($t:ty),*) => {
+        $(
+            impl Checkpoint for $t {
+                fn encode(&self, tokens: &mut Vec<String>) {
+                    tokens.push(self.to_string());
+                }
+
+                fn decode<'a, I: Iterator<Item = &'a str>>(tokens: &mut I) -> Result<Self> {
+                    let token = tokens.next().ok_or_else(|| anyhow!("Truncated checkpoint"))?;
+                    <$t>::from_str(token)
+                        .map_err(|e| anyhow!("Invalid checkpoint value {:?}: {}", token, e))
+                }
+            }
+        )*
+    };
+}
+
+impl_checkpoint_for_numbers!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+impl Checkpoint for bool {
+    fn encode(&self, tokens: &mut Vec<String>) {
+        (*self as u8).encode(tokens)
+    }
+
+    fn decode<'a, I: Iterator<Item = &'a str>>(tokens: &mut I) -> Result<Self> {
+        match u8::decode(tokens)? {
+            0 => Ok(false),
+            1 => Ok(true),
+            v => bail!("Invalid checkpoint bool {}", v),
+        }
+    }
+}
+
+impl<T: Checkpoint> Checkpoint for Vec<T> {
+    fn encode(&self, tokens: &mut Vec<String>) {
+        self.len().encode(tokens);
+        self.iter().for_each(|item| item.encode(tokens));
+    }
+
+    fn decode<'a, I: Iterator<Item = &'a str>>(tokens: &mut I) -> Result<Self> {
+        let len = usize::decode(tokens)?;
+        (0..len).map(|_| T::decode(tokens)).collect()
+    }
+}
+
+impl<A: Checkpoint, B: Checkpoint> Checkpoint for (A, B) {
+    fn encode(&self, tokens: &mut Vec<String>) {
+        self.0.encode(tokens);
+        self.1.encode(tokens);
+    }
+
+    fn decode<'a, I: Iterator<Item = &'a str>>(tokens: &mut I) -> Result<Self> {
+        Ok((A::decode(tokens)?, B::decode(tokens)?))
+    }
+}
+
+pub fn to_string<T: Checkpoint>(state: &T) -> String {
+    let mut tokens = Vec::new();
+    state.encode(&mut tokens);
+    format!("{}\n{}\n", HEADER, tokens.join(" "))
+}
+
+/// 64 bit FNV-1a hash of the input, which identifies the input a checkpoint belongs to. Unlike
+/// the std hashers it is guaranteed to stay the same across Rust versions.
+fn fingerprint(content: &[u8]) -> u64 {
+    content.iter().fold(0xcbf29ce484222325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+    })
+}
+
+pub fn from_str<T: Checkpoint>(s: &str) -> Result<T> {
+    let (header, body) = s.split_once('\n').unwrap_or((s, ""));
+    if header != HEADER {
+        bail!("Not a checkpoint file");
+    }
+    let mut tokens = body.split_whitespace();
+    let state = T::decode(&mut tokens)?;
+    if tokens.next().is_some() {
+        bail!("Trailing data in checkpoint");
+    }
+    Ok(state)
+}
+
+/// Periodically persists the progress of a solver.
+///
+/// Checkpoints are stored in `checkpoints/<name>.ckpt`, or in the directory given with
+/// `--checkpoint-dir DIR`. They are only picked up again when the runner was started with
+/// `--resume`; `--checkpoint-interval SECONDS` controls how often the state is written.
+///
+/// Each checkpoint records a fingerprint of the input it was made for, and resuming with any
+/// other input fails instead of continuing from a foreign state.
+pub struct Checkpointer {
+    path: PathBuf,
+    fingerprint: u64,
+    interval: Duration,
+    last_save: Instant,
+    resume: bool,
+}
+
+impl Checkpointer {
+    /// Checkpointer for a solver working on the given input
+    pub fn new(name: &str, input: &Path) -> Result<Self> {
+        let dir = options()
+            .get::<PathBuf>("checkpoint-dir")?
+            .unwrap_or_else(|| PathBuf::from(DEFAULT_DIR));
+        Self::in_dir(&dir, name, input)
+    }
+
+    /// Same as [`Checkpointer::new`], but storing the checkpoint in `dir`
+    pub fn in_dir(dir: &Path, name: &str, input: &Path) -> Result<Self> {
+        let options = options();
+        let interval = options
+            .get::<u64>("checkpoint-interval")?
+            .map(Duration::from_secs)
+            .unwrap_or(DEFAULT_INTERVAL);
+        let content =
+            fs::read(input).with_context(|| format!("Could not read input {}", input.display()))?;
+        Ok(Self {
+            path: dir.join(format!("{}.ckpt", name)),
+            fingerprint: fingerprint(&content),
+            interval,
+            last_save: Instant::now(),
+            resume: options.flag("resume"),
+        })
+    }
+
+    /// Loads the stored state if resuming was requested and a checkpoint exists.
+    pub fn load<T: Checkpoint>(&self) -> Result<Option<T>> {
+        if !self.resume || !self.path.is_file() {
+            return Ok(None);
+        }
+        let content = fs::read_to_string(&self.path)?;
+        let (fingerprint, state) = from_str::<(u64, T)>(&content)
+            .with_context(|| format!("Could not resume from {}", self.path.display()))?;
+        if fingerprint != self.fingerprint {
+            bail!(
+                "Checkpoint {} was made for a different input, remove it or run without --resume",
+                self.path.display()
+            );
+        }
+        Ok(Some(state))
+    }
+
+    /// Stores the state if the checkpoint interval has passed since the last save.
+    pub fn maybe_save<T: Checkpoint>(&mut self, state: &T) -> Result<()> {
+        if self.last_save.elapsed() >= self.interval {
+            self.save(state)?;
+        }
+        Ok(())
+    }
+
+    pub fn save<T: Checkpoint>(&mut self, state: &T) -> Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        // Write to a temporary file first, so an interrupted save never destroys the previous
+        // checkpoint
+        let tmp_path = self.path.with_extension("tmp");
+        let mut tokens = Vec::new();
+        self.fingerprint.encode(&mut tokens);
+        state.encode(&mut tokens);
+        let mut writer = BufWriter::new(File::create(&tmp_path)?);
+        write!(writer, "{}\n{}\n", HEADER, tokens.join(" "))?;
+        writer.flush()?;
+        drop(writer);
+        fs::rename(&tmp_path, &self.path)?;
+        self.last_save = Instant::now();
+        Ok(())
+    }
+
+    /// Removes the checkpoint once the solver has finished.
+    pub fn finish(self) -> Result<()> {
+        if self.path.is_file() {
+            fs::remove_file(&self.path)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip() {
+        let state: (usize, Vec<(i32, bool)>) = (17, vec![(-3, true), (5, false)]);
+        let encoded = to_string(&state);
+        assert_eq!(encoded, "aoc-checkpoint v1\n17 2 -3 1 5 0\n");
+        assert_eq!(
+            from_str::<(usize, Vec<(i32, bool)>)>(&encoded).unwrap(),
+            state
+        );
+    }
+
+    #[test]
+    fn test_invalid_checkpoints() {
+        assert!(from_str::<usize>("something else\n17\n").is_err());
+        assert!(from_str::<(usize, usize)>("aoc-checkpoint v1\n17\n").is_err());
+        assert!(from_str::<usize>("aoc-checkpoint v1\n17 18\n").is_err());
+        assert!(from_str::<usize>("aoc-checkpoint v1\n-1\n").is_err());
+    }
+
+    #[test]
+    fn test_resume_only_same_input() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("input.txt");
+        fs::write(&input, "1 2 3\n").unwrap();
+        let mut checkpointer = Checkpointer::in_dir(dir.path(), "test", &input).unwrap();
+        checkpointer.save(&(5usize, 7usize)).unwrap();
+        let saved = fs::read_to_string(dir.path().join("test.ckpt")).unwrap();
+        assert_eq!(
+            from_str::<(u64, (usize, usize))>(&saved).unwrap(),
+            (fingerprint(b"1 2 3\n"), (5, 7))
+        );
+
+        checkpointer.resume = true;
+        assert_eq!(checkpointer.load::<(usize, usize)>().unwrap(), Some((5, 7)));
+        fs::write(&input, "1 2 4\n").unwrap();
+        let mut changed = Checkpointer::in_dir(dir.path(), "test", &input).unwrap();
+        changed.resume = true;
+        assert!(changed.load::<(usize, usize)>().is_err());
+
+        changed.finish().unwrap();
+        assert!(!dir.path().join("test.ckpt").exists());
+    }
+}
@@ -0,0 +1,103 @@
+use std::{collections::HashMap, str::FromStr};
+
+use anyhow::{anyhow, bail, Result};
+
+/// Minimal command line parser shared by all day binaries.
+///
+/// Options are given as `--name value`, `--name=value` or just `--name` for boolean flags.
+/// Anything not starting with `--` is collected as a positional argument.
+#[derive(Debug, Clone, Default)]
+pub struct Options {
+    named: HashMap<String, Option<String>>,
+    positional: Vec<String>,
+}
+
+impl Options {
+    pub fn parse<I>(args: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let mut options = Options::default();
+        let mut args = args.into_iter().peekable();
+        while let Some(arg) = args.next() {
+            if let Some(name) = arg.strip_prefix("--") {
+                if name.is_empty() {
+                    bail!("Empty option name");
+                }
+                let (name, value) = match name.split_once('=') {
+                    Some((name, value)) => (name.to_owned(), Some(value.to_owned())),
+                    None => {
+                        let value = args.next_if(|next| !next.starts_with("--"));
+                        (name.to_owned(), value)
+                    }
+                };
+                options.named.insert(name, value);
+            } else {
+                options.positional.push(arg);
+            }
+        }
+        Ok(options)
+    }
+
+    /// Parses the options the current process was started with, skipping the program name.
+    pub fn from_env() -> Result<Self> {
+        Self::parse(std::env::args().skip(1))
+    }
+
+    pub fn flag(&self, name: &str) -> bool {
+        self.named.contains_key(name)
+    }
+
+    pub fn get<T>(&self, name: &str) -> Result<Option<T>>
+    where
+        T: FromStr,
+        T::Err: std::fmt::Display,
+    {
+        match self.named.get(name) {
+            None => Ok(None),
+            Some(None) => bail!("Option --{} requires a value", name),
+            Some(Some(value)) => value
+                .parse()
+                .map(Some)
+                .map_err(|e| anyhow!("Invalid value {:?} for --{}: {}", value, name, e)),
+        }
+    }
+
+    pub fn positional(&self) -> &[String] {
+        &self.positional
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Options {
+        Options::parse(args.iter().map(|s| s.to_string())).unwrap()
+    }
+
+    #[test]
+    fn test_named_options() {
+        let options = parse(&["--year", "2024", "--input=foo.txt", "--verbose"]);
+        assert_eq!(options.get::<u16>("year").unwrap(), Some(2024));
+        assert_eq!(
+            options.get::<String>("input").unwrap(),
+            Some("foo.txt".to_owned())
+        );
+        assert!(options.flag("verbose"));
+        assert!(!options.flag("quiet"));
+        assert!(options.get::<String>("verbose").is_err());
+    }
+
+    #[test]
+    fn test_positional_and_invalid_values() {
+        let options = parse(&["run", "--year", "twenty", "--flag", "--other", "x"]);
+        assert_eq!(options.positional(), &["run".to_owned()]);
+        assert!(options.get::<u16>("year").is_err());
+        assert!(options.flag("flag"));
+        assert_eq!(
+            options.get::<String>("other").unwrap(),
+            Some("x".to_owned())
+        );
+    }
+}
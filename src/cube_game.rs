@@ -0,0 +1,257 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::str::FromStr;
+use std::sync::Mutex;
+
+use lazy_static::lazy_static;
+use thiserror::Error;
+
+// Cube games of day 2: an elf draws handfuls of colored cubes from a bag.
+//
+// From the example:
+// Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+// A game has rounds which are separated by ;.
+// Each round contains several Draws, which are a color and an amount.
+
+/// Why a game, draw or bag description could not be parsed
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    #[error("Missing ':' between the game id and the rounds")]
+    MissingColon,
+    #[error("Invalid game id {0:?}, expected \"Game <number>\"")]
+    InvalidId(String),
+    #[error("Invalid draw {0:?}, expected \"<amount> <color>\"")]
+    InvalidDraw(String),
+}
+
+lazy_static! {
+    static ref COLOR_NAMES: Mutex<HashSet<&'static str>> = Mutex::new(HashSet::new());
+}
+
+/// Interned color name, so any color can appear in the input while colors stay cheap to copy
+/// and compare.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Color(&'static str);
+
+impl Color {
+    pub fn new(name: &str) -> Self {
+        let mut names = COLOR_NAMES.lock().unwrap();
+        match names.get(name) {
+            Some(interned) => Color(interned),
+            None => {
+                // Leaking is fine, there is only a handful of distinct colors
+                let interned: &'static str = Box::leak(name.to_owned().into_boxed_str());
+                names.insert(interned);
+                Color(interned)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Draw {
+    pub color: Color,
+    pub amount: usize,
+}
+
+impl FromStr for Draw {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, ParseError> {
+        let invalid = || ParseError::InvalidDraw(s.trim().to_owned());
+        let (amount, color) = s.trim().split_once(' ').ok_or_else(invalid)?;
+        Ok(Draw {
+            color: Color::new(color.trim()),
+            amount: amount.parse().map_err(|_| invalid())?,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Round {
+    pub draws: Vec<Draw>,
+}
+
+impl Round {
+    pub fn can_be_drawn_from_bag(&self, bag: &Bag) -> bool {
+        self.draws
+            .iter()
+            .all(|draw| bag.count(draw.color) >= draw.amount)
+    }
+
+    pub fn grow_bag_to_make_round_possible(&self, bag: &mut Bag) {
+        for draw in &self.draws {
+            if draw.amount > bag.count(draw.color) {
+                bag.set(draw.color, draw.amount);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Game {
+    pub rounds: Vec<Round>,
+    pub id: usize,
+}
+
+impl FromStr for Game {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, ParseError> {
+        let (descriptor, game_content) = s.split_once(':').ok_or(ParseError::MissingColon)?;
+        let id = descriptor
+            .strip_prefix("Game ")
+            .and_then(|id| id.trim().parse().ok())
+            .ok_or_else(|| ParseError::InvalidId(descriptor.to_owned()))?;
+        let rounds = game_content
+            .split(';')
+            .map(|round| {
+                let draws = round
+                    .split(',')
+                    .map(str::parse)
+                    .collect::<Result<Vec<Draw>, _>>()?;
+                Ok(Round { draws })
+            })
+            .collect::<Result<Vec<Round>, _>>()?;
+
+        Ok(Game { rounds, id })
+    }
+}
+
+impl Game {
+    pub fn can_be_drawn_from_bag(&self, bag: &Bag) -> bool {
+        self.rounds
+            .iter()
+            .all(|round| round.can_be_drawn_from_bag(bag))
+    }
+
+    /// Smallest bag that every round of the game could have been drawn from
+    pub fn get_min_bag(&self) -> Bag {
+        let mut bag = Bag::default();
+        for round in &self.rounds {
+            round.grow_bag_to_make_round_possible(&mut bag);
+        }
+        bag
+    }
+
+    pub fn colors(&self) -> impl Iterator<Item = Color> + '_ {
+        self.rounds
+            .iter()
+            .flat_map(|round| round.draws.iter().map(|draw| draw.color))
+    }
+}
+
+/// Number of cubes per color, colors that are not in the bag have no cubes.
+#[derive(Debug, Clone, Default)]
+pub struct Bag(HashMap<Color, usize>);
+
+impl Bag {
+    pub fn set(&mut self, color: Color, amount: usize) {
+        self.0.insert(color, amount);
+    }
+
+    pub fn count(&self, color: Color) -> usize {
+        self.0.get(&color).copied().unwrap_or(0)
+    }
+
+    /// Product of the cube counts of the given colors
+    pub fn power<'a, I>(&self, colors: I) -> usize
+    where
+        I: IntoIterator<Item = &'a Color>,
+    {
+        colors.into_iter().map(|color| self.count(*color)).product()
+    }
+}
+
+/// Parses bag contents in the format of a round, e.g. `12 red, 13 green, 14 blue`.
+impl FromStr for Bag {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, ParseError> {
+        let mut bag = Bag::default();
+        for draw in s.split(',').map(str::parse::<Draw>) {
+            let draw = draw?;
+            bag.set(draw.color, draw.amount);
+        }
+        Ok(bag)
+    }
+}
+
+/// Games that are possible if the cubes were drawn from the given bag
+pub fn possible_games<'a>(games: &'a [Game], bag: &'a Bag) -> impl Iterator<Item = &'a Game> {
+    games.iter().filter(|game| game.can_be_drawn_from_bag(bag))
+}
+
+/// All colors that appear in any of the games, in a stable order
+pub fn all_colors(games: &[Game]) -> BTreeSet<Color> {
+    games.iter().flat_map(Game::colors).collect()
+}
+
+/// Smallest bag per game, along with the game id
+pub fn min_bags(games: &[Game]) -> impl Iterator<Item = (usize, Bag)> + '_ {
+    games.iter().map(|game| (game.id, game.get_min_bag()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn games() -> Vec<Game> {
+        [
+            "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green",
+            "Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red",
+        ]
+        .iter()
+        .map(|line| line.parse().unwrap())
+        .collect()
+    }
+
+    #[test]
+    fn test_possible_games() {
+        let games = games();
+        let bag = "12 red, 13 green, 14 blue".parse().unwrap();
+        let ids = possible_games(&games, &bag)
+            .map(|game| game.id)
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec![1]);
+    }
+
+    #[test]
+    fn test_min_bags() {
+        let games = games();
+        let colors = all_colors(&games);
+        assert_eq!(colors.len(), 3);
+        let powers = min_bags(&games)
+            .map(|(id, bag)| (id, bag.power(&colors)))
+            .collect::<Vec<_>>();
+        assert_eq!(powers, vec![(1, 48), (3, 1560)]);
+        let (_, bag) = min_bags(&games).next().unwrap();
+        assert_eq!(bag.count(Color::new("blue")), 6);
+        assert_eq!(bag.count(Color::new("purple")), 0);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let parse = |line: &str| line.parse::<Game>().unwrap_err();
+        assert_eq!(parse("Game 1 3 blue"), ParseError::MissingColon);
+        assert_eq!(
+            parse("Round 1: 3 blue"),
+            ParseError::InvalidId("Round 1".to_owned())
+        );
+        assert_eq!(
+            parse("Game x: 3 blue"),
+            ParseError::InvalidId("Game x".to_owned())
+        );
+        assert_eq!(
+            parse("Game 1: 3 blue; red"),
+            ParseError::InvalidDraw("red".to_owned())
+        );
+        assert_eq!(
+            parse("Game 1: 3 blue, three red"),
+            ParseError::InvalidDraw("three red".to_owned())
+        );
+        assert_eq!(
+            "12 red,".parse::<Bag>().unwrap_err(),
+            ParseError::InvalidDraw(String::new())
+        );
+    }
+}
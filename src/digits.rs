@@ -0,0 +1,55 @@
+// Finding digits in the calibration lines of day 1, which may be spelled out and may overlap,
+// like `twone` or `eightwo`.
+
+const SPELLED_DIGITS: [&[u8]; 9] = [
+    b"one", b"two", b"three", b"four", b"five", b"six", b"seven", b"eight", b"nine",
+];
+
+/// Digit that starts at the given index of the line, either as a plain digit or spelled out
+fn digit_at(line: &[u8], idx: usize, allow_spelled: bool) -> Option<u32> {
+    if line[idx].is_ascii_digit() {
+        return Some(u32::from(line[idx] - b'0'));
+    }
+    if !allow_spelled {
+        return None;
+    }
+    let rest = &line[idx..];
+    SPELLED_DIGITS
+        .iter()
+        .position(|word| rest.starts_with(word))
+        .map(|pos| pos as u32 + 1)
+}
+
+/// Finds the first and last digit of a line in a single scan from either end. Spelled out digits
+/// like `one` only count if `allow_spelled` is set.
+///
+/// Scanning each direction separately handles overlapping spelled digits like `twone` or
+/// `eightwo` without any preprocessing.
+pub fn first_and_last_digit(line: &[u8], allow_spelled: bool) -> Option<(u32, u32)> {
+    let first = (0..line.len()).find_map(|idx| digit_at(line, idx, allow_spelled))?;
+    let last = (0..line.len())
+        .rev()
+        .find_map(|idx| digit_at(line, idx, allow_spelled))?;
+    Some((first, last))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_overlapping_spelled_digits() {
+        assert_eq!(first_and_last_digit(b"twone", true), Some((2, 1)));
+        assert_eq!(first_and_last_digit(b"eightwo", true), Some((8, 2)));
+        assert_eq!(first_and_last_digit(b"xoneightx", true), Some((1, 8)));
+        assert_eq!(first_and_last_digit(b"eightwo7", false), Some((7, 7)));
+        assert_eq!(first_and_last_digit(b"nothing", true), None);
+    }
+
+    #[test]
+    fn test_single_digit() {
+        assert_eq!(first_and_last_digit(b"treb7uchet", false), Some((7, 7)));
+        assert_eq!(first_and_last_digit(b"seven", true), Some((7, 7)));
+        assert_eq!(first_and_last_digit(b"", true), None);
+    }
+}
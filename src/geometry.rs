@@ -0,0 +1,63 @@
+use num::integer::gcd;
+
+/// Twice the signed area of a polygon with the given corners, by the shoelace formula.
+///
+/// The area is positive if the corners go counter-clockwise in a coordinate system whose y axis
+/// points up, i.e. clockwise on a grid whose rows go down. Twice the area is always an integer.
+pub fn double_signed_area(corners: &[(i64, i64)]) -> i64 {
+    corners
+        .iter()
+        .zip(corners.iter().cycle().skip(1))
+        .map(|((x1, y1), (x2, y2))| x1 * y2 - x2 * y1)
+        .sum()
+}
+
+/// Number of integer points on the edges of the polygon, including the corners
+pub fn boundary_points(corners: &[(i64, i64)]) -> i64 {
+    corners
+        .iter()
+        .zip(corners.iter().cycle().skip(1))
+        .map(|((x1, y1), (x2, y2))| gcd(x2 - x1, y2 - y1))
+        .sum()
+}
+
+/// Number of integer points strictly inside a simple polygon with integer corners.
+///
+/// By Pick's theorem, `area = interior + boundary / 2 - 1` for such polygons.
+pub fn interior_points(corners: &[(i64, i64)]) -> i64 {
+    (double_signed_area(corners).abs() - boundary_points(corners) + 2) / 2
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rectangle() {
+        let corners = [(0, 0), (4, 0), (4, 3), (0, 3)];
+        assert_eq!(double_signed_area(&corners), 24);
+        assert_eq!(boundary_points(&corners), 14);
+        assert_eq!(interior_points(&corners), 3 * 2);
+
+        let reversed = corners.iter().rev().cloned().collect::<Vec<_>>();
+        assert_eq!(double_signed_area(&reversed), -24);
+        assert_eq!(interior_points(&reversed), 6);
+    }
+
+    #[test]
+    fn test_triangle() {
+        let corners = [(0, 0), (4, 0), (0, 4)];
+        assert_eq!(double_signed_area(&corners), 16);
+        assert_eq!(boundary_points(&corners), 12);
+        assert_eq!(interior_points(&corners), 3);
+    }
+
+    #[test]
+    fn test_collinear_corners() {
+        // Corners in the middle of an edge don't change anything
+        let corners = [(0, 0), (2, 0), (4, 0), (4, 3), (0, 3)];
+        assert_eq!(double_signed_area(&corners), 24);
+        assert_eq!(boundary_points(&corners), 14);
+        assert_eq!(double_signed_area(&[]), 0);
+    }
+}
@@ -0,0 +1,246 @@
+use std::ops::{Index, IndexMut};
+
+use anyhow::{bail, Result};
+
+/// One of the four directions on a grid, where up means decreasing `y`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Direction {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Direction {
+    /// All directions, clockwise starting at the top
+    pub const ALL: [Direction; 4] = [
+        Direction::Up,
+        Direction::Right,
+        Direction::Down,
+        Direction::Left,
+    ];
+
+    /// Change of `(y, x)` when taking a step in this direction
+    pub fn delta(self) -> (isize, isize) {
+        match self {
+            Direction::Up => (-1, 0),
+            Direction::Right => (0, 1),
+            Direction::Down => (1, 0),
+            Direction::Left => (0, -1),
+        }
+    }
+
+    pub fn opposite(self) -> Self {
+        self.turn_right().turn_right()
+    }
+
+    pub fn turn_right(self) -> Self {
+        Self::ALL[(self as usize + 1) % 4]
+    }
+
+    pub fn turn_left(self) -> Self {
+        Self::ALL[(self as usize + 3) % 4]
+    }
+
+    pub fn is_vertical(self) -> bool {
+        matches!(self, Direction::Up | Direction::Down)
+    }
+
+    /// Clockwise rotation from [`Direction::Up`], in degrees
+    pub fn degrees(self) -> u16 {
+        self as u16 * 90
+    }
+}
+
+/// Rectangular field of cells, stored row by row and indexed by `(y, x)`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Self
+    where
+        T: Clone,
+    {
+        Grid {
+            width,
+            height,
+            cells: vec![fill; width * height],
+        }
+    }
+
+    /// Builds a grid from its rows, which all need to have the same length.
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Result<Self> {
+        let height = rows.len();
+        let width = rows.first().map_or(0, Vec::len);
+        if let Some(y) = rows.iter().position(|row| row.len() != width) {
+            bail!("Row {} has {} cells, expected {}", y, rows[y].len(), width);
+        }
+        Ok(Grid {
+            width,
+            height,
+            cells: rows.into_iter().flatten().collect(),
+        })
+    }
+
+    /// Parses a grid with one character per cell, e.g. the lines of a puzzle input.
+    pub fn parse<I, S, F>(lines: I, mut cell: F) -> Result<Self>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+        F: FnMut(char) -> Result<T>,
+    {
+        let rows = lines
+            .into_iter()
+            .map(|line| line.as_ref().chars().map(&mut cell).collect())
+            .collect::<Result<Vec<_>>>()?;
+        Self::from_rows(rows)
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, y: usize, x: usize) -> bool {
+        y < self.height && x < self.width
+    }
+
+    pub fn get(&self, y: usize, x: usize) -> Option<&T> {
+        self.contains(y, x).then(|| &self.cells[y * self.width + x])
+    }
+
+    pub fn get_mut(&mut self, y: usize, x: usize) -> Option<&mut T> {
+        self.contains(y, x)
+            .then(|| &mut self.cells[y * self.width + x])
+    }
+
+    /// The cell at a position of the grid repeated infinitely in all directions, e.g. `(-1, 0)`
+    /// is the first cell of the last row.
+    pub fn get_wrapping(&self, y: isize, x: isize) -> &T {
+        let y = y.rem_euclid(self.height as isize) as usize;
+        let x = x.rem_euclid(self.width as isize) as usize;
+        &self.cells[y * self.width + x]
+    }
+
+    /// Neighbor of a position in the given direction, if it is still inside the grid.
+    pub fn step(&self, y: usize, x: usize, direction: Direction) -> Option<(usize, usize)> {
+        let (dy, dx) = direction.delta();
+        let y = y.checked_add_signed(dy)?;
+        let x = x.checked_add_signed(dx)?;
+        self.contains(y, x).then_some((y, x))
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        // chunks panics on a zero chunk size, which an empty grid would otherwise request
+        self.cells.chunks(self.width.max(1))
+    }
+
+    /// All cells as `(y, x, cell)`, row by row
+    pub fn iter(&self) -> impl Iterator<Item = (usize, usize, &T)> {
+        self.cells
+            .iter()
+            .enumerate()
+            .map(|(idx, cell)| (idx / self.width, idx % self.width, cell))
+    }
+
+    pub fn map<U, F>(&self, f: F) -> Grid<U>
+    where
+        F: FnMut(&T) -> U,
+    {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, (y, x): (usize, usize)) -> &T {
+        self.get(y, x).unwrap_or_else(|| {
+            panic!(
+                "Position ({}, {}) is outside of the {}x{} grid",
+                y, x, self.height, self.width
+            )
+        })
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, (y, x): (usize, usize)) -> &mut T {
+        let (height, width) = (self.height, self.width);
+        self.get_mut(y, x).unwrap_or_else(|| {
+            panic!(
+                "Position ({}, {}) is outside of the {}x{} grid",
+                y, x, height, width
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::anyhow;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_and_index() {
+        let mut grid = Grid::parse(["#..", ".#."], |c| match c {
+            '#' => Ok(true),
+            '.' => Ok(false),
+            c => Err(anyhow!("Invalid cell {}", c)),
+        })
+        .unwrap();
+        assert_eq!((grid.height(), grid.width()), (2, 3));
+        assert!(grid[(0, 0)] && grid[(1, 1)] && !grid[(1, 2)]);
+        assert_eq!(grid.get(2, 0), None);
+        assert!(*grid.get_wrapping(-2, 3) && *grid.get_wrapping(3, -2));
+        assert!(!grid.get_wrapping(-1, 0));
+
+        grid[(1, 2)] = true;
+        assert_eq!(
+            grid.iter()
+                .filter(|(_, _, cell)| **cell)
+                .map(|(y, x, _)| (y, x))
+                .collect::<Vec<_>>(),
+            vec![(0, 0), (1, 1), (1, 2)]
+        );
+        assert_eq!(grid.rows().nth(1), Some(&[false, true, true][..]));
+        assert_eq!(grid.map(|&cell| cell as u8)[(1, 2)], 1);
+
+        assert!(Grid::parse(["#x"], |_| Err::<bool, _>(anyhow!("nope"))).is_err());
+    }
+
+    #[test]
+    fn test_directions() {
+        assert_eq!(Direction::Up.turn_right(), Direction::Right);
+        assert_eq!(Direction::Up.turn_left(), Direction::Left);
+        assert_eq!(Direction::Left.opposite(), Direction::Right);
+        assert_eq!(Direction::Left.degrees(), 270);
+        assert!(Direction::Down.is_vertical() && !Direction::Right.is_vertical());
+
+        let grid = Grid::new(3, 2, 0);
+        assert_eq!(grid.step(0, 0, Direction::Right), Some((0, 1)));
+        assert_eq!(grid.step(0, 0, Direction::Up), None);
+        assert_eq!(grid.step(1, 2, Direction::Down), None);
+        assert_eq!(grid.step(1, 2, Direction::Up), Some((0, 2)));
+    }
+
+    #[test]
+    fn test_ragged_rows() {
+        assert!(Grid::from_rows(vec![vec![1, 2], vec![3]]).is_err());
+        let empty = Grid::<u8>::from_rows(Vec::new()).unwrap();
+        assert_eq!(empty.iter().count(), 0);
+        assert_eq!(empty.rows().count(), 0);
+    }
+}
@@ -0,0 +1,247 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    process::Command,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Context, Result};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+
+/// Location of the timing history, relative to the working directory (next to `input/`).
+pub fn history_path() -> PathBuf {
+    PathBuf::from(".aoc").join("timings.jsonl")
+}
+
+/// A single timed run of one part, as stored in the timing history.
+///
+/// The history is a JSON lines file, one record per line, so new runs can simply be appended.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimingRecord {
+    /// Seconds since the Unix epoch
+    pub timestamp: u64,
+    /// Short hash of the checked out commit, with a `-dirty` suffix for uncommitted changes
+    pub commit: String,
+    /// `release` or `debug`, timings of both are not comparable
+    pub profile: String,
+    pub year: u16,
+    pub day: u8,
+    pub part: u8,
+    pub algorithm: String,
+    pub total_ns: u64,
+}
+
+impl TimingRecord {
+    pub fn total(&self) -> Duration {
+        Duration::from_nanos(self.total_ns)
+    }
+}
+
+pub fn now_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+pub fn current_profile() -> &'static str {
+    if cfg!(debug_assertions) {
+        "debug"
+    } else {
+        "release"
+    }
+}
+
+/// Identifies the code that is being timed, or `unknown` outside of a git checkout.
+pub fn current_commit() -> String {
+    let git = |args: &[&str]| {
+        Command::new("git")
+            .args(args)
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_owned())
+    };
+    match git(&["rev-parse", "--short", "HEAD"]) {
+        Some(hash) if !hash.is_empty() => {
+            // Untracked files don't change the timed code, so they don't make the tree dirty
+            let dirty = git(&["status", "--porcelain", "--untracked-files=no"])
+                .is_some_and(|status| !status.is_empty());
+            if dirty {
+                format!("{}-dirty", hash)
+            } else {
+                hash
+            }
+        }
+        _ => "unknown".to_owned(),
+    }
+}
+
+pub fn append<P: AsRef<Path>>(path: P, records: &[TimingRecord]) -> Result<()> {
+    let path = path.as_ref();
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("Could not open {}", path.display()))?;
+    let mut lines = String::new();
+    for record in records {
+        lines.push_str(&serde_json::to_string(record)?);
+        lines.push('\n');
+    }
+    // One write per batch, so concurrent runs don't interleave within a line
+    file.write_all(lines.as_bytes())?;
+    Ok(())
+}
+
+/// Loads all records from the history, which is empty if nothing was recorded yet.
+pub fn load<P: AsRef<Path>>(path: P) -> Result<Vec<TimingRecord>> {
+    let path = path.as_ref();
+    if !path.is_file() {
+        return Ok(Vec::new());
+    }
+    let reader = BufReader::new(fs::File::open(path)?);
+    let mut records = Vec::new();
+    for (idx, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        records.push(
+            serde_json::from_str(&line)
+                .with_context(|| format!("Invalid record in {}:{}", path.display(), idx + 1))?,
+        );
+    }
+    Ok(records)
+}
+
+/// Timings of all runs of one commit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrendPoint {
+    pub commit: String,
+    pub first_run: u64,
+    pub runs: usize,
+    pub best: Duration,
+}
+
+/// How the timings of one part with one algorithm evolved over the commits.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Trend {
+    pub year: u16,
+    pub day: u8,
+    pub part: u8,
+    pub algorithm: String,
+    pub profile: String,
+    /// Ordered by the first run of each commit
+    pub points: Vec<TrendPoint>,
+}
+
+/// Groups the records by day, part, algorithm and profile, and summarizes each commit by its best
+/// run, which is the least noisy measure for comparing refactors.
+pub fn trends(records: &[TimingRecord]) -> Vec<Trend> {
+    records
+        .iter()
+        .into_group_map_by(|r| {
+            (
+                r.year,
+                r.day,
+                r.part,
+                r.algorithm.clone(),
+                r.profile.clone(),
+            )
+        })
+        .into_iter()
+        .sorted_by(|(a, _), (b, _)| a.cmp(b))
+        .map(|((year, day, part, algorithm, profile), runs)| {
+            let points = runs
+                .into_iter()
+                .into_group_map_by(|r| r.commit.clone())
+                .into_iter()
+                .map(|(commit, runs)| TrendPoint {
+                    commit,
+                    first_run: runs.iter().map(|r| r.timestamp).min().unwrap_or(0),
+                    runs: runs.len(),
+                    best: runs.iter().map(|r| r.total()).min().unwrap_or_default(),
+                })
+                .sorted_by_key(|point| (point.first_run, point.commit.clone()))
+                .collect();
+            Trend {
+                year,
+                day,
+                part,
+                algorithm,
+                profile,
+                points,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(commit: &str, timestamp: u64, part: u8, total_ns: u64) -> TimingRecord {
+        TimingRecord {
+            timestamp,
+            commit: commit.to_owned(),
+            profile: "release".to_owned(),
+            year: 2023,
+            day: 16,
+            part,
+            algorithm: "default".to_owned(),
+            total_ns,
+        }
+    }
+
+    #[test]
+    fn test_append_and_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested").join("timings.jsonl");
+        assert_eq!(load(&path).unwrap(), Vec::new());
+
+        let records = vec![record("abc1234", 10, 1, 500), record("abc1234", 10, 2, 900)];
+        append(&path, &records[..1]).unwrap();
+        append(&path, &records[1..]).unwrap();
+        assert_eq!(load(&path).unwrap(), records);
+
+        fs::write(&path, "{\"not\": \"a record\"}\n").unwrap();
+        assert!(load(&path).is_err());
+    }
+
+    #[test]
+    fn test_trends() {
+        let records = vec![
+            record("new", 30, 1, 100),
+            record("old", 10, 1, 400),
+            record("old", 20, 1, 300),
+            record("old", 10, 2, 50),
+        ];
+        let trends = trends(&records);
+        assert_eq!(trends.len(), 2);
+        assert_eq!(trends[0].part, 1);
+        assert_eq!(
+            trends[0].points,
+            vec![
+                TrendPoint {
+                    commit: "old".to_owned(),
+                    first_run: 10,
+                    runs: 2,
+                    best: Duration::from_nanos(300),
+                },
+                TrendPoint {
+                    commit: "new".to_owned(),
+                    first_run: 30,
+                    runs: 1,
+                    best: Duration::from_nanos(100),
+                },
+            ]
+        );
+        assert_eq!(trends[1].points.len(), 1);
+    }
+}
@@ -0,0 +1,129 @@
+// The Holiday ASCII String Helper algorithm (HASH) and the Holiday ASCII String Helper Manual
+// Arrangement Procedure (HASHMAP) of day 15, which puts labeled lenses into 256 boxes.
+
+/// Number of boxes, one for every possible hash
+pub const BOX_COUNT: usize = 256;
+
+/// Hashes a string into a box number: for each byte, add its value, multiply by 17 and keep the
+/// remainder of the division by 256.
+pub fn hash(val: &str) -> usize {
+    val.bytes()
+        .fold(0, |acc, v| ((acc + v as usize) * 17) % BOX_COUNT)
+}
+
+/// The lenses in one box, as `(label, focal length)` from the front to the back
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct LensBox(Vec<(String, usize)>);
+
+impl LensBox {
+    pub fn lenses(&self) -> &[(String, usize)] {
+        &self.0
+    }
+}
+
+/// The 256 boxes of the HASHMAP, each lens goes into the box its label hashes to.
+#[derive(Debug, Clone)]
+pub struct LensBoxes([LensBox; BOX_COUNT]);
+
+impl Default for LensBoxes {
+    fn default() -> Self {
+        Self(std::array::from_fn(|_| LensBox::default()))
+    }
+}
+
+impl LensBoxes {
+    /// Replaces the lens with the same label in its box, keeping its position, or puts the lens
+    /// behind all others if there is none (the `=` operation).
+    pub fn insert(&mut self, label: &str, focal_length: usize) {
+        let lens_list = &mut self.0[hash(label)].0;
+        match lens_list
+            .iter_mut()
+            .find(|(lens_label, _)| lens_label == label)
+        {
+            Some(lens) => lens.1 = focal_length,
+            None => lens_list.push((label.to_owned(), focal_length)),
+        }
+    }
+
+    /// Takes the lens with the label out of its box and moves the lenses behind it forward (the
+    /// `-` operation). Returns its focal length, or `None` if there was no such lens.
+    pub fn remove(&mut self, label: &str) -> Option<usize> {
+        let lens_list = &mut self.0[hash(label)].0;
+        let idx = lens_list
+            .iter()
+            .position(|(lens_label, _)| lens_label == label)?;
+        Some(lens_list.remove(idx).1)
+    }
+
+    pub fn get(&self, box_number: usize) -> &LensBox {
+        &self.0[box_number]
+    }
+
+    /// Sum of the box number times the slot number times the focal length of each lens, with
+    /// boxes and slots counted from 1
+    pub fn focusing_power(&self) -> usize {
+        self.0
+            .iter()
+            .enumerate()
+            .map(|(box_idx, box_content)| {
+                box_content
+                    .0
+                    .iter()
+                    .enumerate()
+                    .map(|(slot_index, (_, focal_length))| {
+                        (box_idx + 1) * (slot_index + 1) * focal_length
+                    })
+                    .sum::<usize>()
+            })
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lenses(boxes: &LensBoxes, box_number: usize) -> Vec<(&str, usize)> {
+        boxes
+            .get(box_number)
+            .lenses()
+            .iter()
+            .map(|(label, focal_length)| (label.as_str(), *focal_length))
+            .collect()
+    }
+
+    #[test]
+    fn test_hash() {
+        assert_eq!(hash("HASH"), 52);
+        assert_eq!(hash("rn=1"), 30);
+        assert_eq!(hash("rn"), 0);
+        assert_eq!(hash("qp"), 1);
+        assert_eq!(hash("pc"), 3);
+        assert_eq!(hash(""), 0);
+    }
+
+    #[test]
+    fn test_example_operations() {
+        let mut boxes = LensBoxes::default();
+        boxes.insert("rn", 1);
+        assert_eq!(lenses(&boxes, 0), [("rn", 1)]);
+        assert_eq!(boxes.remove("cm"), None);
+        boxes.insert("qp", 3);
+        assert_eq!(lenses(&boxes, 1), [("qp", 3)]);
+        boxes.insert("cm", 2);
+        assert_eq!(lenses(&boxes, 0), [("rn", 1), ("cm", 2)]);
+        assert_eq!(boxes.remove("qp"), Some(3));
+        assert_eq!(lenses(&boxes, 1), []);
+        boxes.insert("pc", 4);
+        boxes.insert("ot", 9);
+        boxes.insert("ab", 5);
+        assert_eq!(lenses(&boxes, 3), [("pc", 4), ("ot", 9), ("ab", 5)]);
+        assert_eq!(boxes.remove("pc"), Some(4));
+        boxes.insert("pc", 6);
+        // Replacing a lens keeps its slot
+        boxes.insert("ot", 7);
+        assert_eq!(lenses(&boxes, 0), [("rn", 1), ("cm", 2)]);
+        assert_eq!(lenses(&boxes, 3), [("ot", 7), ("ab", 5), ("pc", 6)]);
+        assert_eq!(boxes.focusing_power(), 145);
+    }
+}
@@ -0,0 +1,63 @@
+// Maps names from the input to dense ids, so graphs can use plain vectors instead of hash maps
+// keyed by strings.
+
+use std::collections::HashMap;
+
+/// Assigns each distinct name the next free id, starting at 0
+#[derive(Debug, Default, Clone)]
+pub struct Interner {
+    ids: HashMap<String, usize>,
+    names: Vec<String>,
+}
+
+impl Interner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The id of the name, which is assigned on first use
+    pub fn intern(&mut self, name: &str) -> usize {
+        if let Some(&id) = self.ids.get(name) {
+            return id;
+        }
+        let id = self.names.len();
+        self.ids.insert(name.to_owned(), id);
+        self.names.push(name.to_owned());
+        id
+    }
+
+    /// The id of a name that was interned before
+    pub fn get(&self, name: &str) -> Option<usize> {
+        self.ids.get(name).copied()
+    }
+
+    pub fn name(&self, id: usize) -> &str {
+        &self.names[id]
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_intern() {
+        let mut interner = Interner::new();
+        assert!(interner.is_empty());
+        assert_eq!(interner.intern("jqt"), 0);
+        assert_eq!(interner.intern("rhn"), 1);
+        assert_eq!(interner.intern("jqt"), 0);
+        assert_eq!(interner.len(), 2);
+        assert_eq!(interner.name(1), "rhn");
+        assert_eq!(interner.get("rhn"), Some(1));
+        assert_eq!(interner.get("xhk"), None);
+    }
+}
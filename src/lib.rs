@@ -1,10 +1,34 @@
+use memmap2::Mmap;
 use std::fs::File;
 use std::io::{self, prelude::*, BufReader};
 use std::marker::PhantomData;
 use std::path::Path;
 use std::str::FromStr;
 use thiserror::Error;
+#[cfg(feature = "alloc-tracking")]
+pub mod alloc_tracking;
+pub mod answer;
+pub mod checkpoint;
+pub mod cli;
+pub mod cube_game;
+pub mod digits;
+pub mod geometry;
+pub mod grid;
+pub mod history;
+pub mod holiday_hash;
+pub mod interner;
+pub mod maths;
+pub mod min_cut;
+pub mod nonogram;
+pub mod ranges;
+pub mod render_graph;
 pub mod render_grid;
+pub mod runner;
+pub mod schematic;
+pub mod search;
+#[cfg(feature = "z3")]
+pub mod smt;
+pub mod timing;
 
 #[derive(Error, Debug)]
 pub enum InputError<T> {
@@ -15,11 +39,30 @@ pub enum InputError<T> {
 }
 
 pub fn read_lines<P>(filename: P) -> io::Result<io::Lines<io::BufReader<File>>>
-where P: AsRef<Path>, {
+where
+    P: AsRef<Path>,
+{
     let file = File::open(filename)?;
     Ok(io::BufReader::new(file).lines())
 }
 
+/// Maps a file into memory, so huge inputs can be scanned as one byte slice without copying or
+/// allocating per line. The file must not be modified while it is mapped.
+pub fn map_file<P: AsRef<Path>>(path: P) -> io::Result<Mmap> {
+    let file = File::open(path)?;
+    // SAFETY: Puzzle inputs are not modified while a day is running
+    unsafe { Mmap::map(&file) }
+}
+
+/// Splits bytes into lines like [`BufRead::lines`], without the `\n` or `\r\n` line endings.
+pub fn byte_lines(bytes: &[u8]) -> impl Iterator<Item = &[u8]> {
+    let bytes = bytes.strip_suffix(b"\n").unwrap_or(bytes);
+    bytes
+        .split(|b| *b == b'\n')
+        .filter(move |_| !bytes.is_empty())
+        .map(|line| line.strip_suffix(b"\r").unwrap_or(line))
+}
+
 pub fn stream_items<I, T>(input: I) -> impl Iterator<Item = Result<T, InputError<T::Err>>>
 where
     I: Read,
@@ -27,7 +70,7 @@ where
 {
     BufReader::new(input).lines().map(|maybe_line| {
         maybe_line
-            .map_err(|e| InputError::IoError(e))
+            .map_err(InputError::IoError)
             .and_then(|l| Ok(l.parse()?))
     })
 }
@@ -73,7 +116,7 @@ where
                     }
                 }
                 None => {
-                    if group.len() > 0 {
+                    if !group.is_empty() {
                         return Some(group);
                     } else {
                         return None;
@@ -88,8 +131,8 @@ pub fn stream_file_blocks<P: AsRef<Path>>(
     path: P,
 ) -> std::io::Result<impl Iterator<Item = Vec<String>>> {
     let file = File::open(path)?;
-    let lines = BufReader::new(file).lines().filter_map(Result::ok);
-    Ok(BlockCollector::new(lines, |line: &String| line.len() == 0))
+    let lines = BufReader::new(file).lines().map_while(Result::ok);
+    Ok(BlockCollector::new(lines, |line: &String| line.is_empty()))
 }
 
 pub mod test_helpers {
@@ -0,0 +1,6 @@
+// Closed-form solutions for puzzles that boil down to a bit of maths
+
+pub mod crt;
+pub mod extrapolate;
+pub mod linear;
+pub mod quadratic;
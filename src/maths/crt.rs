@@ -0,0 +1,100 @@
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+#[error("Overflow: the combined modulus exceeds the range of 64 bit integers")]
+pub struct Overflow;
+
+/// Greatest common divisor `g` of `a` and `b` together with `x` and `y` such that
+/// `a * x + b * y = g`
+fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    if b == 0 {
+        (a, 1, 0)
+    } else {
+        let (g, x, y) = extended_gcd(b, a % b);
+        (g, y, x - (a / b) * y)
+    }
+}
+
+/// Combines the congruences `x ≡ a (mod m)` and `x ≡ b (mod n)` into a single one modulo
+/// `lcm(m, n)`. Congruences are given and returned as `(residue, modulus)`.
+///
+/// The moduli don't need to be coprime, which means there may be no solution at all, e.g. for
+/// `x ≡ 0 (mod 2)` and `x ≡ 1 (mod 4)`.
+///
+/// Fails if the combined modulus doesn't fit into 64 bits.
+pub fn combine((a, m): (u64, u64), (b, n): (u64, u64)) -> Result<Option<(u64, u64)>, Overflow> {
+    let (a, m, b, n) = (
+        i128::from(a % m),
+        i128::from(m),
+        i128::from(b % n),
+        i128::from(n),
+    );
+    let (g, p, _) = extended_gcd(m, n);
+    if (b - a) % g != 0 {
+        return Ok(None);
+    }
+    let lcm = (m / g).checked_mul(n).ok_or(Overflow)?;
+    let lcm = u64::try_from(lcm).map_err(|_| Overflow)?;
+    // m * p ≡ g (mod n), so stepping by m * p * (b - a) / g gets from a to b modulo n. Both factors
+    // are reduced below n / g <= lcm first, so their product fits into 128 bits.
+    let k = n / g;
+    let steps = ((b - a) / g).rem_euclid(k) as u128 * p.rem_euclid(k) as u128 % k as u128;
+    // a < m and m * steps < m * n / g = lcm, so this fits into 128 bits easily
+    let residue = a as u128 + m as u128 * steps;
+    Ok(Some(((residue % u128::from(lcm)) as u64, lcm)))
+}
+
+/// Solves a whole system of congruences `(residue, modulus)` with the Chinese remainder theorem,
+/// see [`combine`]. An empty system is solved by every number, i.e. `x ≡ 0 (mod 1)`.
+pub fn solve(congruences: &[(u64, u64)]) -> Result<Option<(u64, u64)>, Overflow> {
+    let mut combined = (0, 1);
+    for &congruence in congruences {
+        match combine(combined, congruence)? {
+            Some(next) => combined = next,
+            None => return Ok(None),
+        }
+    }
+    Ok(Some(combined))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_coprime_moduli() {
+        assert_eq!(solve(&[(2, 3), (3, 5), (2, 7)]), Ok(Some((23, 105))));
+        assert_eq!(solve(&[]), Ok(Some((0, 1))));
+        // Residues larger than their modulus are fine
+        assert_eq!(combine((7, 3), (0, 4)), Ok(Some((4, 12))));
+    }
+
+    #[test]
+    fn test_common_factors() {
+        assert_eq!(combine((1, 4), (3, 6)), Ok(Some((9, 12))));
+        assert_eq!(combine((0, 2), (1, 4)), Ok(None));
+        assert_eq!(solve(&[(3, 6), (3, 6)]), Ok(Some((3, 6))));
+    }
+
+    #[test]
+    fn test_large_moduli() {
+        let (residue, modulus) = solve(&[(1, 4_294_967_291), (2, 4_294_967_279)])
+            .unwrap()
+            .unwrap();
+        assert_eq!(modulus, 4_294_967_291 * 4_294_967_279);
+        assert_eq!(residue % 4_294_967_291, 1);
+        assert_eq!(residue % 4_294_967_279, 2);
+    }
+
+    #[test]
+    fn test_overflow() {
+        // Consecutive numbers are coprime, so their product doesn't fit
+        assert_eq!(combine((0, u64::MAX), (1, u64::MAX - 1)), Err(Overflow));
+        assert_eq!(solve(&[(0, 1 << 32), (0, (1 << 32) + 1)]), Err(Overflow));
+        // Just below the limit
+        assert_eq!(
+            solve(&[(0, 1 << 32), (0, (1 << 32) - 1)]),
+            Ok(Some((0, u64::MAX - u64::from(u32::MAX))))
+        );
+    }
+}
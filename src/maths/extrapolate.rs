@@ -0,0 +1,110 @@
+/// Evaluates the quadratic through evenly spaced samples `(x, f(x))` at `x`, e.g. for counts that
+/// grow with the area of a tiling. Uses Newton's forward differences on the first three samples,
+/// so the value is exact as long as it fits into 128 bits.
+///
+/// Returns `None` if the second differences of the samples aren't all the same, i.e. the samples
+/// don't lie on a single quadratic. Three samples always do, so it takes four or more to verify
+/// that the sequence is quadratic.
+///
+/// Panics if there are fewer than three samples, if they aren't spaced evenly in increasing `x`,
+/// if `x` isn't a multiple of the spacing away from them or if the value overflows.
+pub fn quadratic(samples: &[(i64, i64)], x: i64) -> Option<i128> {
+    assert!(samples.len() >= 3, "Expected at least three samples");
+    let spacing = i128::from(samples[1].0) - i128::from(samples[0].0);
+    assert!(
+        spacing > 0
+            && samples
+                .windows(2)
+                .all(|pair| i128::from(pair[1].0) - i128::from(pair[0].0) == spacing),
+        "Expected samples spaced evenly in increasing x"
+    );
+    let values = samples
+        .iter()
+        .map(|&(_, value)| i128::from(value))
+        .collect::<Vec<_>>();
+    let second_differences = values
+        .windows(3)
+        .map(|f| f[2] - 2 * f[1] + f[0])
+        .collect::<Vec<_>>();
+    let second = second_differences[0];
+    if second_differences.iter().any(|&d| d != second) {
+        return None;
+    }
+
+    let offset = i128::from(x) - i128::from(samples[0].0);
+    assert!(
+        offset % spacing == 0,
+        "Expected x to be a multiple of the spacing away from the samples"
+    );
+    // f(x_0 + k * spacing) = f_0 + k * first + k * (k - 1) / 2 * second, where k * (k - 1) is even
+    let k = offset / spacing;
+    let first = values[1] - values[0];
+    let triangle = k
+        .checked_mul(k - 1)
+        .map(|product| product / 2)
+        .and_then(|triangle| triangle.checked_mul(second));
+    let value = k
+        .checked_mul(first)
+        .zip(triangle)
+        .and_then(|(linear, quadratic)| linear.checked_add(quadratic))
+        .and_then(|sum| sum.checked_add(values[0]));
+    Some(value.expect("Extrapolated value exceeds 128 bits"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quadratic() {
+        // f(x) = 3x^2 - 2x + 5, sampled every 10 starting at 7
+        let f = |x: i64| 3 * x * x - 2 * x + 5;
+        let samples = [7, 17, 27, 37].map(|x| (x, f(x)));
+        assert_eq!(quadratic(&samples, 47), Some(f(47).into()));
+        // Before the samples as well
+        assert_eq!(quadratic(&samples, -13), Some(f(-13).into()));
+        assert_eq!(quadratic(&samples, 7), Some(f(7).into()));
+        // Lines and constants are quadratics, too
+        assert_eq!(quadratic(&[(0, 1), (1, 3), (2, 5)], 100), Some(201));
+        assert_eq!(
+            quadratic(&[(0, 4), (5, 4), (10, 4), (15, 4)], 1000),
+            Some(4)
+        );
+    }
+
+    #[test]
+    fn test_huge_target() {
+        // Day 21: 26501365 steps on a 131 wide map
+        let samples = [(65, 3784), (196, 33680), (327, 93366)];
+        let target = 26501365;
+        let k = i128::from((target - 65) / 131);
+        assert_eq!(
+            quadratic(&samples, target),
+            Some(3784 + k * 29896 + k * (k - 1) / 2 * 29790)
+        );
+        // The squared offset exceeds 64 bits
+        assert_eq!(
+            quadratic(&[(0, 0), (1, 1), (2, 4)], i64::MAX),
+            Some(i128::from(i64::MAX).pow(2))
+        );
+    }
+
+    #[test]
+    fn test_not_quadratic() {
+        // Cubes have constant third differences only
+        let samples = [0, 1, 2, 3].map(|x: i64| (x, x.pow(3)));
+        assert_eq!(quadratic(&samples, 10), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "evenly")]
+    fn test_uneven_samples() {
+        quadratic(&[(0, 0), (1, 1), (3, 9)], 10);
+    }
+
+    #[test]
+    #[should_panic(expected = "multiple of the spacing")]
+    fn test_unaligned_target() {
+        quadratic(&[(0, 0), (2, 4), (4, 16)], 5);
+    }
+}
@@ -0,0 +1,80 @@
+use num::{traits::NumOps, Zero};
+
+/// Solves the linear system `matrix * x = rhs` with Gaussian elimination, returning `None` if
+/// the matrix is singular.
+///
+/// The numbers need exact division for exact results, e.g. [`num::BigRational`] for systems whose
+/// coefficients grow too large for fixed-size fractions while eliminating.
+pub fn solve<T>(mut matrix: Vec<Vec<T>>, mut rhs: Vec<T>) -> Option<Vec<T>>
+where
+    T: Clone + Zero + NumOps,
+{
+    let n = rhs.len();
+    assert!(
+        matrix.len() == n && matrix.iter().all(|row| row.len() == n),
+        "Expected a square matrix with one row per right-hand side value"
+    );
+    for col in 0..n {
+        let pivot = (col..n).find(|&row| !matrix[row][col].is_zero())?;
+        matrix.swap(col, pivot);
+        rhs.swap(col, pivot);
+        let pivot_row = matrix[col].clone();
+        for row in 0..n {
+            if row == col || matrix[row][col].is_zero() {
+                continue;
+            }
+            let factor = matrix[row][col].clone() / pivot_row[col].clone();
+            for (value, pivot) in matrix[row].iter_mut().zip(&pivot_row).skip(col) {
+                *value = value.clone() - factor.clone() * pivot.clone();
+            }
+            rhs[row] = rhs[row].clone() - factor * rhs[col].clone();
+        }
+    }
+    Some(
+        rhs.into_iter()
+            .zip(matrix)
+            .enumerate()
+            .map(|(idx, (value, row))| value / row[idx].clone())
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use num::{rational::Ratio, BigInt, BigRational};
+
+    use super::*;
+
+    #[test]
+    fn test_solve() {
+        let r = |n: i64| Ratio::from_integer(n);
+        // x + 2y = 5, 3x - y = 1 with a zero pivot in the first column to swap away
+        let matrix = vec![
+            vec![r(0), r(1), r(1)],
+            vec![r(1), r(2), r(0)],
+            vec![r(3), r(-1), r(0)],
+        ];
+        assert_eq!(
+            solve(matrix, vec![r(4), r(5), r(1)]),
+            Some(vec![r(1), r(2), r(2)])
+        );
+        let fractions = solve(vec![vec![r(2), r(0)], vec![r(0), r(3)]], vec![r(1), r(1)]);
+        assert_eq!(fractions, Some(vec![Ratio::new(1, 2), Ratio::new(1, 3)]));
+        assert_eq!(
+            solve(vec![vec![r(1), r(2)], vec![r(2), r(4)]], vec![r(1), r(2)]),
+            None
+        );
+    }
+
+    #[test]
+    fn test_solve_large_coefficients() {
+        let big = |n: i128| BigRational::from_integer(BigInt::from(n));
+        let a = 123_456_789_012_345_678i128;
+        let matrix = vec![vec![big(a), big(a - 1)], vec![big(a + 1), big(a)]];
+        // The determinant is 1, so the solution is integral
+        assert_eq!(
+            solve(matrix, vec![big(1), big(0)]),
+            Some(vec![big(a), big(-a - 1)])
+        );
+    }
+}
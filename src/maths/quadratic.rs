@@ -0,0 +1,106 @@
+use std::ops::RangeInclusive;
+
+/* Given a time-limit T and distance record R, we can calculate our distance in the race like this:
+ * d(t) = t * (T - t) = -t^2 + T*t
+ *
+ * From that, we can calculate by how much we would beat the record for a given acceleration time t
+ * using this formula: d(t) = -t^2 + T*t - R
+ *
+ * Solving the polynomial for the roots gives:
+ *
+ * x_1/2 = (-T +- sqrt(T^2 - 4*R)) / (-2)
+ *
+ * This is derived from the general formula for roots of quadratic functions :
+ *
+ * x_1/2 = (-b +- sqrt(b^2 - 4ac)) / (2a)
+ *
+ * given the function f(x) = ax^2 + bx + c
+ * so a=-1, b=T and c=R
+ *
+ * Floats can't tell whether a root is exactly on an integer or just next to it, so we stay with
+ * integers: With the integer square root, (T - isqrt(T^2 - 4*R)) / 2 is at most one below the
+ * first acceleration time beating the record, which we then find by checking the distance
+ * directly. The upper bound follows from the symmetry d(t) = d(T - t).
+ *
+ * T^2 overflows 64 bits for huge races, so the computation happens in u128, where T^2 - 4*R fits
+ * for all 64 bit parameters.
+ */
+
+/// All acceleration times `t` with `t * (time_limit - t) > distance_record`, i.e. beating the
+/// record of a boat race on day 6. The range is empty if the record can't be beaten.
+pub fn beating_range(time_limit: u64, distance_record: u64) -> RangeInclusive<u64> {
+    let no_way_to_win = RangeInclusive::new(1, 0);
+    let (time_limit, distance_record) = (u128::from(time_limit), u128::from(distance_record));
+    let beats_record = |t: u128| t * (time_limit - t) > distance_record;
+    let Some(discriminant) = time_limit.pow(2).checked_sub(4 * distance_record) else {
+        // No real roots, the distance never reaches the record
+        return no_way_to_win;
+    };
+
+    let mut lower = (time_limit - discriminant.isqrt()) / 2;
+    while lower <= time_limit / 2 && !beats_record(lower) {
+        lower += 1;
+    }
+    if lower > time_limit / 2 {
+        // Not even the maximum distance at T/2 beats the record
+        return no_way_to_win;
+    }
+    let upper = time_limit - lower;
+
+    // Both bounds are at most T, so they fit into the type of T again
+    lower as u64..=upper as u64
+}
+
+/// Number of integers `t` with `t * (time_limit - t) > distance_record`
+pub fn count_beating(time_limit: u64, distance_record: u64) -> u64 {
+    let range = beating_range(time_limit, distance_record);
+    if range.is_empty() {
+        0
+    } else {
+        range.end() - range.start() + 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_beating_range() {
+        assert_eq!(beating_range(7, 9), 2..=5);
+        assert_eq!(count_beating(7, 9), 4);
+        // Roots exactly on integers, 10 * 20 is not enough to beat the record
+        assert_eq!(beating_range(30, 200), 11..=19);
+        assert_eq!(count_beating(30, 0), 29);
+    }
+
+    #[test]
+    fn test_unbeatable_records() {
+        // Negative discriminant
+        assert!(beating_range(7, 13).is_empty());
+        assert_eq!(count_beating(7, 13), 0);
+        // The maximum distance only ties the record
+        assert_eq!(count_beating(7, 12), 0);
+        assert_eq!(count_beating(8, 16), 0);
+        // Zero-length windows
+        assert_eq!(count_beating(0, 0), 0);
+        assert_eq!(count_beating(1, 0), 0);
+        // Only the middle of an even time beats the record
+        assert_eq!(beating_range(8, 15), 4..=4);
+    }
+
+    #[test]
+    fn test_huge_races() {
+        // T^2 doesn't fit into 64 bits here
+        let time_limit = u64::MAX;
+        assert_eq!(count_beating(time_limit, 0), u64::MAX - 1);
+        assert_eq!(beating_range(time_limit, u64::MAX), 2..=u64::MAX - 2);
+    }
+
+    #[test]
+    fn test_root_close_to_integer() {
+        // The lower root is about 1e-6 below 1000
+        assert_eq!(beating_range(1_000_000, 1000 * 999_000 - 1), 1000..=999_000);
+        assert_eq!(beating_range(1_000_000, 1000 * 999_000), 1001..=998_999);
+    }
+}
@@ -0,0 +1,134 @@
+// Minimum cuts of undirected graphs with unit edge weights, as the maximum flow between two nodes
+// (max-flow min-cut theorem). Each augmenting path is found with a breadth-first search, and
+// after the last one, the nodes still reachable from the source in the residual graph form its
+// side of the cut. Small cuts only need a few searches, so asking for a cut of a known small
+// size is fast even for large graphs.
+
+use std::collections::{HashMap, VecDeque};
+
+/// A split of the nodes into two sides and the edges between them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cut {
+    /// Whether each node is on the side of the source
+    pub source_side: Vec<bool>,
+    /// The cut edges as `(source side, sink side)`
+    pub edges: Vec<(usize, usize)>,
+}
+
+impl Cut {
+    /// Number of nodes on the source side and on the other side
+    pub fn sizes(&self) -> (usize, usize) {
+        let source = self.source_side.iter().filter(|side| **side).count();
+        (source, self.source_side.len() - source)
+    }
+}
+
+/// Minimum cut between source and sink in the graph given as adjacency lists, where each entry
+/// is an edge of weight 1. Returns `None` if the cut has more than `max_size` edges, without
+/// searching further than that.
+pub fn min_cut(
+    adjacency: &[Vec<usize>],
+    source: usize,
+    sink: usize,
+    max_size: usize,
+) -> Option<Cut> {
+    assert_ne!(source, sink, "The source can't be the sink");
+    // Flow along each directed edge, the reverse direction has the negated flow
+    let mut flow = HashMap::<(usize, usize), i32>::new();
+    let residual = |flow: &HashMap<(usize, usize), i32>, from: usize, to: usize| {
+        1 - flow.get(&(from, to)).copied().unwrap_or(0)
+    };
+    for _ in 0..=max_size {
+        // Breadth-first search for a path with residual capacity
+        let mut prev = vec![None; adjacency.len()];
+        prev[source] = Some(source);
+        let mut queue = VecDeque::from([source]);
+        while let Some(node) = queue.pop_front() {
+            if node == sink {
+                break;
+            }
+            for &next in &adjacency[node] {
+                if prev[next].is_none() && residual(&flow, node, next) > 0 {
+                    prev[next] = Some(node);
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        if prev[sink].is_none() {
+            let source_side = prev.iter().map(Option::is_some).collect::<Vec<_>>();
+            let edges = (0..adjacency.len())
+                .filter(|&node| source_side[node])
+                .flat_map(|node| {
+                    adjacency[node]
+                        .iter()
+                        .filter(|&&next| !source_side[next])
+                        .map(move |&next| (node, next))
+                })
+                .collect();
+            return Some(Cut { source_side, edges });
+        }
+
+        let mut node = sink;
+        while node != source {
+            let from = prev[node].expect("The path leads back to the source");
+            *flow.entry((from, node)).or_default() += 1;
+            *flow.entry((node, from)).or_default() -= 1;
+            node = from;
+        }
+    }
+    None
+}
+
+/// A cut with exactly `size` edges splitting the graph into two non-empty sides, if there is one
+/// and no smaller cut exists. Node 0 is on one side, so some other node is on the other side, and
+/// the minimum cut between the two is the one searched for.
+pub fn find_cut(adjacency: &[Vec<usize>], size: usize) -> Option<Cut> {
+    (1..adjacency.len())
+        .filter_map(|sink| min_cut(adjacency, 0, sink, size))
+        .find(|cut| cut.edges.len() == size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Two complete graphs with `n` nodes each, joined by `bridges` edges
+    fn two_cliques(n: usize, bridges: usize) -> Vec<Vec<usize>> {
+        let mut adjacency = vec![Vec::new(); 2 * n];
+        let mut connect = |a: usize, b: usize| {
+            adjacency[a].push(b);
+            adjacency[b].push(a);
+        };
+        for offset in [0, n] {
+            for a in 0..n {
+                for b in a + 1..n {
+                    connect(offset + a, offset + b);
+                }
+            }
+        }
+        for bridge in 0..bridges {
+            connect(bridge, n + bridge);
+        }
+        adjacency
+    }
+
+    #[test]
+    fn test_min_cut() {
+        let graph = two_cliques(5, 3);
+        let cut = min_cut(&graph, 0, 9, 3).unwrap();
+        assert_eq!(cut.sizes(), (5, 5));
+        assert_eq!(cut.edges, [(0, 5), (1, 6), (2, 7)]);
+        // The cut between two nodes of the same clique is larger
+        assert_eq!(min_cut(&graph, 0, 4, 3), None);
+    }
+
+    #[test]
+    fn test_find_cut() {
+        let graph = two_cliques(6, 3);
+        let cut = find_cut(&graph, 3).unwrap();
+        assert_eq!(cut.sizes(), (6, 6));
+        assert!(cut.source_side[..6].iter().all(|side| *side));
+        assert_eq!(find_cut(&graph, 2), None);
+    }
+}
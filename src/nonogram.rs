@@ -0,0 +1,303 @@
+use std::fmt::Display;
+
+/// A cell of a nonogram row, e.g. a spring that is operational (empty), damaged (filled) or of
+/// unknown condition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Cell {
+    Empty,
+    Filled,
+    Unknown,
+}
+
+impl Cell {
+    /// Reads the usual notation: `.` is empty, `#` is filled and `?` is unknown.
+    pub fn from_char(c: char) -> Option<Self> {
+        match c {
+            '.' => Some(Cell::Empty),
+            '#' => Some(Cell::Filled),
+            '?' => Some(Cell::Unknown),
+            _ => None,
+        }
+    }
+
+    pub fn could_be_filled(&self) -> bool {
+        matches!(self, Cell::Filled | Cell::Unknown)
+    }
+
+    pub fn could_be_empty(&self) -> bool {
+        matches!(self, Cell::Empty | Cell::Unknown)
+    }
+}
+
+impl Display for Cell {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let c = match self {
+            Cell::Empty => '.',
+            Cell::Filled => '#',
+            Cell::Unknown => '?',
+        };
+        write!(f, "{}", c)
+    }
+}
+
+/// Number of ways to fill the unknown cells so the runs of filled cells have exactly the lengths
+/// of `groups`, in order.
+///
+/// This fills a table over the states after each cell: how many groups are complete and how
+/// long the current run of filled cells is. Only the table of the previous cell is needed for
+/// the next one, so this takes `O(cells * groups * longest group)` time without recursion.
+pub fn count_arrangements(cells: &[Cell], groups: &[usize]) -> usize {
+    let width = groups.iter().copied().max().unwrap_or(0) + 1;
+    let state = |group: usize, run: usize| group * width + run;
+    // ways[state(group, run)], next is reused for the cell after
+    let mut ways = vec![0; (groups.len() + 1) * width];
+    let mut next = ways.clone();
+    ways[state(0, 0)] = 1;
+    for cell in cells {
+        next.fill(0);
+        for (idx, &count) in ways.iter().enumerate().filter(|(_, c)| **c > 0) {
+            let (group, run) = (idx / width, idx % width);
+            if cell.could_be_filled() && groups.get(group).is_some_and(|g| run < *g) {
+                next[state(group, run + 1)] += count;
+            }
+            if cell.could_be_empty() {
+                if run == 0 {
+                    next[state(group, 0)] += count;
+                } else if run == groups[group] {
+                    next[state(group + 1, 0)] += count;
+                }
+            }
+        }
+        std::mem::swap(&mut ways, &mut next);
+    }
+    let complete = groups.len();
+    let last_run_complete = match groups.last() {
+        Some(last) => ways[state(complete - 1, *last)],
+        None => 0,
+    };
+    ways[state(complete, 0)] + last_run_complete
+}
+
+/// The concrete rows [`count_arrangements`] counts, with every unknown cell filled or empty,
+/// produced lazily in lexicographic order where empty comes first.
+///
+/// Only states that can still be completed are explored, so taking the first few rows is cheap
+/// even if there are astronomically many of them.
+pub fn arrangements<'a>(cells: &'a [Cell], groups: &'a [usize]) -> Arrangements<'a> {
+    // solvable[pos][group]: groups[group..] fit into cells[pos..], given that the cell before
+    // pos is empty
+    let mut solvable = vec![vec![false; groups.len() + 1]; cells.len() + 1];
+    solvable[cells.len()][groups.len()] = true;
+    for pos in (0..cells.len()).rev() {
+        for group in 0..=groups.len() {
+            solvable[pos][group] = (cells[pos].could_be_empty() && solvable[pos + 1][group])
+                || next_after_group(cells, groups, pos, group)
+                    .is_some_and(|next| solvable[next][group + 1]);
+        }
+    }
+    let stack = if solvable[0][0] {
+        vec![(0, 0, Vec::with_capacity(cells.len()))]
+    } else {
+        vec![]
+    };
+    Arrangements {
+        cells,
+        groups,
+        solvable,
+        stack,
+    }
+}
+
+/// Position after placing `groups[group]` at `pos` and the empty cell behind it, if the group
+/// fits there
+fn next_after_group(cells: &[Cell], groups: &[usize], pos: usize, group: usize) -> Option<usize> {
+    let len = *groups.get(group)?;
+    let end = pos + len;
+    let fits = end <= cells.len()
+        && cells[pos..end].iter().all(Cell::could_be_filled)
+        && cells.get(end).is_none_or(Cell::could_be_empty);
+    fits.then_some((end + 1).min(cells.len()))
+}
+
+/// Iterator over the concrete rows of a nonogram row, see [`arrangements`].
+pub struct Arrangements<'a> {
+    cells: &'a [Cell],
+    groups: &'a [usize],
+    solvable: Vec<Vec<bool>>,
+    /// Partial rows to continue, as the next position, the next group and the cells so far
+    stack: Vec<(usize, usize, Vec<Cell>)>,
+}
+
+impl Iterator for Arrangements<'_> {
+    type Item = Vec<Cell>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((pos, group, row)) = self.stack.pop() {
+            if pos == self.cells.len() {
+                return Some(row);
+            }
+            // Pushed first, so the empty cell is continued first
+            if let Some(next) = next_after_group(self.cells, self.groups, pos, group)
+                .filter(|next| self.solvable[*next][group + 1])
+            {
+                let mut row = row.clone();
+                row.extend(std::iter::repeat_n(Cell::Filled, self.groups[group]));
+                row.resize(next, Cell::Empty);
+                self.stack.push((next, group + 1, row));
+            }
+            if self.cells[pos].could_be_empty() && self.solvable[pos + 1][group] {
+                let mut row = row;
+                row.push(Cell::Empty);
+                self.stack.push((pos + 1, group, row));
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use num::integer::binomial;
+
+    fn cells(pattern: &str) -> Vec<Cell> {
+        pattern
+            .chars()
+            .map(|c| Cell::from_char(c).unwrap())
+            .collect()
+    }
+
+    /// Lengths of the runs of filled cells
+    fn runs(cells: &[Cell]) -> Vec<usize> {
+        cells
+            .split(|cell| *cell != Cell::Filled)
+            .map(|run| run.len())
+            .filter(|len| *len > 0)
+            .collect()
+    }
+
+    /// Tries every way to fill the unknown cells
+    fn brute_force(cells: &[Cell], groups: &[usize]) -> usize {
+        match cells.iter().position(|cell| *cell == Cell::Unknown) {
+            Some(idx) => [Cell::Empty, Cell::Filled]
+                .into_iter()
+                .map(|fill| {
+                    let mut filled = cells.to_vec();
+                    filled[idx] = fill;
+                    brute_force(&filled, groups)
+                })
+                .sum(),
+            None => usize::from(runs(cells) == groups),
+        }
+    }
+
+    /// Every row of up to `max_len` cells
+    fn all_rows(max_len: usize) -> Vec<Vec<Cell>> {
+        let mut rows = vec![vec![]];
+        let mut last = vec![vec![]];
+        for _ in 0..max_len {
+            last = last
+                .iter()
+                .flat_map(|row: &Vec<Cell>| {
+                    [Cell::Empty, Cell::Filled, Cell::Unknown].map(|cell| {
+                        let mut row = row.clone();
+                        row.push(cell);
+                        row
+                    })
+                })
+                .collect();
+            rows.extend(last.iter().cloned());
+        }
+        rows
+    }
+
+    const GROUPS: [&[usize]; 7] = [&[], &[1], &[3], &[1, 1], &[2, 1], &[1, 3, 1], &[1, 1, 1, 1]];
+
+    #[test]
+    fn test_count_arrangements() {
+        assert_eq!(count_arrangements(&cells("???.###"), &[1, 1, 3]), 1);
+        assert_eq!(count_arrangements(&cells(".??..??...?##."), &[1, 1, 3]), 4);
+        assert_eq!(count_arrangements(&cells("?###????????"), &[3, 2, 1]), 10);
+        assert_eq!(count_arrangements(&cells(""), &[]), 1);
+        assert_eq!(count_arrangements(&cells(""), &[1]), 0);
+        assert_eq!(count_arrangements(&cells("#"), &[]), 0);
+        assert_eq!(count_arrangements(&cells("##"), &[1]), 0);
+    }
+
+    #[test]
+    fn test_matches_brute_force() {
+        for row in all_rows(7) {
+            for groups in GROUPS {
+                assert_eq!(
+                    count_arrangements(&row, groups),
+                    brute_force(&row, groups),
+                    "{:?} {:?}",
+                    row,
+                    groups
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_arrangements() {
+        let row = |cells: Vec<Cell>| cells.iter().map(Cell::to_string).collect::<String>();
+        assert_eq!(
+            arrangements(&cells("?###????????"), &[3, 2, 1])
+                .take(3)
+                .map(row)
+                .collect::<Vec<_>>(),
+            vec![".###....##.#", ".###...##..#", ".###...##.#."]
+        );
+        assert_eq!(arrangements(&cells("#.#"), &[3]).count(), 0);
+        assert_eq!(
+            arrangements(&cells(""), &[]).map(row).collect::<Vec<_>>(),
+            [""]
+        );
+
+        for row in all_rows(6) {
+            for groups in GROUPS {
+                let all = arrangements(&row, groups).collect::<Vec<_>>();
+                assert_eq!(all.len(), count_arrangements(&row, groups));
+                assert!(all.windows(2).all(|pair| pair[0] < pair[1]));
+                for arrangement in all {
+                    assert_eq!(runs(&arrangement), groups);
+                    assert!(row
+                        .iter()
+                        .zip(&arrangement)
+                        .all(|(cell, fill)| *cell == Cell::Unknown || cell == fill));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_reversed_rows() {
+        for row in all_rows(6) {
+            for groups in GROUPS {
+                let reversed_row = row.iter().rev().copied().collect::<Vec<_>>();
+                let reversed_groups = groups.iter().rev().copied().collect::<Vec<_>>();
+                assert_eq!(
+                    count_arrangements(&row, groups),
+                    count_arrangements(&reversed_row, &reversed_groups)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_unknown_rows() {
+        // The empty cells that aren't needed as separators can go into any of the gaps
+        for len in 0..30 {
+            let row = vec![Cell::Unknown; len];
+            for groups in GROUPS {
+                let needed = groups.iter().sum::<usize>() + groups.len().saturating_sub(1);
+                let expected = match len.checked_sub(needed) {
+                    Some(spare) => binomial(spare + groups.len(), groups.len()),
+                    None => 0,
+                };
+                assert_eq!(count_arrangements(&row, groups), expected);
+            }
+        }
+    }
+}
@@ -0,0 +1,36 @@
+/// Brings half-open `(start, end)` ranges into canonical form: sorted by start, without empty
+/// ranges, and with overlapping or adjacent ranges merged into one.
+///
+/// The ranges cover the same values before and after, but there are as few of them as possible,
+/// which keeps range-based algorithms from producing ever more fragments.
+pub fn normalize<T>(ranges: &mut Vec<(T, T)>)
+where
+    T: Ord + Copy,
+{
+    ranges.retain(|(start, end)| start < end);
+    ranges.sort_unstable();
+    let mut merged: Vec<(T, T)> = Vec::with_capacity(ranges.len());
+    for &(start, end) in ranges.iter() {
+        match merged.last_mut() {
+            Some((_, last_end)) if start <= *last_end => *last_end = (*last_end).max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    *ranges = merged;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize() {
+        let mut ranges = vec![(10, 12), (3, 5), (5, 7), (4, 6), (8, 8), (9, 11), (20, 15)];
+        normalize(&mut ranges);
+        assert_eq!(ranges, vec![(3, 7), (9, 12)]);
+
+        let mut empty: Vec<(u8, u8)> = Vec::new();
+        normalize(&mut empty);
+        assert!(empty.is_empty());
+    }
+}
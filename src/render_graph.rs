@@ -0,0 +1,171 @@
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+struct Node {
+    id: String,
+    label: Option<String>,
+    color: Option<String>,
+}
+
+struct Edge {
+    from: String,
+    to: String,
+    label: Option<String>,
+    color: Option<String>,
+}
+
+/// Collects nodes and edges and writes them in the DOT language, e.g. to inspect a network with
+/// Graphviz: `dot -Tsvg graph.dot -o graph.svg`.
+///
+/// Nodes that are only mentioned by edges don't need to be added explicitly.
+pub struct GraphRenderer {
+    directed: bool,
+    nodes: Vec<Node>,
+    edges: Vec<Edge>,
+}
+
+/// Quotes an ID or label, so arbitrary text can be used
+fn quote(text: &str) -> String {
+    let mut quoted = String::with_capacity(text.len() + 2);
+    quoted.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Attribute list like `[label="a", color="red"]`, or nothing if no attribute is set
+fn attributes(label: &Option<String>, color: &Option<String>) -> String {
+    let attributes = [("label", label), ("color", color)]
+        .into_iter()
+        .filter_map(|(name, value)| value.as_ref().map(|v| format!("{}={}", name, quote(v))))
+        .collect::<Vec<_>>();
+    if attributes.is_empty() {
+        String::new()
+    } else {
+        format!(" [{}]", attributes.join(", "))
+    }
+}
+
+impl GraphRenderer {
+    /// Creates a renderer for a graph whose edges are arrows if `directed` is set.
+    pub fn new(directed: bool) -> Self {
+        GraphRenderer {
+            directed,
+            nodes: Vec::new(),
+            edges: Vec::new(),
+        }
+    }
+
+    pub fn add_node(&mut self, id: String, label: Option<String>, color: Option<String>) {
+        self.nodes.push(Node { id, label, color });
+    }
+
+    pub fn add_edge(
+        &mut self,
+        from: String,
+        to: String,
+        label: Option<String>,
+        color: Option<String>,
+    ) {
+        self.edges.push(Edge {
+            from,
+            to,
+            label,
+            color,
+        });
+    }
+
+    /// Writes the graph in the DOT language, nodes first and everything in insertion order.
+    pub fn render_dot<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        let (keyword, connector) = if self.directed {
+            ("digraph", "->")
+        } else {
+            ("graph", "--")
+        };
+        writeln!(writer, "{} {{", keyword)?;
+        for node in &self.nodes {
+            writeln!(
+                writer,
+                "  {}{};",
+                quote(&node.id),
+                attributes(&node.label, &node.color)
+            )?;
+        }
+        for edge in &self.edges {
+            writeln!(
+                writer,
+                "  {} {} {}{};",
+                quote(&edge.from),
+                connector,
+                quote(&edge.to),
+                attributes(&edge.label, &edge.color)
+            )?;
+        }
+        writeln!(writer, "}}")
+    }
+
+    pub fn store_dot<P>(&self, path: P) -> io::Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.render_dot(&mut writer)?;
+        writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_dot() {
+        let mut graph = GraphRenderer::new(true);
+        graph.add_node("AAA".to_owned(), None, Some("green".to_owned()));
+        graph.add_edge(
+            "AAA".to_owned(),
+            "BBB".to_owned(),
+            Some("L".to_owned()),
+            None,
+        );
+        graph.add_edge("AAA".to_owned(), "say \"hi\"".to_owned(), None, None);
+
+        let mut dot = Vec::new();
+        graph.render_dot(&mut dot).unwrap();
+        assert_eq!(
+            String::from_utf8(dot).unwrap(),
+            [
+                "digraph {",
+                "  \"AAA\" [color=\"green\"];",
+                "  \"AAA\" -> \"BBB\" [label=\"L\"];",
+                "  \"AAA\" -> \"say \\\"hi\\\"\";",
+                "}",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn test_undirected() {
+        let mut graph = GraphRenderer::new(false);
+        graph.add_edge("a".to_owned(), "b".to_owned(), None, None);
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("graph.dot");
+        graph.store_dot(&path).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(path).unwrap(),
+            "graph {\n  \"a\" -- \"b\";\n}\n"
+        );
+    }
+}
@@ -1,8 +1,610 @@
-use std::{fmt::Display, fs::File, io::Write, path::Path};
+use std::{
+    fmt::Display,
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+    time::Duration,
+};
 
+use anyhow::{anyhow, bail, Result};
+use image::{
+    codecs::gif::{GifEncoder, Repeat},
+    Delay, Frame, Rgba, RgbaImage,
+};
+use num::ToPrimitive;
+
+use crate::grid::{Direction, Grid};
+
+/// Largest bitmap `store_png` will allocate, in pixels
+const MAX_PNG_PIXELS: u64 = 1 << 28;
+
+/// Default size of a grid cell in the SVG output, in pixels
+const DEFAULT_SVG_SCALE: f64 = 10.0;
+
+/// Area covered by the SVG shapes as `(min_y, min_x, max_y, max_x)`
+type SvgBounds = (f64, f64, f64, f64);
+
+/// `(y, x, w, h, color, tooltip)` of a rect that is yet to be drawn
+type ColoredRect<C> = (C, C, C, C, Option<String>, Option<String>);
+
+/// `(y, x, h, w, color)` in integer grid coordinates
+type RasterRect = (i64, i64, i64, i64, Rgba<u8>);
+
+/// Open line through the centers of the given `(y, x)` cells
+struct Polyline<C> {
+    points: Vec<(C, C)>,
+    stroke: String,
+    width: f64,
+}
+
+/// Filled area whose corners are the centers of the given `(y, x)` cells
+struct Polygon<C> {
+    points: Vec<(C, C)>,
+    fill: String,
+}
+
+const HTML_PREFIX: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<style>
+body { margin: 0; overflow: hidden; font-family: monospace; }
+svg { width: 100vw; height: 100vh; cursor: grab; }
+#info { position: fixed; top: 0; left: 0; padding: 4px 8px; background: rgba(255, 255, 255, 0.8); }
+</style>
+</head>
+<body>
+<div id="info"></div>
+"#;
+
+const HTML_SUFFIX: &str = r#"
+<script>
+const svg = document.querySelector("svg");
+const info = document.getElementById("info");
+let [vx, vy, vw, vh] = svg.getAttribute("viewBox").split(" ").map(Number);
+const update = () => svg.setAttribute("viewBox", `${vx} ${vy} ${vw} ${vh}`);
+const toGrid = (e) => new DOMPoint(e.clientX, e.clientY).matrixTransform(svg.getScreenCTM().inverse());
+svg.addEventListener("wheel", (e) => {
+  e.preventDefault();
+  const p = toGrid(e);
+  const factor = e.deltaY > 0 ? 1.2 : 1 / 1.2;
+  vx = p.x - (p.x - vx) * factor;
+  vy = p.y - (p.y - vy) * factor;
+  vw *= factor;
+  vh *= factor;
+  update();
+}, { passive: false });
+let dragStart = null;
+svg.addEventListener("mousedown", (e) => { dragStart = toGrid(e); });
+window.addEventListener("mouseup", () => { dragStart = null; });
+svg.addEventListener("mousemove", (e) => {
+  const p = toGrid(e);
+  if (dragStart) {
+    vx -= p.x - dragStart.x;
+    vy -= p.y - dragStart.y;
+    update();
+    return;
+  }
+  const target = e.target;
+  const value = target.dataset.value ?? (target.tagName === "text" ? target.textContent : undefined);
+  info.textContent = `y ${Math.floor(p.y)}, x ${Math.floor(p.x)}` + (value !== undefined ? `: ${value}` : "");
+});
+</script>
+</body>
+</html>
+"#;
+
+/// Vertical distance between legend entries, in grid units
+const LEGEND_ROW_HEIGHT: f64 = 1.5;
+/// Distance of the legend labels from the grid, leaving space for the swatches
+const LEGEND_TEXT_OFFSET: f64 = 2.5;
+/// Approximate width of a monospace character in the legend, in grid units
+const LEGEND_CHAR_WIDTH: f64 = 0.5;
+
+/// Arrow filling a unit cell and pointing up, rotated around the cell center for other directions
+const ARROW_PATH: &str = "M0.5 0.1 L0.9 0.55 L0.64 0.55 L0.64 0.9 L0.36 0.9 L0.36 0.55 L0.1 0.55 Z";
+
+/// Everything drawn on one layer of a scene
+struct Shapes<C> {
+    /// `(y, x, color, tooltip)`
+    tiles: Vec<(C, C, Option<String>, Option<String>)>,
+    heat_tiles: Vec<(C, C, f64)>,
+    arrows: Vec<(C, C, Direction, String)>,
+    labels: Vec<(C, C, String, String)>,
+    rects: Vec<ColoredRect<C>>,
+    polygons: Vec<Polygon<C>>,
+    paths: Vec<Polyline<C>>,
+}
+
+impl<C> Shapes<C> {
+    fn is_empty(&self) -> bool {
+        self.tiles.is_empty()
+            && self.heat_tiles.is_empty()
+            && self.arrows.is_empty()
+            && self.labels.is_empty()
+            && self.rects.is_empty()
+            && self.polygons.is_empty()
+            && self.paths.is_empty()
+    }
+
+    fn new() -> Self {
+        Shapes {
+            tiles: Vec::new(),
+            heat_tiles: Vec::new(),
+            arrows: Vec::new(),
+            labels: Vec::new(),
+            rects: Vec::new(),
+            polygons: Vec::new(),
+            paths: Vec::new(),
+        }
+    }
+}
+
+/// Shapes that are drawn together, either the static background or a single animation frame,
+/// indexed by layer.
+struct Scene<C> {
+    layers: Vec<Shapes<C>>,
+}
+
+impl<C> Scene<C> {
+    fn new() -> Self {
+        Scene { layers: Vec::new() }
+    }
+
+    fn shapes_mut(&mut self, layer: usize) -> &mut Shapes<C> {
+        if self.layers.len() <= layer {
+            self.layers.resize_with(layer + 1, Shapes::new);
+        }
+        &mut self.layers[layer]
+    }
+
+    /// Non-empty shapes of the given layers, in that order
+    fn ordered<'a>(
+        &'a self,
+        order: &'a [(usize, &'a str)],
+    ) -> impl Iterator<Item = (&'a str, &'a Shapes<C>)> {
+        order.iter().filter_map(|(idx, name)| {
+            self.layers
+                .get(*idx)
+                .filter(|shapes| !shapes.is_empty())
+                .map(|shapes| (*name, shapes))
+        })
+    }
+}
+
+/// Named group of shapes that is drawn and hidden together.
+struct Layer {
+    name: String,
+    z: i32,
+    visible: bool,
+}
+
+const DEFAULT_LAYER: &str = "default";
+
+/// Color gradient through evenly spaced RGB stops.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Gradient {
+    stops: Vec<[u8; 3]>,
+}
+
+impl Default for Gradient {
+    fn default() -> Self {
+        Self::heat()
+    }
+}
+
+impl Gradient {
+    pub fn new(stops: Vec<[u8; 3]>) -> Self {
+        assert!(!stops.is_empty(), "A gradient needs at least one color");
+        Gradient { stops }
+    }
+
+    /// Black over red and yellow to white
+    pub fn heat() -> Self {
+        Self::new(vec![[0, 0, 0], [160, 0, 0], [255, 160, 0], [255, 255, 255]])
+    }
+
+    pub fn grayscale() -> Self {
+        Self::new(vec![[0, 0, 0], [255, 255, 255]])
+    }
+
+    /// Perceptually uniform dark blue over green to yellow, see [`Palette::viridis`]
+    pub fn viridis() -> Self {
+        Palette::viridis().gradient()
+    }
+
+    /// Color at position `t` between 0 and 1, interpolating linearly between the stops.
+    pub fn color_at(&self, t: f64) -> [u8; 3] {
+        let t = if t.is_nan() { 0.0 } else { t.clamp(0.0, 1.0) };
+        let position = t * (self.stops.len() - 1) as f64;
+        let idx = (position.floor() as usize).min(self.stops.len() - 1);
+        let next = (idx + 1).min(self.stops.len() - 1);
+        let fraction = position - idx as f64;
+        let (from, to) = (self.stops[idx], self.stops[next]);
+        [0, 1, 2].map(|channel| {
+            let (from, to) = (f64::from(from[channel]), f64::from(to[channel]));
+            (from + (to - from) * fraction).round() as u8
+        })
+    }
+}
+
+/// Named list of colors, either picked by index for categories or interpolated like a
+/// [`Gradient`] for continuous values.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Palette {
+    colors: Vec<[u8; 3]>,
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Self::categorical()
+    }
+}
+
+impl Palette {
+    pub fn new(colors: Vec<[u8; 3]>) -> Self {
+        assert!(!colors.is_empty(), "A palette needs at least one color");
+        Palette { colors }
+    }
+
+    /// Samples of matplotlib's viridis map, which stays readable in grayscale
+    pub fn viridis() -> Self {
+        Self::new(vec![
+            [68, 1, 84],
+            [72, 40, 120],
+            [62, 74, 137],
+            [49, 104, 142],
+            [38, 130, 142],
+            [31, 158, 137],
+            [53, 183, 121],
+            [109, 205, 89],
+            [180, 222, 44],
+            [253, 231, 37],
+        ])
+    }
+
+    /// The ten distinct colors of Tableau 10
+    pub fn categorical() -> Self {
+        Self::new(vec![
+            [78, 121, 167],
+            [242, 142, 43],
+            [225, 87, 89],
+            [118, 183, 178],
+            [89, 161, 79],
+            [237, 201, 72],
+            [176, 122, 161],
+            [255, 157, 167],
+            [156, 117, 95],
+            [186, 176, 172],
+        ])
+    }
+
+    /// The Okabe-Ito colors, which stay distinguishable with all common kinds of color blindness
+    pub fn colorblind_safe() -> Self {
+        Self::new(vec![
+            [230, 159, 0],
+            [86, 180, 233],
+            [0, 158, 115],
+            [240, 228, 66],
+            [0, 114, 178],
+            [213, 94, 0],
+            [204, 121, 167],
+            [0, 0, 0],
+        ])
+    }
+
+    pub fn len(&self) -> usize {
+        self.colors.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.colors.is_empty()
+    }
+
+    /// Color of a category, starting over at the first color once all are used.
+    pub fn color_for(&self, index: usize) -> String {
+        hex_color(self.colors[index % self.colors.len()])
+    }
+
+    /// Color at position `t` between 0 and 1, interpolating between neighboring colors.
+    pub fn color_at(&self, t: f64) -> String {
+        hex_color(self.gradient().color_at(t))
+    }
+
+    /// Gradient through all colors, e.g. for heat tiles
+    pub fn gradient(&self) -> Gradient {
+        Gradient::new(self.colors.clone())
+    }
+}
+
+fn hex_color([r, g, b]: [u8; 3]) -> String {
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+/// Appearance of the SVG output, built with chained calls starting from the default style, e.g.
+/// `RenderStyle::default().cell_size(24.0).gridlines(true)`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderStyle {
+    cell_size: f64,
+    cell_stroke: Option<(String, f64)>,
+    background: Option<String>,
+    gridlines: bool,
+}
+
+impl Default for RenderStyle {
+    fn default() -> Self {
+        RenderStyle {
+            cell_size: DEFAULT_SVG_SCALE,
+            cell_stroke: None,
+            background: None,
+            gridlines: false,
+        }
+    }
+}
+
+impl RenderStyle {
+    /// Size of a grid cell, in pixels
+    pub fn cell_size(mut self, size: f64) -> Self {
+        self.cell_size = size;
+        self
+    }
+
+    /// Outlines every tile and rect, `width` is in grid units.
+    pub fn cell_stroke(mut self, color: &str, width: f64) -> Self {
+        self.cell_stroke = Some((color.to_owned(), width));
+        self
+    }
+
+    /// Fills the whole image, which is transparent otherwise.
+    pub fn background(mut self, color: &str) -> Self {
+        self.background = Some(color.to_owned());
+        self
+    }
+
+    /// Draws thin lines between all cells on top of the shapes, which makes it easy to count
+    /// cells in small examples.
+    pub fn gridlines(mut self, enabled: bool) -> Self {
+        self.gridlines = enabled;
+        self
+    }
+
+    /// Style sheet and background that go right after the opening `<svg>` tag
+    fn svg_prelude(&self, bounds: SvgBounds) -> String {
+        let mut prelude = String::new();
+        if let Some((color, width)) = &self.cell_stroke {
+            prelude.push_str(&format!(
+                r#"<style>rect:not(.background){{stroke:{};stroke-width:{}}}</style>"#,
+                color, width
+            ));
+        }
+        if let Some(color) = &self.background {
+            let (min_y, min_x, max_y, max_x) = bounds;
+            prelude.push_str(&format!(
+                r#"<rect class="background" x="{}" y="{}" width="{}" height="{}" fill="{}"/>"#,
+                min_x,
+                min_y,
+                (max_x - min_x).max(1.0),
+                (max_y - min_y).max(1.0),
+                color
+            ));
+        }
+        prelude
+    }
+
+    /// Lines along all cell borders within the bounds, if enabled
+    fn svg_gridlines(&self, bounds: SvgBounds) -> String {
+        if !self.gridlines {
+            return String::new();
+        }
+        let (min_y, min_x, max_y, max_x) = bounds;
+        let (min_y, min_x) = (min_y.floor(), min_x.floor());
+        let (max_y, max_x) = (max_y.ceil().max(min_y + 1.0), max_x.ceil().max(min_x + 1.0));
+        let mut d = String::new();
+        let mut x = min_x;
+        while x <= max_x {
+            d.push_str(&format!("M{} {}V{}", x, min_y, max_y));
+            x += 1.0;
+        }
+        let mut y = min_y;
+        while y <= max_y {
+            d.push_str(&format!("M{} {}H{}", min_x, y, max_x));
+            y += 1.0;
+        }
+        format!(
+            r##"<path class="gridlines" d="{}" fill="none" stroke="#888" stroke-width="0.03"/>"##,
+            d
+        )
+    }
+}
+
+/// Maps the values of heat tiles to colors.
+struct HeatScale {
+    min: f64,
+    max: f64,
+    gradient: Gradient,
+}
+
+impl Default for HeatScale {
+    fn default() -> Self {
+        HeatScale {
+            min: 0.0,
+            max: 1.0,
+            gradient: Gradient::default(),
+        }
+    }
+}
+
+impl HeatScale {
+    fn color(&self, value: f64) -> String {
+        let t = if self.max > self.min {
+            (value - self.min) / (self.max - self.min)
+        } else {
+            0.0
+        };
+        hex_color(self.gradient.color_at(t))
+    }
+}
+
+/// Closes a `<rect>`, nesting a `<title>` if there is a tooltip, which browsers show on hover
+fn svg_element_end(tooltip: Option<&str>) -> String {
+    match tooltip {
+        Some(tooltip) => format!("><title>{}</title></rect>", escape_xml(tooltip)),
+        None => "/>".to_owned(),
+    }
+}
+
+/// Escapes text for use in SVG content and attribute values.
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn svg_points<C: Display>(points: &[(C, C)]) -> String {
+    points
+        .iter()
+        .map(|(y, x)| format!("{},{}", x, y))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+impl<C> Shapes<C>
+where
+    C: Display,
+{
+    fn write_svg<W: Write>(&self, writer: &mut W, heat: &HeatScale) -> io::Result<()> {
+        for (y, x, maybe_color, tooltip) in &self.tiles {
+            writeln!(
+                writer,
+                r#"<rect width="1" height="1" x="{}" y="{}" fill="{}"{}"#,
+                x,
+                y,
+                maybe_color.as_deref().unwrap_or("black"),
+                svg_element_end(tooltip.as_deref())
+            )?;
+        }
+        for (y, x, value) in &self.heat_tiles {
+            writeln!(
+                writer,
+                r#"<rect width="1" height="1" x="{}" y="{}" fill="{}" data-value="{}"/>"#,
+                x,
+                y,
+                heat.color(*value),
+                value
+            )?;
+        }
+        for (y, x, direction, color) in &self.arrows {
+            writeln!(
+                writer,
+                r#"<path d="{}" fill="{}" transform="translate({} {}) rotate({} 0.5 0.5)"/>"#,
+                ARROW_PATH,
+                color,
+                x,
+                y,
+                direction.degrees()
+            )?;
+        }
+        for (y, x, w, h, maybe_color, tooltip) in &self.rects {
+            writeln!(
+                writer,
+                r#"<rect width="{}" height="{}" x="{}" y="{}" fill="{}"{}"#,
+                w,
+                h,
+                x,
+                y,
+                maybe_color.as_deref().unwrap_or("black"),
+                svg_element_end(tooltip.as_deref())
+            )?;
+        }
+        // The points are cells, so the lines are shifted to run through the cell centers
+        for polygon in &self.polygons {
+            writeln!(
+                writer,
+                r#"<polygon points="{}" fill="{}" transform="translate(0.5 0.5)"/>"#,
+                svg_points(&polygon.points),
+                polygon.fill
+            )?;
+        }
+        for path in &self.paths {
+            writeln!(
+                writer,
+                r#"<polyline points="{}" fill="none" stroke="{}" stroke-width="{}" stroke-linejoin="round" stroke-linecap="round" transform="translate(0.5 0.5)"/>"#,
+                svg_points(&path.points),
+                path.stroke,
+                path.width
+            )?;
+        }
+        // Labels go last so they stay readable on top of everything else
+        for (y, x, text, color) in &self.labels {
+            writeln!(
+                writer,
+                r#"<text x="0.5" y="0.5" transform="translate({} {})" fill="{}" font-size="0.6" font-family="monospace" text-anchor="middle" dominant-baseline="central">{}</text>"#,
+                x,
+                y,
+                color,
+                escape_xml(text)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl<C> Scene<C>
+where
+    C: Display,
+{
+    fn write_svg<W: Write>(
+        &self,
+        writer: &mut W,
+        heat: &HeatScale,
+        order: &[(usize, &str)],
+    ) -> io::Result<()> {
+        for (name, shapes) in self.ordered(order) {
+            writeln!(writer, r#"<g class="layer-{}">"#, name)?;
+            shapes.write_svg(writer, heat)?;
+            writeln!(writer, "</g>")?;
+        }
+        Ok(())
+    }
+}
+
+/// Collects colored tiles and rectangles and writes them as an image.
+///
+/// Shapes added between [`start_frame`](Self::start_frame) and [`end_frame`](Self::end_frame)
+/// make up one frame of an animation, all other shapes are a static background shown in every
+/// frame. Still images show the background and the last frame.
+///
+/// Shapes are drawn in insertion order within their layer. Layers are drawn by ascending z-order,
+/// which defaults to the order they were created in, and can be hidden as a whole.
 pub struct GridRenderer<C> {
-    tiles: Vec<(C, C, Option<String>)>,
-    rects: Vec<(C, C, C, C, Option<String>)>,
+    background: Scene<C>,
+    frames: Vec<Scene<C>>,
+    recording: bool,
+    style: RenderStyle,
+    heat_range: Option<(f64, f64)>,
+    gradient: Gradient,
+    palette: Palette,
+    layers: Vec<Layer>,
+    current_layer: usize,
+    origin: Option<(C, C)>,
+    legend: Vec<(String, String)>,
+}
+
+impl<C> Default for GridRenderer<C>
+where
+    C: Display,
+{
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl<C> GridRenderer<C>
@@ -10,19 +612,230 @@ where
     C: Display,
 {
     pub fn new() -> Self {
-        GridRenderer { tiles: Vec::new(), rects: Vec::new() }
+        GridRenderer {
+            background: Scene::new(),
+            frames: Vec::new(),
+            recording: false,
+            style: RenderStyle::default(),
+            heat_range: None,
+            gradient: Gradient::default(),
+            palette: Palette::default(),
+            layers: vec![Layer {
+                name: DEFAULT_LAYER.to_owned(),
+                z: 0,
+                visible: true,
+            }],
+            current_layer: 0,
+            origin: None,
+            legend: Vec::new(),
+        }
     }
 
-    pub fn add_colored_grid_tile(&mut self, y: C, x: C, color: String) {
-        self.tiles.push((y, x, Some(color)));
+    /// Sets the size of a grid cell in the SVG output, in pixels.
+    pub fn set_scale(&mut self, scale: f64) {
+        self.style.cell_size = scale;
     }
 
-    pub fn add_colored_rect(&mut self, y: C, x: C, h: C, w: C, color: String) {
-        self.rects.push((y, x, w, h, Some(color)));
+    /// Replaces the appearance of the SVG output, including the cell size.
+    pub fn set_style(&mut self, style: RenderStyle) {
+        self.style = style;
+    }
+
+    pub fn with_style(mut self, style: RenderStyle) -> Self {
+        self.set_style(style);
+        self
+    }
+
+    /// Pins the top left corner of the output to the given cell.
+    ///
+    /// By default the output starts at the smallest coordinates of all shapes, which may be
+    /// negative. A fixed origin keeps separate renderings comparable; shapes above or left of it
+    /// are cut off.
+    pub fn set_origin(&mut self, y: C, x: C) {
+        self.origin = Some((y, x));
+    }
+
+    /// Fixes the values mapped to the ends of the gradient. By default, the smallest and largest
+    /// value of all heat tiles are used.
+    pub fn set_heat_range(&mut self, min: f64, max: f64) {
+        self.heat_range = Some((min, max));
+    }
+
+    pub fn set_gradient(&mut self, gradient: Gradient) {
+        self.gradient = gradient;
+    }
+
+    /// Sets the colors of category tiles, see [`add_category_tile`](Self::add_category_tile).
+    pub fn set_palette(&mut self, palette: Palette) {
+        self.palette = palette;
+    }
+
+    /// Index of the layer with the given name, which is created on top of all others if needed
+    fn layer_index(&mut self, name: &str) -> usize {
+        if let Some(idx) = self.layers.iter().position(|layer| layer.name == name) {
+            return idx;
+        }
+        let z = self.layers.iter().map(|layer| layer.z).max().unwrap_or(0) + 1;
+        self.layers.push(Layer {
+            name: name.to_owned(),
+            z,
+            visible: true,
+        });
+        self.layers.len() - 1
+    }
+
+    /// Selects the layer new shapes are added to, creating it on top of the existing ones if it
+    /// doesn't exist yet. Shapes initially go to the `"default"` layer.
+    pub fn set_layer(&mut self, name: &str) {
+        self.current_layer = self.layer_index(name);
+    }
+
+    /// Sets the draw order of a layer, layers with higher values are drawn on top.
+    pub fn set_layer_z(&mut self, name: &str, z: i32) {
+        let idx = self.layer_index(name);
+        self.layers[idx].z = z;
+    }
+
+    pub fn set_layer_visible(&mut self, name: &str, visible: bool) {
+        let idx = self.layer_index(name);
+        self.layers[idx].visible = visible;
+    }
+
+    /// Indices and names of the visible layers, in drawing order
+    fn layer_order(&self) -> Vec<(usize, &str)> {
+        let mut order = self
+            .layers
+            .iter()
+            .enumerate()
+            .filter(|(_, layer)| layer.visible)
+            .collect::<Vec<_>>();
+        order.sort_by_key(|(_, layer)| layer.z);
+        order
+            .into_iter()
+            .map(|(idx, layer)| (idx, layer.name.as_str()))
+            .collect()
+    }
+
+    /// The shapes of the current layer in the scene new shapes are added to
+    fn scene(&mut self) -> &mut Shapes<C> {
+        let layer = self.current_layer;
+        match self.frames.last_mut() {
+            Some(frame) if self.recording => frame.shapes_mut(layer),
+            _ => self.background.shapes_mut(layer),
+        }
+    }
+
+    /// Adds a tile, the tooltip is shown when hovering it in a browser (SVG output only).
+    pub fn add_colored_grid_tile(&mut self, y: C, x: C, color: String, tooltip: Option<String>) {
+        self.scene().tiles.push((y, x, Some(color), tooltip));
+    }
+
+    /// Adds a rect, the tooltip is shown when hovering it in a browser (SVG output only).
+    pub fn add_colored_rect(
+        &mut self,
+        y: C,
+        x: C,
+        h: C,
+        w: C,
+        color: String,
+        tooltip: Option<String>,
+    ) {
+        self.scene().rects.push((y, x, w, h, Some(color), tooltip));
     }
 
     pub fn add_grid_tile(&mut self, y: C, x: C) {
-        self.tiles.push((y, x, None));
+        self.scene().tiles.push((y, x, None, None));
+    }
+
+    /// Adds a tile in the palette color of the given category, e.g. the kind of a pipe tile.
+    pub fn add_category_tile(&mut self, y: C, x: C, category: usize) {
+        let color = self.palette.color_for(category);
+        self.scene().tiles.push((y, x, Some(color), None));
+    }
+
+    /// Adds a tile colored by its value on the heat gradient, see
+    /// [`set_heat_range`](Self::set_heat_range) and [`set_gradient`](Self::set_gradient).
+    pub fn add_heat_tile(&mut self, y: C, x: C, value: f64) {
+        self.scene().heat_tiles.push((y, x, value));
+    }
+
+    /// Adds an arrow pointing in the given direction, e.g. to show where a beam is heading.
+    ///
+    /// Like paths, arrows are only part of the SVG output.
+    pub fn add_arrow_tile(&mut self, y: C, x: C, direction: Direction, color: String) {
+        self.scene().arrows.push((y, x, direction, color));
+    }
+
+    /// Adds text centered in a cell, e.g. a distance or the character the cell was parsed from.
+    ///
+    /// Labels are drawn on top of all other shapes of their layer and are only part of the SVG
+    /// output.
+    pub fn add_labeled_tile(&mut self, y: C, x: C, text: String, color: String) {
+        self.scene().labels.push((y, x, text, color));
+    }
+
+    /// Adds `(color, label)` entries to a legend drawn to the right of the grid, which explains
+    /// what the colors mean. The legend is shared by all frames and only part of the SVG output.
+    pub fn add_legend(&mut self, entries: &[(&str, &str)]) {
+        self.legend.extend(
+            entries
+                .iter()
+                .map(|(color, label)| (color.to_string(), label.to_string())),
+        );
+    }
+
+    /// Bounds extended by the space the legend needs next to the grid
+    fn legend_bounds(&self, bounds: SvgBounds) -> SvgBounds {
+        if self.legend.is_empty() {
+            return bounds;
+        }
+        let (min_y, min_x, max_y, max_x) = bounds;
+        let longest = self
+            .legend
+            .iter()
+            .map(|(_, label)| label.chars().count())
+            .max()
+            .unwrap_or(0);
+        let height = LEGEND_ROW_HEIGHT * self.legend.len() as f64 - (LEGEND_ROW_HEIGHT - 1.0);
+        let width = LEGEND_TEXT_OFFSET + LEGEND_CHAR_WIDTH * longest as f64;
+        (min_y, min_x, max_y.max(min_y + height), max_x + width)
+    }
+
+    /// Swatches and labels of the legend, starting at the top right of the grid bounds
+    fn svg_legend(&self, bounds: SvgBounds) -> String {
+        let (min_y, _, _, max_x) = bounds;
+        let mut legend = String::new();
+        for (idx, (color, label)) in self.legend.iter().enumerate() {
+            let y = min_y + LEGEND_ROW_HEIGHT * idx as f64;
+            legend.push_str(&format!(
+                r#"<g class="legend"><rect width="1" height="1" x="{}" y="{}" fill="{}"/><text x="{}" y="{}" font-size="0.8" font-family="monospace" dominant-baseline="central">{}</text></g>"#,
+                max_x + 1.0,
+                y,
+                color,
+                max_x + LEGEND_TEXT_OFFSET,
+                y + 0.5,
+                escape_xml(label)
+            ));
+        }
+        legend
+    }
+
+    /// The heat scale shared by all frames
+    fn heat_scale(&self) -> HeatScale {
+        let (min, max) = self.heat_range.unwrap_or_else(|| {
+            std::iter::once(&self.background)
+                .chain(&self.frames)
+                .flat_map(|scene| &scene.layers)
+                .flat_map(|shapes| shapes.heat_tiles.iter().map(|(_, _, value)| *value))
+                .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), value| {
+                    (min.min(value), max.max(value))
+                })
+        });
+        HeatScale {
+            min,
+            max,
+            gradient: self.gradient.clone(),
+        }
     }
 
     pub fn extend<I>(&mut self, iter: I)
@@ -32,41 +845,985 @@ where
         iter.for_each(|(y, x)| self.add_grid_tile(y, x));
     }
 
-    pub fn store_svg<P>(&self, path: P)
+    /// Adds a line through the centers of the given `(y, x)` cells, `width` is in grid units.
+    ///
+    /// Paths and polygons are drawn on top of the tiles and rects. They are only part of the SVG
+    /// output.
+    pub fn add_path<I>(&mut self, points: I, stroke: String, width: f64)
+    where
+        I: IntoIterator<Item = (C, C)>,
+    {
+        let points = points.into_iter().collect();
+        self.scene().paths.push(Polyline {
+            points,
+            stroke,
+            width,
+        });
+    }
+
+    /// Adds a filled polygon with corners at the centers of the given `(y, x)` cells.
+    pub fn add_polygon<I>(&mut self, points: I, fill: String)
+    where
+        I: IntoIterator<Item = (C, C)>,
+    {
+        let points = points.into_iter().collect();
+        self.scene().polygons.push(Polygon { points, fill });
+    }
+
+    /// Starts a new animation frame, ending the current one if there is one.
+    pub fn start_frame(&mut self) {
+        self.frames.push(Scene::new());
+        self.recording = true;
+    }
+
+    /// Ends the current animation frame, shapes added afterwards go to the background again.
+    pub fn end_frame(&mut self) {
+        self.recording = false;
+    }
+
+    pub fn frame_count(&self) -> usize {
+        self.frames.len()
+    }
+}
+
+impl GridRenderer<usize> {
+    /// Creates a renderer showing a whole grid, colored by the given closure. Cells it returns
+    /// `None` for are left empty.
+    pub fn from_grid<T, F>(grid: &Grid<T>, color: F) -> Self
+    where
+        F: FnMut(&T) -> Option<String>,
+    {
+        let mut renderer = Self::new();
+        renderer.add_grid(grid, color);
+        renderer
+    }
+
+    /// Adds all cells of a grid to the current layer, e.g. to draw a path on top of the field.
+    pub fn add_grid<T, F>(&mut self, grid: &Grid<T>, mut color: F)
+    where
+        F: FnMut(&T) -> Option<String>,
+    {
+        for (y, x, cell) in grid.iter() {
+            if let Some(color) = color(cell) {
+                self.add_colored_grid_tile(y, x, color, None);
+            }
+        }
+    }
+}
+
+/// Parses the subset of SVG colors used by the days: `#rgb`, `#rrggbb`, `#rrggbbaa` and the basic
+/// named colors.
+fn parse_color(color: &str) -> Result<Rgba<u8>> {
+    if let Some(hex) = color.strip_prefix('#') {
+        let digits = hex
+            .chars()
+            .map(|c| c.to_digit(16).map(|d| d as u8))
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| anyhow!("Invalid color {:?}", color))?;
+        let channels = match digits.len() {
+            3 => digits.iter().map(|d| d * 17).chain([255]).collect(),
+            6 | 8 => digits
+                .chunks(2)
+                .map(|pair| pair[0] * 16 + pair[1])
+                .chain([255])
+                .take(4)
+                .collect::<Vec<_>>(),
+            _ => bail!("Invalid color {:?}", color),
+        };
+        return Ok(Rgba([channels[0], channels[1], channels[2], channels[3]]));
+    }
+    let rgb = match color.to_ascii_lowercase().as_str() {
+        "none" | "transparent" => return Ok(Rgba([0, 0, 0, 0])),
+        "black" => [0, 0, 0],
+        "white" => [255, 255, 255],
+        "gray" | "grey" => [128, 128, 128],
+        "lightgray" | "lightgrey" => [211, 211, 211],
+        "darkgray" | "darkgrey" => [169, 169, 169],
+        "red" => [255, 0, 0],
+        "green" => [0, 128, 0],
+        "lime" => [0, 255, 0],
+        "blue" => [0, 0, 255],
+        "yellow" => [255, 255, 0],
+        "orange" => [255, 165, 0],
+        "purple" => [128, 0, 128],
+        "cyan" | "aqua" => [0, 255, 255],
+        "magenta" | "fuchsia" => [255, 0, 255],
+        "brown" => [165, 42, 42],
+        "pink" => [255, 192, 203],
+        _ => bail!("Unsupported color {:?}", color),
+    };
+    Ok(Rgba([rgb[0], rgb[1], rgb[2], 255]))
+}
+
+/// Area covered by the shapes as `(min_y, min_x, max_y, max_x)`, with exclusive maxima
+type Bounds = (i64, i64, i64, i64);
+
+fn raster_bounds(shapes: &[RasterRect]) -> Bounds {
+    (
+        shapes.iter().map(|s| s.0).min().unwrap_or(0),
+        shapes.iter().map(|s| s.1).min().unwrap_or(0),
+        shapes.iter().map(|s| s.0 + s.2).max().unwrap_or(1),
+        shapes.iter().map(|s| s.1 + s.3).max().unwrap_or(1),
+    )
+}
+
+fn rasterize<'a, I>(shapes: I, bounds: Bounds, scale: u32) -> Result<RgbaImage>
+where
+    I: IntoIterator<Item = &'a RasterRect>,
+{
+    if scale == 0 {
+        bail!("Scale must be positive");
+    }
+    let (min_y, min_x, max_y, max_x) = bounds;
+    let scale = i64::from(scale);
+    let width = (max_x - min_x).max(1) * scale;
+    let height = (max_y - min_y).max(1) * scale;
+    if width as u64 * height as u64 > MAX_PNG_PIXELS {
+        bail!("Image of {}x{} pixels is too large", width, height);
+    }
+
+    let mut image = RgbaImage::new(width as u32, height as u32);
+    for &(y, x, h, w, color) in shapes {
+        // Shapes outside of the bounds are clipped
+        let top = (y - min_y) * scale;
+        let left = (x - min_x) * scale;
+        let bottom = (top + h.max(0) * scale).min(height);
+        let right = (left + w.max(0) * scale).min(width);
+        for py in top.max(0)..bottom {
+            for px in left.max(0)..right {
+                image.put_pixel(px as u32, py as u32, color);
+            }
+        }
+    }
+    Ok(image)
+}
+
+impl<C> Shapes<C>
+where
+    C: Display + ToPrimitive,
+{
+    fn svg_bounds(&self) -> Option<SvgBounds> {
+        let coordinate = |c: &C| c.to_f64().unwrap_or(0.0);
+        let tiles = self
+            .tiles
+            .iter()
+            .map(|(y, x, _, _)| (y, x))
+            .chain(self.heat_tiles.iter().map(|(y, x, _)| (y, x)))
+            .chain(self.arrows.iter().map(|(y, x, _, _)| (y, x)))
+            .chain(self.labels.iter().map(|(y, x, _, _)| (y, x)))
+            .map(|(y, x)| {
+                let (y, x) = (coordinate(y), coordinate(x));
+                (y, x, y + 1.0, x + 1.0)
+            });
+        let rects = self.rects.iter().map(|(y, x, w, h, _, _)| {
+            let (y, x) = (coordinate(y), coordinate(x));
+            (y, x, y + coordinate(h), x + coordinate(w))
+        });
+        let points = self
+            .polygons
+            .iter()
+            .flat_map(|polygon| &polygon.points)
+            .chain(self.paths.iter().flat_map(|path| &path.points))
+            .map(|(y, x)| {
+                let (y, x) = (coordinate(y), coordinate(x));
+                (y, x, y + 1.0, x + 1.0)
+            });
+        tiles
+            .chain(rects)
+            .chain(points)
+            .reduce(|a, b| (a.0.min(b.0), a.1.min(b.1), a.2.max(b.2), a.3.max(b.3)))
+    }
+
+    /// All shapes in drawing order
+    fn raster_shapes(&self, heat: &HeatScale) -> Result<Vec<RasterRect>> {
+        let coordinate = |c: &C| {
+            c.to_i64()
+                .ok_or_else(|| anyhow!("Coordinate {} is not representable as a pixel", c))
+        };
+        let color = |c: &Option<String>| parse_color(c.as_deref().unwrap_or("black"));
+        let tiles = self
+            .tiles
+            .iter()
+            .map(|(y, x, c, _)| Ok((coordinate(y)?, coordinate(x)?, 1, 1, color(c)?)));
+        let heat_tiles = self.heat_tiles.iter().map(|(y, x, value)| {
+            Ok((
+                coordinate(y)?,
+                coordinate(x)?,
+                1,
+                1,
+                parse_color(&heat.color(*value))?,
+            ))
+        });
+        let rects = self.rects.iter().map(|(y, x, w, h, c, _)| {
+            Ok((
+                coordinate(y)?,
+                coordinate(x)?,
+                coordinate(h)?,
+                coordinate(w)?,
+                color(c)?,
+            ))
+        });
+        tiles.chain(heat_tiles).chain(rects).collect()
+    }
+}
+
+impl<C> Scene<C>
+where
+    C: Display + ToPrimitive,
+{
+    fn svg_bounds(&self, order: &[(usize, &str)]) -> Option<SvgBounds> {
+        self.ordered(order)
+            .filter_map(|(_, shapes)| shapes.svg_bounds())
+            .reduce(|a, b| (a.0.min(b.0), a.1.min(b.1), a.2.max(b.2), a.3.max(b.3)))
+    }
+
+    fn raster_shapes(&self, heat: &HeatScale, order: &[(usize, &str)]) -> Result<Vec<RasterRect>> {
+        let mut rects = Vec::new();
+        for (_, shapes) in self.ordered(order) {
+            rects.extend(shapes.raster_shapes(heat)?);
+        }
+        Ok(rects)
+    }
+}
+
+impl<C> GridRenderer<C>
+where
+    C: Display + ToPrimitive,
+{
+    fn background_and_last_frame(&self) -> impl Iterator<Item = &Scene<C>> {
+        std::iter::once(&self.background).chain(self.frames.last())
+    }
+
+    /// Bounds of the visible layers of the scenes
+    fn svg_bounds<'a, I>(&self, scenes: I) -> SvgBounds
+    where
+        I: Iterator<Item = &'a Scene<C>>,
+        C: 'a,
+    {
+        let order = self.layer_order();
+        let bounds = scenes
+            .filter_map(|scene| scene.svg_bounds(&order))
+            .reduce(|a, b| (a.0.min(b.0), a.1.min(b.1), a.2.max(b.2), a.3.max(b.3)));
+        let coordinate = |c: &C| c.to_f64().unwrap_or(0.0);
+        match (&self.origin, bounds) {
+            (Some((y, x)), Some((_, _, max_y, max_x))) => {
+                let (y, x) = (coordinate(y), coordinate(x));
+                (y, x, max_y.max(y + 1.0), max_x.max(x + 1.0))
+            }
+            (Some((y, x)), None) => {
+                let (y, x) = (coordinate(y), coordinate(x));
+                (y, x, y + 1.0, x + 1.0)
+            }
+            (None, bounds) => bounds.unwrap_or((0.0, 0.0, 1.0, 1.0)),
+        }
+    }
+
+    /// Area of the raster output, see [`set_origin`](Self::set_origin)
+    fn raster_view(&self, shapes: &[RasterRect]) -> Result<Bounds> {
+        let bounds = raster_bounds(shapes);
+        let Some((y, x)) = &self.origin else {
+            return Ok(bounds);
+        };
+        let coordinate = |c: &C| {
+            c.to_i64()
+                .ok_or_else(|| anyhow!("Coordinate {} is not representable as a pixel", c))
+        };
+        let (y, x) = (coordinate(y)?, coordinate(x)?);
+        let (max_y, max_x) = if shapes.is_empty() {
+            (y + 1, x + 1)
+        } else {
+            (bounds.2.max(y + 1), bounds.3.max(x + 1))
+        };
+        Ok((y, x, max_y, max_x))
+    }
+
+    /// Opening `<svg>` tag whose viewBox covers the bounds, so shapes at large or negative
+    /// coordinates end up in view, followed by the styling of [`RenderStyle`].
+    fn svg_header(&self, bounds: SvgBounds) -> String {
+        let bounds = self.legend_bounds(bounds);
+        let (min_y, min_x, max_y, max_x) = bounds;
+        let width = (max_x - min_x).max(1.0);
+        let height = (max_y - min_y).max(1.0);
+        format!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" viewBox="{} {} {} {}" shape-rendering="crispEdges">{}"#,
+            width * self.style.cell_size,
+            height * self.style.cell_size,
+            min_x,
+            min_y,
+            width,
+            height,
+            self.style.svg_prelude(bounds)
+        )
+    }
+
+    /// Overlays that go on top of all shapes, and the closing `</svg>` tag
+    fn svg_footer(&self, bounds: SvgBounds) -> String {
+        format!(
+            "{}{}</svg>",
+            self.style.svg_gridlines(bounds),
+            self.svg_legend(bounds)
+        )
+    }
+
+    /// Writes the SVG image, showing the background and the last frame, e.g. to a buffer or an
+    /// HTTP response.
+    pub fn render_svg<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        let bounds = self.svg_bounds(self.background_and_last_frame());
+        writer.write_all(self.svg_header(bounds).as_bytes())?;
+        let heat = self.heat_scale();
+        let order = self.layer_order();
+        self.background.write_svg(&mut writer, &heat, &order)?;
+        if let Some(frame) = self.frames.last() {
+            frame.write_svg(&mut writer, &heat, &order)?;
+        }
+        writer.write_all(self.svg_footer(bounds).as_bytes())
+    }
+
+    pub fn store_svg<P>(&self, path: P) -> io::Result<()>
     where
         P: AsRef<Path>,
     {
-        let mut file = File::create(path).unwrap();
-        file.write_all(br#"<svg xmlns="http://www.w3.org/2000/svg">"#)
-            .unwrap();
-        for (y, x, maybe_color) in &self.tiles {
-            writeln!(
-                file,
-                r#"<rect width="1" height="1" x="{}" y="{}" fill="{}"/>"#,
-                x,
-                y,
-                maybe_color
-                    .as_ref()
-                    .map(|s| s.to_owned())
-                    .unwrap_or_else(|| "black".to_owned())
-            )
-            .unwrap();
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.render_svg(&mut writer)?;
+        writer.flush()
+    }
+
+    /// Stores the SVG image in an HTML page that allows panning (drag) and zooming (mouse wheel)
+    /// and shows the coordinates and value of the hovered tile, which keeps huge grids usable.
+    pub fn store_html<P>(&self, path: P) -> Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        let mut writer = BufWriter::new(File::create(path)?);
+        writer.write_all(HTML_PREFIX.as_bytes())?;
+        self.render_svg(&mut writer)?;
+        writer.write_all(HTML_SUFFIX.as_bytes())?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Stores all frames as an SVG that uses SMIL to show them one after another, in a loop.
+    pub fn store_animated_svg<P>(&self, path: P, frame_delay: Duration) -> Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        if self.frames.is_empty() {
+            bail!("No frames recorded");
         }
-        for (y, x, w, h, maybe_color) in &self.rects {
+        let mut writer = BufWriter::new(File::create(path)?);
+        let bounds = self.svg_bounds(std::iter::once(&self.background).chain(&self.frames));
+        writeln!(writer, "{}", self.svg_header(bounds))?;
+        let heat = self.heat_scale();
+        let order = self.layer_order();
+        self.background.write_svg(&mut writer, &heat, &order)?;
+
+        let count = self.frames.len();
+        let total = frame_delay.as_secs_f64() * count as f64;
+        for (idx, frame) in self.frames.iter().enumerate() {
+            // Each frame is only visible during its share of the loop
+            let (values, key_times) = if idx == 0 {
+                ("visible;hidden", format!("0;{}", 1.0 / count as f64))
+            } else {
+                (
+                    "hidden;visible;hidden",
+                    format!(
+                        "0;{};{}",
+                        idx as f64 / count as f64,
+                        (idx + 1) as f64 / count as f64
+                    ),
+                )
+            };
+            writeln!(writer, r#"<g visibility="hidden">"#)?;
             writeln!(
-                file,
-                r#"<rect width="{}" height="{}" x="{}" y="{}" fill="{}"/>"#,
-                w,
-                h,
-                x,
-                y,
-                maybe_color
-                    .as_ref()
-                    .map(|s| s.to_owned())
-                    .unwrap_or_else(|| "black".to_owned())
-            )
+                writer,
+                r#"<animate attributeName="visibility" calcMode="discrete" values="{}" keyTimes="{}" dur="{}s" repeatCount="indefinite"/>"#,
+                values, key_times, total
+            )?;
+            frame.write_svg(&mut writer, &heat, &order)?;
+            writeln!(writer, "</g>")?;
+        }
+        writeln!(writer, "{}", self.svg_footer(bounds))?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Renders all tiles and rects to a bitmap with `scale` pixels per grid unit.
+    ///
+    /// Unlike the SVG output this stays fast to view for huge grids. Like the SVG output, the
+    /// image is cropped to the drawn shapes unless an origin is set, so negative coordinates are
+    /// fine.
+    pub fn render_png(&self, scale: u32) -> Result<RgbaImage> {
+        let shapes = self.still_raster_shapes()?;
+        rasterize(&shapes, self.raster_view(&shapes)?, scale)
+    }
+
+    /// Shapes of the background and the last frame, as shown in still images
+    fn still_raster_shapes(&self) -> Result<Vec<RasterRect>> {
+        let heat = self.heat_scale();
+        let order = self.layer_order();
+        let mut shapes = self.background.raster_shapes(&heat, &order)?;
+        if let Some(frame) = self.frames.last() {
+            shapes.extend(frame.raster_shapes(&heat, &order)?);
+        }
+        Ok(shapes)
+    }
+
+    pub fn store_png<P>(&self, path: P, scale: u32) -> Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        self.render_png(scale)?.save(path)?;
+        Ok(())
+    }
+
+    /// Renders every frame on top of the background. All frames share the same bounds, so they
+    /// line up when played back.
+    pub fn render_frames(&self, scale: u32) -> Result<Vec<RgbaImage>> {
+        let heat = self.heat_scale();
+        let order = self.layer_order();
+        let background = self.background.raster_shapes(&heat, &order)?;
+        let frames = self
+            .frames
+            .iter()
+            .map(|frame| frame.raster_shapes(&heat, &order))
+            .collect::<Result<Vec<_>>>()?;
+        let all_shapes = background
+            .iter()
+            .chain(frames.iter().flatten())
+            .copied()
+            .collect::<Vec<_>>();
+        let bounds = self.raster_view(&all_shapes)?;
+        frames
+            .iter()
+            .map(|frame| rasterize(background.iter().chain(frame), bounds, scale))
+            .collect()
+    }
+
+    /// Stores all frames as a looping animated GIF.
+    pub fn store_gif<P>(&self, path: P, scale: u32, frame_delay: Duration) -> Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        if self.frames.is_empty() {
+            bail!("No frames recorded");
+        }
+        let delay = Delay::from_saturating_duration(frame_delay);
+        let mut encoder = GifEncoder::new(BufWriter::new(File::create(path)?));
+        encoder.set_repeat(Repeat::Infinite)?;
+        encoder.encode_frames(
+            self.render_frames(scale)?
+                .into_iter()
+                .map(|image| Frame::from_parts(image, 0, 0, delay)),
+        )?;
+        Ok(())
+    }
+}
+
+/// Writes the frames of a long animation to disk one at a time, so only the current frame needs
+/// to be kept in memory.
+///
+/// Each frame is a separate PNG in the output directory. The frames are listed in
+/// `frames.ffconcat`, which also holds their timing and can be turned into a video with
+/// `ffmpeg -i frames.ffconcat animation.mp4`.
+pub struct FrameWriter {
+    dir: PathBuf,
+    scale: u32,
+    frame_delay: Duration,
+    bounds: Option<Bounds>,
+    manifest: BufWriter<File>,
+    count: usize,
+}
+
+impl FrameWriter {
+    pub fn create<P>(dir: P, scale: u32, frame_delay: Duration) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+        let mut manifest = BufWriter::new(File::create(dir.join("frames.ffconcat"))?);
+        writeln!(manifest, "ffconcat version 1.0")?;
+        Ok(FrameWriter {
+            dir,
+            scale,
+            frame_delay,
+            bounds: None,
+            manifest,
+            count: 0,
+        })
+    }
+
+    /// Renders the still image of the renderer as the next frame.
+    ///
+    /// All frames use the bounds of the first one, so they line up. Anything outside of them is
+    /// cut off.
+    pub fn write_frame<C>(&mut self, renderer: &GridRenderer<C>) -> Result<()>
+    where
+        C: Display + ToPrimitive,
+    {
+        let shapes = renderer.still_raster_shapes()?;
+        let bounds = match self.bounds {
+            Some(bounds) => bounds,
+            None => *self.bounds.insert(renderer.raster_view(&shapes)?),
+        };
+        let name = format!("frame_{:06}.png", self.count);
+        rasterize(&shapes, bounds, self.scale)?.save(self.dir.join(&name))?;
+        writeln!(self.manifest, "file '{}'", name)?;
+        writeln!(self.manifest, "duration {}", self.frame_delay.as_secs_f64())?;
+        // Keep the manifest usable even if the simulation is interrupted
+        self.manifest.flush()?;
+        self.count += 1;
+        Ok(())
+    }
+
+    /// Finishes the animation, returning the number of frames written.
+    pub fn finish(mut self) -> Result<usize> {
+        self.manifest.flush()?;
+        Ok(self.count)
+    }
+}
+
+/// Draws tiles and rects as colored blocks straight to the terminal, which is quicker for
+/// debugging than opening an SVG.
+///
+/// Every call to [`draw`](Self::draw) shows the collected shapes and starts over with an empty
+/// grid. With frame clearing enabled, each draw replaces the previous one on screen, so a
+/// simulation can be watched live.
+pub struct TermRenderer<C> {
+    scene: Shapes<C>,
+    clear_frames: bool,
+}
+
+impl<C> Default for TermRenderer<C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C> TermRenderer<C> {
+    pub fn new() -> Self {
+        TermRenderer {
+            scene: Shapes::new(),
+            clear_frames: false,
+        }
+    }
+
+    pub fn with_frame_clearing(mut self, clear_frames: bool) -> Self {
+        self.clear_frames = clear_frames;
+        self
+    }
+
+    pub fn add_colored_grid_tile(&mut self, y: C, x: C, color: String) {
+        self.scene.tiles.push((y, x, Some(color), None));
+    }
+
+    pub fn add_colored_rect(&mut self, y: C, x: C, h: C, w: C, color: String) {
+        self.scene.rects.push((y, x, w, h, Some(color), None));
+    }
+
+    pub fn add_grid_tile(&mut self, y: C, x: C) {
+        self.scene.tiles.push((y, x, None, None));
+    }
+
+    pub fn extend<I>(&mut self, iter: I)
+    where
+        I: Iterator<Item = (C, C)>,
+    {
+        iter.for_each(|(y, x)| self.add_grid_tile(y, x));
+    }
+}
+
+impl<C> TermRenderer<C>
+where
+    C: Display + ToPrimitive,
+{
+    /// Renders the shapes as lines of ANSI escaped blocks, two characters per tile so they come
+    /// out roughly square.
+    pub fn render(&self) -> Result<String> {
+        let shapes = self.scene.raster_shapes(&HeatScale::default())?;
+        let image = rasterize(&shapes, raster_bounds(&shapes), 1)?;
+        let mut output = String::new();
+        for row in image.rows() {
+            let mut current = None;
+            for pixel in row {
+                let color = (pixel[3] > 0).then_some(pixel);
+                if color != current {
+                    match color {
+                        Some(Rgba([r, g, b, _])) => {
+                            output.push_str(&format!("\x1b[38;2;{};{};{}m", r, g, b))
+                        }
+                        None => output.push_str("\x1b[0m"),
+                    }
+                    current = color;
+                }
+                output.push_str(if color.is_some() {
+                    "\u{2588}\u{2588}"
+                } else {
+                    "  "
+                });
+            }
+            if current.is_some() {
+                output.push_str("\x1b[0m");
+            }
+            output.push('\n');
+        }
+        Ok(output)
+    }
+
+    /// Prints the current frame to stdout and clears the grid for the next one.
+    pub fn draw(&mut self) -> Result<()> {
+        let frame = self.render()?;
+        let mut stdout = io::stdout().lock();
+        if self.clear_frames {
+            // Move the cursor home and clear the screen
+            stdout.write_all(b"\x1b[H\x1b[2J")?;
+        }
+        stdout.write_all(frame.as_bytes())?;
+        stdout.flush()?;
+        self.scene = Shapes::new();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_color() {
+        assert_eq!(parse_color("gray").unwrap(), Rgba([128, 128, 128, 255]));
+        assert_eq!(parse_color("#f80").unwrap(), Rgba([255, 136, 0, 255]));
+        assert_eq!(parse_color("#12ab34").unwrap(), Rgba([18, 171, 52, 255]));
+        assert_eq!(parse_color("#12ab3480").unwrap(), Rgba([18, 171, 52, 128]));
+        assert!(parse_color("#12ab3").is_err());
+        assert!(parse_color("chartreuse-ish").is_err());
+    }
+
+    #[test]
+    fn test_render_png() {
+        let mut renderer = GridRenderer::new();
+        renderer.add_grid_tile(-1, -1);
+        renderer.add_colored_rect(0, 1, 1, 2, "#ff0000".to_owned(), None);
+        renderer.add_colored_grid_tile(0, 2, "white".to_owned(), None);
+
+        let image = renderer.render_png(2).unwrap();
+        assert_eq!(image.dimensions(), (8, 4));
+        assert_eq!(*image.get_pixel(1, 1), Rgba([0, 0, 0, 255]));
+        assert_eq!(*image.get_pixel(2, 2), Rgba([0, 0, 0, 0]));
+        assert_eq!(*image.get_pixel(4, 3), Rgba([255, 0, 0, 255]));
+        // Rects are drawn after the tiles, just like in the SVG output
+        assert_eq!(*image.get_pixel(6, 2), Rgba([255, 0, 0, 255]));
+    }
+
+    #[test]
+    fn test_svg_view_box() {
+        let mut renderer = GridRenderer::new();
+        renderer.add_grid_tile(-3, 2);
+        renderer.add_colored_rect(1, -1, 2, 4, "red".to_owned(), None);
+        renderer.set_scale(5.0);
+
+        let mut svg = Vec::new();
+        renderer.render_svg(&mut svg).unwrap();
+        let svg = String::from_utf8(svg).unwrap();
+        assert!(svg.starts_with(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="20" height="30" viewBox="-1 -3 4 6""#
+        ));
+    }
+
+    #[test]
+    fn test_render_style() {
+        let mut renderer = GridRenderer::new().with_style(
+            RenderStyle::default()
+                .cell_size(20.0)
+                .cell_stroke("#444", 0.05)
+                .background("white")
+                .gridlines(true),
+        );
+        renderer.add_grid_tile(0, 0);
+        renderer.add_grid_tile(1, 2);
+
+        let mut svg = Vec::new();
+        renderer.render_svg(&mut svg).unwrap();
+        let svg = String::from_utf8(svg).unwrap();
+        assert!(svg.contains(r#"width="60" height="40""#));
+        assert!(svg.contains("rect:not(.background){stroke:#444;stroke-width:0.05}"));
+        assert!(svg.contains(
+            r#"<rect class="background" x="0" y="0" width="3" height="2" fill="white"/>"#
+        ));
+        assert!(svg.contains(r#"d="M0 0V2M1 0V2M2 0V2M3 0V2M0 0H3M0 1H3M0 2H3""#));
+        // The gridlines are an overlay on top of all shapes
+        assert!(svg.find("gridlines") > svg.rfind("<rect"));
+    }
+
+    #[test]
+    fn test_origin() {
+        let mut renderer = GridRenderer::new();
+        renderer.add_grid_tile(-2, -5);
+        renderer.add_grid_tile(1, 1);
+        assert_eq!(renderer.render_png(1).unwrap().dimensions(), (7, 4));
+
+        renderer.set_origin(-1, -1);
+        let image = renderer.render_png(1).unwrap();
+        assert_eq!(image.dimensions(), (3, 3));
+        assert_eq!(*image.get_pixel(2, 2), Rgba([0, 0, 0, 255]));
+        assert_eq!(
+            renderer.svg_bounds(renderer.background_and_last_frame()),
+            (-1.0, -1.0, 2.0, 2.0)
+        );
+    }
+
+    #[test]
+    fn test_paths_and_polygons() {
+        let mut renderer = GridRenderer::new();
+        renderer.add_path([(0, 0), (0, 3), (2, 3)], "red".to_owned(), 0.2);
+        renderer.add_polygon(vec![(-1, -1), (-1, 1), (1, 1)], "gray".to_owned());
+
+        let mut svg = Vec::new();
+        let shapes = &renderer.background.layers[0];
+        shapes.write_svg(&mut svg, &HeatScale::default()).unwrap();
+        let svg = String::from_utf8(svg).unwrap();
+        assert!(svg.contains(r#"<polygon points="-1,-1 1,-1 1,1" fill="gray""#));
+        assert!(svg.contains(
+            r#"<polyline points="0,0 3,0 3,2" fill="none" stroke="red" stroke-width="0.2""#
+        ));
+        assert_eq!(shapes.svg_bounds(), Some((-1.0, -1.0, 3.0, 4.0)));
+    }
+
+    #[test]
+    fn test_arrow_tiles() {
+        let mut renderer = GridRenderer::new();
+        renderer.add_arrow_tile(2, 3, Direction::Left, "red".to_owned());
+
+        let mut svg = Vec::new();
+        let shapes = &renderer.background.layers[0];
+        shapes.write_svg(&mut svg, &HeatScale::default()).unwrap();
+        let svg = String::from_utf8(svg).unwrap();
+        assert!(svg.contains(r#"fill="red" transform="translate(3 2) rotate(270 0.5 0.5)""#));
+        assert_eq!(shapes.svg_bounds(), Some((2.0, 3.0, 3.0, 4.0)));
+    }
+
+    #[test]
+    fn test_labeled_tiles() {
+        let mut renderer = GridRenderer::new();
+        renderer.add_labeled_tile(1, 4, "<7>".to_owned(), "white".to_owned());
+
+        let mut svg = Vec::new();
+        let shapes = &renderer.background.layers[0];
+        shapes.write_svg(&mut svg, &HeatScale::default()).unwrap();
+        let svg = String::from_utf8(svg).unwrap();
+        assert!(svg.contains(r#"transform="translate(4 1)" fill="white""#));
+        assert!(svg.contains(">&lt;7&gt;</text>"));
+        assert_eq!(shapes.svg_bounds(), Some((1.0, 4.0, 2.0, 5.0)));
+    }
+
+    #[test]
+    fn test_layers() {
+        let mut renderer = GridRenderer::new();
+        renderer.set_layer("loop");
+        renderer.add_colored_grid_tile(0, 0, "red".to_owned(), None);
+        renderer.set_layer("pipes");
+        renderer.add_colored_grid_tile(0, 0, "blue".to_owned(), None);
+        renderer.set_layer("enclosed");
+        renderer.add_colored_grid_tile(1, 1, "green".to_owned(), None);
+
+        let pixel = |renderer: &GridRenderer<i32>| *renderer.render_png(1).unwrap().get_pixel(0, 0);
+        assert_eq!(pixel(&renderer), Rgba([0, 0, 255, 255]));
+        renderer.set_layer_z("loop", 10);
+        assert_eq!(pixel(&renderer), Rgba([255, 0, 0, 255]));
+        renderer.set_layer_visible("loop", false);
+        assert_eq!(pixel(&renderer), Rgba([0, 0, 255, 255]));
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("test.svg");
+        renderer.store_svg(&path).unwrap();
+        let svg = std::fs::read_to_string(path).unwrap();
+        let pipes = svg.find(r#"<g class="layer-pipes">"#).unwrap();
+        let enclosed = svg.find(r#"<g class="layer-enclosed">"#).unwrap();
+        assert!(pipes < enclosed);
+        assert!(!svg.contains("layer-loop"));
+        assert!(!svg.contains("layer-default"));
+    }
+
+    #[test]
+    fn test_from_grid() {
+        let grid = Grid::from_rows(vec![vec!['O', '.'], vec!['#', 'O']]).unwrap();
+        let renderer = GridRenderer::from_grid(&grid, |cell| match cell {
+            'O' => Some("red".to_owned()),
+            '#' => Some("gray".to_owned()),
+            _ => None,
+        });
+        let image = renderer.render_png(1).unwrap();
+        assert_eq!(image.dimensions(), (2, 2));
+        assert_eq!(*image.get_pixel(0, 0), Rgba([255, 0, 0, 255]));
+        assert_eq!(*image.get_pixel(1, 0), Rgba([0, 0, 0, 0]));
+        assert_eq!(*image.get_pixel(0, 1), Rgba([128, 128, 128, 255]));
+    }
+
+    #[test]
+    fn test_legend() {
+        let mut renderer = GridRenderer::new();
+        renderer.add_colored_grid_tile(0, 0, "red".to_owned(), None);
+        renderer.add_legend(&[("red", "inside"), ("blue", "a & b")]);
+
+        let mut svg = Vec::new();
+        renderer.render_svg(&mut svg).unwrap();
+        let svg = String::from_utf8(svg).unwrap();
+        // 1 cell of grid, 2.5 for the swatches and 6 characters of labels
+        assert!(svg.contains(r#"viewBox="0 0 6.5 2.5""#));
+        assert!(svg.contains(r#"<rect width="1" height="1" x="2" y="1.5" fill="blue"/>"#));
+        assert!(svg.contains(">a &amp; b</text>"));
+    }
+
+    #[test]
+    fn test_tooltips() {
+        let mut renderer = GridRenderer::new();
+        renderer.add_colored_grid_tile(0, 0, "red".to_owned(), Some("loss <3>".to_owned()));
+        renderer.add_colored_rect(1, 0, 1, 2, "blue".to_owned(), None);
+
+        let mut svg = Vec::new();
+        renderer.render_svg(&mut svg).unwrap();
+        let svg = String::from_utf8(svg).unwrap();
+        assert!(svg.contains(
+            r#"<rect width="1" height="1" x="0" y="0" fill="red"><title>loss &lt;3&gt;</title></rect>"#
+        ));
+        assert!(svg.contains(r#"<rect width="2" height="1" x="0" y="1" fill="blue"/>"#));
+    }
+
+    #[test]
+    fn test_palettes() {
+        let palette = Palette::colorblind_safe();
+        assert_eq!(palette.color_for(0), "#e69f00");
+        assert_eq!(palette.color_for(palette.len()), "#e69f00");
+        assert_eq!(Palette::viridis().color_at(0.0), "#440154");
+        assert_eq!(Palette::viridis().color_at(1.0), "#fde725");
+        assert_eq!(Gradient::viridis().color_at(2.0), [253, 231, 37]);
+
+        let mut renderer = GridRenderer::new();
+        renderer.set_palette(Palette::new(vec![[255, 0, 0], [0, 0, 255]]));
+        renderer.add_category_tile(0, 0, 1);
+        renderer.add_category_tile(0, 1, 2);
+        let image = renderer.render_png(1).unwrap();
+        assert_eq!(*image.get_pixel(0, 0), Rgba([0, 0, 255, 255]));
+        assert_eq!(*image.get_pixel(1, 0), Rgba([255, 0, 0, 255]));
+    }
+
+    #[test]
+    fn test_heat_tiles() {
+        let gradient = Gradient::new(vec![[0, 0, 0], [255, 0, 0], [255, 255, 255]]);
+        assert_eq!(gradient.color_at(0.25), [128, 0, 0]);
+        assert_eq!(gradient.color_at(0.75), [255, 128, 128]);
+        assert_eq!(gradient.color_at(7.0), [255, 255, 255]);
+
+        let mut renderer = GridRenderer::new();
+        renderer.set_gradient(gradient);
+        renderer.add_heat_tile(0, 0, 10.0);
+        renderer.add_heat_tile(0, 1, 20.0);
+        renderer.add_heat_tile(0, 2, 30.0);
+        let image = renderer.render_png(1).unwrap();
+        assert_eq!(*image.get_pixel(0, 0), Rgba([0, 0, 0, 255]));
+        assert_eq!(*image.get_pixel(1, 0), Rgba([255, 0, 0, 255]));
+        assert_eq!(*image.get_pixel(2, 0), Rgba([255, 255, 255, 255]));
+
+        renderer.set_heat_range(0.0, 40.0);
+        let image = renderer.render_png(1).unwrap();
+        assert_eq!(*image.get_pixel(0, 0), Rgba([128, 0, 0, 255]));
+    }
+
+    #[test]
+    fn test_store_html() {
+        let mut renderer = GridRenderer::new();
+        renderer.add_heat_tile(0, 0, 12.5);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("test.html");
+        renderer.store_html(&path).unwrap();
+        let html = std::fs::read_to_string(path).unwrap();
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains(r#"data-value="12.5""#));
+        let svg_start = html.find("<svg").unwrap();
+        assert!(svg_start < html.find("</svg>").unwrap());
+        assert!(html.find("<script>").unwrap() > svg_start);
+    }
+
+    #[test]
+    fn test_frames() {
+        let mut renderer = GridRenderer::new();
+        renderer.add_grid_tile(0, 0);
+        for x in 1..=3 {
+            renderer.start_frame();
+            renderer.add_colored_grid_tile(0, x, "red".to_owned(), None);
+            renderer.end_frame();
+        }
+        assert_eq!(renderer.frame_count(), 3);
+
+        let frames = renderer.render_frames(1).unwrap();
+        assert_eq!(frames.len(), 3);
+        for (idx, frame) in frames.iter().enumerate() {
+            assert_eq!(frame.dimensions(), (4, 1));
+            assert_eq!(*frame.get_pixel(0, 0), Rgba([0, 0, 0, 255]));
+            for x in 1..=3 {
+                let expected = if x == idx as u32 + 1 { 255 } else { 0 };
+                assert_eq!(frame.get_pixel(x, 0)[0], expected);
+            }
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        renderer
+            .store_gif(dir.path().join("test.gif"), 2, Duration::from_millis(100))
+            .unwrap();
+        let svg_path = dir.path().join("test.svg");
+        renderer
+            .store_animated_svg(&svg_path, Duration::from_millis(500))
             .unwrap();
+        let svg = std::fs::read_to_string(svg_path).unwrap();
+        assert_eq!(svg.matches("<animate ").count(), 3);
+        assert!(svg.contains(
+            r#"values="hidden;visible;hidden" keyTimes="0;0.6666666666666666;1" dur="1.5s""#
+        ));
+    }
+
+    #[test]
+    fn test_frame_writer() {
+        let dir = tempfile::tempdir().unwrap();
+        let frames_dir = dir.path().join("frames");
+        let mut writer = FrameWriter::create(&frames_dir, 2, Duration::from_millis(250)).unwrap();
+        for x in 0..3 {
+            let mut renderer = GridRenderer::new();
+            renderer.add_grid_tile(0, 0);
+            renderer.add_grid_tile(1, x);
+            writer.write_frame(&renderer).unwrap();
         }
-        file.write_all(b"</svg>").unwrap();
+        assert_eq!(writer.finish().unwrap(), 3);
+
+        let manifest = std::fs::read_to_string(frames_dir.join("frames.ffconcat")).unwrap();
+        assert_eq!(
+            manifest.lines().take(3).collect::<Vec<_>>(),
+            [
+                "ffconcat version 1.0",
+                "file 'frame_000000.png'",
+                "duration 0.25"
+            ]
+        );
+        // The bounds of the first frame are kept, the tile outside of them is clipped
+        let last = image::open(frames_dir.join("frame_000002.png"))
+            .unwrap()
+            .into_rgba8();
+        assert_eq!(last.dimensions(), (2, 4));
+        assert_eq!(*last.get_pixel(0, 3), Rgba([0, 0, 0, 0]));
+    }
+
+    #[test]
+    fn test_term_renderer() {
+        let mut renderer = TermRenderer::new();
+        renderer.add_grid_tile(0, 0);
+        renderer.add_colored_grid_tile(0, 1, "red".to_owned());
+        renderer.add_colored_grid_tile(1, 2, "red".to_owned());
+        assert_eq!(
+            renderer.render().unwrap(),
+            concat!(
+                "\x1b[38;2;0;0;0m\u{2588}\u{2588}\x1b[38;2;255;0;0m\u{2588}\u{2588}\x1b[0m  \n",
+                "    \x1b[38;2;255;0;0m\u{2588}\u{2588}\x1b[0m\n",
+            )
+        );
     }
 }
@@ -0,0 +1,312 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::OnceLock,
+    time::{Duration, Instant},
+};
+
+use anyhow::{bail, Result};
+use itertools::Itertools;
+
+use crate::{
+    answer::Answer,
+    cli::Options,
+    history::{self, TimingRecord},
+    timing::take_phases,
+};
+
+pub type Solver = fn(&Path) -> Result<Answer>;
+
+static OPTIONS: OnceLock<Options> = OnceLock::new();
+
+/// The command line options the runner was started with.
+///
+/// Days can use this to read their own flags. Outside of the runner (e.g. in tests), no options
+/// are set.
+pub fn options() -> &'static Options {
+    OPTIONS.get_or_init(Options::default)
+}
+
+/// Name under which the `part1`/`part2` implementations of a day can be selected.
+pub const DEFAULT_ALGORITHM: &str = "default";
+
+/// Uniform interface of a single puzzle day.
+///
+/// The day binaries implement this on a marker type and hand it to [`run`], which takes care of
+/// locating the input and reporting the answers.
+///
+/// `part1` and `part2` should be the fastest known implementations. Slower or experimental
+/// implementations can be registered as named alternatives and selected with `--algo NAME`.
+pub trait AocDay {
+    const YEAR: u16;
+    const DAY: u8;
+
+    fn part1(input: &Path) -> Result<Answer>;
+    fn part2(input: &Path) -> Result<Answer>;
+
+    fn part1_alternatives() -> Vec<(&'static str, Solver)> {
+        Vec::new()
+    }
+
+    fn part2_alternatives() -> Vec<(&'static str, Solver)> {
+        Vec::new()
+    }
+}
+
+/// Picks the implementation with the given name, falling back to the default one for parts that
+/// don't provide an implementation of that name. Returns the name of the picked implementation
+/// along with it.
+fn select_solver(
+    default: Solver,
+    alternatives: &[(&'static str, Solver)],
+    algo: Option<&str>,
+) -> (&'static str, Solver) {
+    algo.and_then(|algo| alternatives.iter().find(|(name, _)| *name == algo))
+        .copied()
+        .unwrap_or((DEFAULT_ALGORITHM, default))
+}
+
+fn check_algorithm<D: AocDay>(algo: &str) -> Result<()> {
+    let alternatives = D::part1_alternatives()
+        .into_iter()
+        .chain(D::part2_alternatives())
+        .map(|(name, _)| name)
+        .collect::<Vec<_>>();
+    if algo != DEFAULT_ALGORITHM && !alternatives.contains(&algo) {
+        bail!(
+            "Unknown algorithm {:?}, available: {}",
+            algo,
+            std::iter::once(DEFAULT_ALGORITHM)
+                .chain(alternatives)
+                .unique()
+                .join(", ")
+        );
+    }
+    Ok(())
+}
+
+/// Location of the puzzle input by convention: `input/<year>/dayNN.txt`
+pub fn input_path(year: u16, day: u8) -> PathBuf {
+    PathBuf::from("input")
+        .join(year.to_string())
+        .join(format!("day{:02}.txt", day))
+}
+
+fn format_timings(total: Duration, phases: &[(&'static str, Duration)]) -> String {
+    // Everything that was not explicitly attributed to a phase counts as solving time
+    let attributed: Duration = phases.iter().map(|(_, d)| *d).sum();
+    let mut parts = phases
+        .iter()
+        .map(|(phase, d)| format!("{}: {:.2?}", phase, d))
+        .collect::<Vec<_>>();
+    if !phases.is_empty() {
+        parts.push(format!("solve: {:.2?}", total.saturating_sub(attributed)));
+    }
+    parts.insert(0, format!("total: {:.2?}", total));
+    parts.join(", ")
+}
+
+/// Result of a single part, as printed in the machine readable output format.
+///
+/// Front-ends like `aoc` start the day binaries with `--machine-readable` and parse these lines
+/// instead of the human readable output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartReport {
+    pub part: u8,
+    pub answer: String,
+    pub total: Duration,
+}
+
+const REPORT_PREFIX: &str = "AOC-REPORT";
+
+impl PartReport {
+    pub fn to_machine_line(&self) -> String {
+        format!(
+            "{}\t{}\t{}\t{}",
+            REPORT_PREFIX,
+            self.part,
+            self.total.as_nanos(),
+            self.answer
+        )
+    }
+
+    pub fn parse_machine_line(line: &str) -> Option<Self> {
+        let mut fields = line.splitn(4, '\t');
+        if fields.next()? != REPORT_PREFIX {
+            return None;
+        }
+        let part = fields.next()?.parse().ok()?;
+        let total = Duration::from_nanos(fields.next()?.parse().ok()?);
+        let answer = fields.next()?.to_owned();
+        Some(Self {
+            part,
+            answer,
+            total,
+        })
+    }
+}
+
+fn run_part(part: u8, solver: Solver, input: &Path) -> Result<Duration> {
+    take_phases();
+    #[cfg(feature = "alloc-tracking")]
+    let heap_baseline = crate::alloc_tracking::reset_peak();
+    let start = Instant::now();
+    let answer = solver(input)?;
+    let total = start.elapsed();
+    if options().flag("machine-readable") {
+        let report = PartReport {
+            part,
+            answer: answer.to_string(),
+            total,
+        };
+        println!("{}", report.to_machine_line());
+        return Ok(total);
+    }
+    #[allow(unused_mut)]
+    let mut report = format_timings(total, &take_phases());
+    #[cfg(feature = "alloc-tracking")]
+    report.push_str(&format!(
+        ", peak heap: {}",
+        crate::alloc_tracking::format_bytes(crate::alloc_tracking::peak() - heap_baseline)
+    ));
+    println!("Answer for part {}: {} ({})", part, answer, report);
+    Ok(total)
+}
+
+/// Runs both parts of the given day.
+///
+/// The input is read from `--input PATH` if given, otherwise from the conventional location for
+/// the day, where `--year` can override the year the day belongs to. `--algo` selects an
+/// alternative implementation for the parts that provide one and `--part` restricts the run to a
+/// single part.
+///
+/// Timings of runs on the conventional input are appended to the timing history (see
+/// [`history`]) unless `--no-record` is given.
+pub fn run<D: AocDay>() -> Result<()> {
+    // Ignoring the error is fine here, it only means that the options were already set
+    let _ = OPTIONS.set(Options::from_env()?);
+    let options = options();
+    let year = options.get("year")?.unwrap_or(D::YEAR);
+    let custom_input = options.get::<PathBuf>("input")?;
+    // Timings on other inputs can't be compared, so only the conventional one is recorded
+    let record = custom_input.is_none() && !options.flag("no-record");
+    let input = custom_input.unwrap_or_else(|| input_path(year, D::DAY));
+    if !input.is_file() {
+        bail!("Input file {} does not exist", input.display());
+    }
+    let algo = options.get::<String>("algo")?;
+    if let Some(algo) = &algo {
+        check_algorithm::<D>(algo)?;
+    }
+    let algo = algo.as_deref();
+    let part = options.get::<u8>("part")?;
+    if !matches!(part, None | Some(1) | Some(2)) {
+        bail!("Invalid part {}, expected 1 or 2", part.unwrap());
+    }
+    let mut timings = Vec::new();
+    if part != Some(2) {
+        let (name, solver) = select_solver(D::part1, &D::part1_alternatives(), algo);
+        timings.push((1, name, run_part(1, solver, &input)?));
+    }
+    if part != Some(1) {
+        let (name, solver) = select_solver(D::part2, &D::part2_alternatives(), algo);
+        timings.push((2, name, run_part(2, solver, &input)?));
+    }
+
+    if record {
+        let timestamp = history::now_timestamp();
+        let commit = history::current_commit();
+        let records = timings
+            .into_iter()
+            .map(|(part, algorithm, total)| TimingRecord {
+                timestamp,
+                commit: commit.clone(),
+                profile: history::current_profile().to_owned(),
+                year,
+                day: D::DAY,
+                part,
+                algorithm: algorithm.to_owned(),
+                total_ns: total.as_nanos().try_into().unwrap_or(u64::MAX),
+            })
+            .collect::<Vec<_>>();
+        // A broken history should never hide the answers, so this only warns
+        if let Err(e) = history::append(history::history_path(), &records) {
+            eprintln!("Could not record timings: {:#}", e);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct TestDay;
+
+    impl AocDay for TestDay {
+        const YEAR: u16 = 2023;
+        const DAY: u8 = 0;
+
+        fn part1(_: &Path) -> Result<Answer> {
+            Ok(1.into())
+        }
+
+        fn part2(_: &Path) -> Result<Answer> {
+            Ok(2.into())
+        }
+
+        fn part1_alternatives() -> Vec<(&'static str, Solver)> {
+            vec![("naive", |_| Ok(10.into()))]
+        }
+    }
+
+    #[test]
+    fn test_algorithm_selection() {
+        let input = Path::new("unused");
+        let run = |(_, solver): (&str, Solver)| solver(input).unwrap();
+
+        let alternatives = TestDay::part1_alternatives();
+        assert_eq!(
+            run(select_solver(TestDay::part1, &alternatives, None)),
+            1.into()
+        );
+        assert_eq!(
+            run(select_solver(TestDay::part1, &alternatives, Some("naive"))),
+            10.into()
+        );
+        // Parts without the requested implementation keep using their default one
+        assert_eq!(
+            run(select_solver(TestDay::part2, &[], Some("naive"))),
+            2.into()
+        );
+        assert_eq!(
+            select_solver(TestDay::part2, &[], Some("naive")).0,
+            DEFAULT_ALGORITHM
+        );
+
+        assert!(check_algorithm::<TestDay>("naive").is_ok());
+        assert!(check_algorithm::<TestDay>(DEFAULT_ALGORITHM).is_ok());
+        assert!(check_algorithm::<TestDay>("fancy").is_err());
+    }
+
+    #[test]
+    fn test_machine_readable_report() {
+        let report = PartReport {
+            part: 2,
+            answer: "some text\twith tab".to_owned(),
+            total: Duration::from_micros(1500),
+        };
+        let line = report.to_machine_line();
+        assert_eq!(line, "AOC-REPORT\t2\t1500000\tsome text\twith tab");
+        assert_eq!(PartReport::parse_machine_line(&line), Some(report));
+        assert_eq!(PartReport::parse_machine_line("Answer for part 1: 5"), None);
+    }
+
+    #[test]
+    fn test_input_path() {
+        assert_eq!(
+            input_path(2024, 3),
+            Path::new("input").join("2024").join("day03.txt")
+        );
+    }
+}
@@ -0,0 +1,183 @@
+use std::collections::{BTreeSet, HashMap};
+use std::convert::Infallible;
+use std::ops::Range;
+use std::str::FromStr;
+
+/// A number in the schematic, spanning the columns `cols` of row `row`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Number {
+    pub value: usize,
+    pub row: usize,
+    pub cols: Range<usize>,
+}
+
+/// Which characters of a schematic count as symbols.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum SymbolClass {
+    /// Everything that is neither empty space (`.`), a digit nor whitespace
+    #[default]
+    Any,
+    OneOf(BTreeSet<char>),
+}
+
+impl SymbolClass {
+    pub fn contains(&self, c: char) -> bool {
+        match self {
+            SymbolClass::Any => !(c.is_whitespace() || c == '.' || c.is_ascii_digit()),
+            SymbolClass::OneOf(chars) => chars.contains(&c),
+        }
+    }
+}
+
+/// Parses `any` or the characters of the class, e.g. `*#` for stars and hashes.
+impl FromStr for SymbolClass {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Infallible> {
+        Ok(if s == "any" {
+            SymbolClass::Any
+        } else {
+            SymbolClass::OneOf(s.chars().collect())
+        })
+    }
+}
+
+/// Grid of numbers and symbols as in the engine schematic of day 3, where `.` is empty space.
+#[derive(Debug, Clone)]
+pub struct Schematic {
+    lines: Vec<String>,
+    symbols: HashMap<(usize, usize), char>,
+}
+
+impl Schematic {
+    /// Parses the schematic with the default [`SymbolClass::Any`].
+    pub fn new(lines: Vec<String>) -> Self {
+        Self::with_symbols(lines, &SymbolClass::Any)
+    }
+
+    /// Parses the schematic, characters outside of the symbol class are ignored.
+    pub fn with_symbols(lines: Vec<String>, class: &SymbolClass) -> Self {
+        let mut symbols = HashMap::new();
+        for (y, line) in lines.iter().enumerate() {
+            for (x, c) in line.chars().enumerate() {
+                if class.contains(c) {
+                    symbols.insert((y, x), c);
+                }
+            }
+        }
+        Schematic { lines, symbols }
+    }
+
+    pub fn symbol_at(&self, y: usize, x: usize) -> Option<char> {
+        self.symbols.get(&(y, x)).copied()
+    }
+
+    /// All symbols as `(y, x, symbol)`, in no particular order
+    pub fn symbols(&self) -> impl Iterator<Item = (usize, usize, char)> + '_ {
+        self.symbols.iter().map(|(&(y, x), &c)| (y, x, c))
+    }
+
+    /// All numbers, row by row from left to right
+    pub fn numbers(&self) -> impl Iterator<Item = Number> + '_ {
+        self.lines.iter().enumerate().flat_map(|(row, line)| {
+            let bytes = line.as_bytes();
+            let mut x = 0;
+            std::iter::from_fn(move || {
+                while x < bytes.len() && !bytes[x].is_ascii_digit() {
+                    x += 1;
+                }
+                let start = x;
+                let mut value = 0;
+                while x < bytes.len() && bytes[x].is_ascii_digit() {
+                    value = value * 10 + usize::from(bytes[x] - b'0');
+                    x += 1;
+                }
+                (start < x).then_some(Number {
+                    value,
+                    row,
+                    cols: start..x,
+                })
+            })
+        })
+    }
+
+    /// Symbols in the cells around the number, including the diagonals, as `(y, x, symbol)`
+    pub fn adjacent_symbols<'a>(
+        &'a self,
+        number: &Number,
+    ) -> impl Iterator<Item = (usize, usize, char)> + 'a {
+        let cols = number.cols.start.saturating_sub(1)..=number.cols.end;
+        (number.row.saturating_sub(1)..=number.row + 1)
+            .flat_map(move |y| cols.clone().map(move |x| (y, x)))
+            .filter_map(|(y, x)| self.symbol_at(y, x).map(|c| (y, x, c)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schematic() -> Schematic {
+        Schematic::new(
+            ["467..114..", "...*......", "..35..633.", "......#..."]
+                .iter()
+                .map(|line| line.to_string())
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_numbers() {
+        let numbers = schematic().numbers().collect::<Vec<_>>();
+        assert_eq!(numbers.len(), 4);
+        assert_eq!(
+            numbers[0],
+            Number {
+                value: 467,
+                row: 0,
+                cols: 0..3
+            }
+        );
+        assert_eq!(
+            numbers[3],
+            Number {
+                value: 633,
+                row: 2,
+                cols: 6..9
+            }
+        );
+    }
+
+    #[test]
+    fn test_adjacent_symbols() {
+        let schematic = schematic();
+        let adjacent = schematic
+            .numbers()
+            .map(|number| schematic.adjacent_symbols(&number).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            adjacent,
+            vec![
+                vec![(1, 3, '*')],
+                vec![],
+                vec![(1, 3, '*')],
+                vec![(3, 6, '#')]
+            ]
+        );
+        assert_eq!(schematic.symbol_at(3, 6), Some('#'));
+        assert_eq!(schematic.symbols().count(), 2);
+    }
+
+    #[test]
+    fn test_symbol_classes() {
+        let class = "#+".parse::<SymbolClass>().unwrap();
+        assert!(class.contains('#') && !class.contains('*'));
+        assert_eq!("any".parse(), Ok(SymbolClass::Any));
+        assert!(SymbolClass::Any.contains('$') && !SymbolClass::Any.contains('.'));
+
+        let lines = schematic().lines;
+        let schematic = Schematic::with_symbols(lines, &class);
+        assert_eq!(schematic.symbol_at(1, 3), None);
+        assert_eq!(schematic.symbols().collect::<Vec<_>>(), vec![(3, 6, '#')]);
+    }
+}
@@ -0,0 +1,234 @@
+// Priority queues for shortest path searches. Dijkstra only needs the entry with the lowest
+// priority, which the heap finds for any priorities. With small integer edge weights, the popped
+// priorities never decrease and stay close together, so one bucket per priority needs no
+// comparisons at all. Without weights, a breadth-first search needs no queue at all, it just
+// expands one layer after the other.
+
+use std::{
+    cmp::Ordering,
+    collections::{BinaryHeap, HashSet},
+    hash::Hash,
+};
+
+/// A queue that pops the entries with the lowest priority first
+pub trait MinQueue<T> {
+    fn push(&mut self, priority: usize, item: T);
+    fn pop(&mut self) -> Option<(usize, T)>;
+}
+
+struct HeapEntry<T>(usize, T);
+
+impl<T> PartialEq for HeapEntry<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<T> Eq for HeapEntry<T> {}
+
+impl<T> PartialOrd for HeapEntry<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for HeapEntry<T> {
+    // Reversed, since the binary heap is a max heap
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.0.cmp(&self.0)
+    }
+}
+
+/// A [`BinaryHeap`] ordered by the priority only, so the items don't need to be comparable
+pub struct HeapQueue<T>(BinaryHeap<HeapEntry<T>>);
+
+impl<T> Default for HeapQueue<T> {
+    fn default() -> Self {
+        Self(BinaryHeap::new())
+    }
+}
+
+impl<T> MinQueue<T> for HeapQueue<T> {
+    fn push(&mut self, priority: usize, item: T) {
+        self.0.push(HeapEntry(priority, item));
+    }
+
+    fn pop(&mut self) -> Option<(usize, T)> {
+        self.0
+            .pop()
+            .map(|HeapEntry(priority, item)| (priority, item))
+    }
+}
+
+/// A monotone priority queue with one bucket per priority (Dial's algorithm).
+///
+/// Pushing and popping take constant time, apart from skipping over empty buckets, but no entry
+/// may have a lower priority than the last one popped, or pushing it panics. That holds for
+/// Dijkstra with non-negative edge weights. Entries with the same priority are popped in last in,
+/// first out order.
+pub struct BucketQueue<T> {
+    buckets: Vec<Vec<T>>,
+    current: usize,
+    len: usize,
+}
+
+impl<T> Default for BucketQueue<T> {
+    fn default() -> Self {
+        Self {
+            buckets: Vec::new(),
+            current: 0,
+            len: 0,
+        }
+    }
+}
+
+impl<T> BucketQueue<T> {
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl<T> MinQueue<T> for BucketQueue<T> {
+    fn push(&mut self, priority: usize, item: T) {
+        assert!(
+            priority >= self.current,
+            "Priority {} is lower than the last popped priority {}",
+            priority,
+            self.current
+        );
+        if priority >= self.buckets.len() {
+            self.buckets.resize_with(priority + 1, Vec::new);
+        }
+        self.buckets[priority].push(item);
+        self.len += 1;
+    }
+
+    fn pop(&mut self) -> Option<(usize, T)> {
+        if self.len == 0 {
+            return None;
+        }
+        while self.buckets[self.current].is_empty() {
+            self.current += 1;
+        }
+        let item = self.buckets[self.current].pop()?;
+        self.len -= 1;
+        Some((self.current, item))
+    }
+}
+
+/// The layers of a breadth-first search: the start nodes first, then all nodes one step away
+/// from them, and so on, each node in the first layer it is reached in.
+pub struct BfsLayers<N, F> {
+    visited: HashSet<N>,
+    layer: Vec<N>,
+    neighbors: F,
+}
+
+/// Breadth-first search from the start nodes, see [`BfsLayers`]. The search runs as long as the
+/// layers are requested, so it also works for infinite graphs.
+pub fn bfs_layers<N, F, I>(start: impl IntoIterator<Item = N>, neighbors: F) -> BfsLayers<N, F>
+where
+    N: Clone + Eq + Hash,
+    F: FnMut(&N) -> I,
+    I: IntoIterator<Item = N>,
+{
+    let mut visited = HashSet::new();
+    let layer = start
+        .into_iter()
+        .filter(|node| visited.insert(node.clone()))
+        .collect();
+    BfsLayers {
+        visited,
+        layer,
+        neighbors,
+    }
+}
+
+impl<N, F, I> Iterator for BfsLayers<N, F>
+where
+    N: Clone + Eq + Hash,
+    F: FnMut(&N) -> I,
+    I: IntoIterator<Item = N>,
+{
+    type Item = Vec<N>;
+
+    fn next(&mut self) -> Option<Vec<N>> {
+        if self.layer.is_empty() {
+            return None;
+        }
+        let mut next = Vec::new();
+        for node in &self.layer {
+            for neighbor in (self.neighbors)(node) {
+                if self.visited.insert(neighbor.clone()) {
+                    next.push(neighbor);
+                }
+            }
+        }
+        Some(std::mem::replace(&mut self.layer, next))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn drain<Q: MinQueue<char>>(queue: &mut Q) -> Vec<usize> {
+        std::iter::from_fn(|| queue.pop())
+            .map(|(priority, _)| priority)
+            .collect()
+    }
+
+    #[test]
+    fn test_queues_pop_lowest_first() {
+        let mut bucket_queue = BucketQueue::default();
+        let mut heap_queue = HeapQueue::default();
+        for (priority, item) in [(5, 'a'), (2, 'b'), (9, 'c'), (2, 'd'), (0, 'e')] {
+            bucket_queue.push(priority, item);
+            heap_queue.push(priority, item);
+        }
+        assert_eq!(bucket_queue.len(), 5);
+        assert_eq!(bucket_queue.pop(), Some((0, 'e')));
+        assert_eq!(heap_queue.pop().map(|(priority, _)| priority), Some(0));
+        // Pushing at the current priority is still allowed
+        bucket_queue.push(0, 'f');
+        heap_queue.push(0, 'f');
+        assert_eq!(drain(&mut bucket_queue), [0, 2, 2, 5, 9]);
+        assert_eq!(drain(&mut heap_queue), [0, 2, 2, 5, 9]);
+        assert!(bucket_queue.is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "Priority 1 is lower than the last popped priority 3")]
+    fn test_bucket_queue_is_monotone() {
+        let mut queue = BucketQueue::default();
+        queue.push(3, ());
+        queue.pop();
+        queue.push(1, ());
+    }
+
+    #[test]
+    fn test_bfs_layers() {
+        // A path 0 - 1 - 2 - 3 with a shortcut from 0 to 2
+        let edges = [(0, 1), (1, 2), (2, 3), (0, 2)];
+        let neighbors = |node: &u8| {
+            edges
+                .iter()
+                .filter_map(|&(a, b)| match (a == *node, b == *node) {
+                    (true, _) => Some(b),
+                    (_, true) => Some(a),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        };
+        let layers = bfs_layers([0], neighbors).collect::<Vec<_>>();
+        assert_eq!(layers, [vec![0], vec![1, 2], vec![3]]);
+
+        // The natural numbers, as far as they are requested
+        let mut numbers = bfs_layers([0u64], |n| [n + 1]);
+        assert_eq!(numbers.nth(1000), Some(vec![1000]));
+    }
+}
@@ -0,0 +1,67 @@
+// A thin wrapper around the Z3 SMT solver for puzzles that are easier to state as constraints
+// than to solve by hand. It serves as an oracle for the hand-rolled solutions, so it is only
+// built with the `z3` feature, which needs the Z3 library installed.
+
+use anyhow::{bail, Result};
+use z3::{Config, Context, SatResult, Solver};
+
+pub use z3::ast::{Ast, Bool, Int};
+
+/// Integer values for the named unknowns that satisfy all constraints returned by `build`, which
+/// gets the unknowns in the same order. `None` if there are no such values.
+pub fn solve_integers<F>(names: &[&str], build: F) -> Result<Option<Vec<i64>>>
+where
+    F: for<'ctx> FnOnce(&'ctx Context, &[Int<'ctx>]) -> Vec<Bool<'ctx>>,
+{
+    let context = Context::new(&Config::new());
+    let unknowns = names
+        .iter()
+        .map(|name| Int::new_const(&context, *name))
+        .collect::<Vec<_>>();
+    let solver = Solver::new(&context);
+    for constraint in build(&context, &unknowns) {
+        solver.assert(&constraint);
+    }
+    match solver.check() {
+        SatResult::Sat => {}
+        SatResult::Unsat => return Ok(None),
+        SatResult::Unknown => bail!("Z3 can't tell whether the constraints can be satisfied"),
+    }
+    let Some(model) = solver.get_model() else {
+        bail!("Z3 found a solution but no model for it");
+    };
+    let mut values = Vec::with_capacity(unknowns.len());
+    for (name, unknown) in names.iter().zip(&unknowns) {
+        let Some(value) = model.eval(unknown).and_then(|value| value.as_i64()) else {
+            bail!("The value of {} doesn't fit into 64 bits", name);
+        };
+        values.push(value);
+    }
+    Ok(Some(values))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_solve_integers() {
+        // x + y = 10, x - y = 4
+        let solution = solve_integers(&["x", "y"], |ctx, xy| {
+            let (x, y) = (&xy[0], &xy[1]);
+            vec![
+                (x + y)._eq(&Int::from_i64(ctx, 10)),
+                (x - y)._eq(&Int::from_i64(ctx, 4)),
+            ]
+        })
+        .unwrap();
+        assert_eq!(solution, Some(vec![7, 3]));
+
+        // 2x = 3 has no integer solution
+        let solution = solve_integers(&["x"], |ctx, x| {
+            vec![(&x[0] * &Int::from_i64(ctx, 2))._eq(&Int::from_i64(ctx, 3))]
+        })
+        .unwrap();
+        assert_eq!(solution, None);
+    }
+}
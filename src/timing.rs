@@ -0,0 +1,56 @@
+use std::{cell::RefCell, time::Duration};
+
+thread_local! {
+    static PHASES: RefCell<Vec<(&'static str, Duration)>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Evaluates the expression and records how long that took under the given phase name.
+///
+/// The recorded phases are collected by the runner after each part, e.g.
+/// `let map = timed!("parse", HeatLossMap::from_input(input))?;`
+#[macro_export]
+macro_rules! timed {
+    ($phase:expr, $e:expr) => {{
+        let start = ::std::time::Instant::now();
+        let result = $e;
+        $crate::timing::record_phase($phase, start.elapsed());
+        result
+    }};
+}
+
+pub fn record_phase(phase: &'static str, duration: Duration) {
+    PHASES.with(|phases| phases.borrow_mut().push((phase, duration)));
+}
+
+/// Returns all phases recorded on this thread since the last call, merging repeated phases.
+pub fn take_phases() -> Vec<(&'static str, Duration)> {
+    let recorded = PHASES.with(|phases| phases.take());
+    let mut merged: Vec<(&'static str, Duration)> = Vec::new();
+    for (phase, duration) in recorded {
+        match merged.iter_mut().find(|(p, _)| *p == phase) {
+            Some((_, total)) => *total += duration,
+            None => merged.push((phase, duration)),
+        }
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_phases_are_merged_and_reset() {
+        take_phases();
+        let v = crate::timed!("parse", 1 + 1);
+        crate::timed!("solve", ());
+        crate::timed!("parse", ());
+        assert_eq!(v, 2);
+        let phases = take_phases();
+        assert_eq!(
+            phases.iter().map(|(p, _)| *p).collect::<Vec<_>>(),
+            vec!["parse", "solve"]
+        );
+        assert!(take_phases().is_empty());
+    }
+}